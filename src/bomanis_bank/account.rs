@@ -1,20 +1,29 @@
-use chrono::NaiveDate;
-use std::borrow::Borrow;
+use chrono::{NaiveDate, NaiveDateTime};
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
-use std::cell::{RefCell, Ref};
+use std::cell::RefCell;
 
+use crate::bomanis_bank::business::{BusinessDetails, BusinessUser, BusinessUserID};
+use crate::bomanis_bank::currency::Currency;
 use crate::bomanis_bank::errors::BalanceError;
+use crate::bomanis_bank::onboarding::{ContactDetails, OnboardingStatus};
+use crate::bomanis_bank::passwords::PasswordHash;
+use crate::bomanis_bank::payments::Payee;
+use crate::bomanis_bank::profile::ProfileChange;
+use crate::bomanis_bank::totp::TwoFactor;
 
 // Type Aliases
 pub type MoneyAccountID = u32;
 pub type BankAccountID = u32;
 
 #[repr(u8)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum MoneyAccountType {
     Checking,
     Savings,
+    TermDeposit,
+    Loan,
+    Credit,
     Length,
 }
 pub struct MoneyAccount {
@@ -22,8 +31,12 @@ pub struct MoneyAccount {
     pub account_type: MoneyAccountType,
     pub id: MoneyAccountID,
     pub name: String,
-    pub balance: i32,
+    pub currency: Currency,
+    pub balance: i32, // minor units of `currency`
+    pub pending_balance: i32, // deposits not yet cleared
     pub can_overdraft: bool,
+    pub credit_limit: i32, // how far below zero the balance may go, zero for deposit accounts
+    pub locked: bool, // no customer deposits or withdrawals, e.g. a term deposit before maturity or a loan
 }
 
 impl MoneyAccount {
@@ -31,6 +44,9 @@ impl MoneyAccount {
         match self.account_type {
             MoneyAccountType::Checking => "Checkings Account",
             MoneyAccountType::Savings => "Savings Account",
+            MoneyAccountType::TermDeposit => "Term Deposit",
+            MoneyAccountType::Loan => "Loan Account",
+            MoneyAccountType::Credit => "Credit Card",
             MoneyAccountType::Length => "NONE",
         }
     }
@@ -39,6 +55,10 @@ impl MoneyAccount {
         return self.id;
     }
 
+    pub fn format_amount(self: &Self, amount: i32) -> String {
+        return self.currency.format(amount);
+    }
+
     pub fn get_balance(self: &Self) -> i32 {
         return self.balance;
     }
@@ -54,7 +74,10 @@ impl MoneyAccount {
     }
 
     pub fn subtract_balance(self: &mut Self, amount: i32) -> Result<i32, BalanceError> {
-        if self.balance - amount < 0 && !self.can_overdraft {
+        if self.locked {
+            return Err(BalanceError::Locked);
+        }
+        if self.balance - amount < -self.credit_limit && !self.can_overdraft {
             return Err(BalanceError::NotEnough);
         } else {
             self.balance -= amount;
@@ -62,6 +85,30 @@ impl MoneyAccount {
         }
     }
 
+    pub fn get_pending_balance(self: &Self) -> i32 {
+        return self.pending_balance;
+    }
+
+    pub fn add_pending_balance(self: &mut Self, amount: i32) -> i32 {
+        self.pending_balance += amount;
+        return self.pending_balance;
+    }
+
+    // Moves a cleared amount from pending into the available balance.
+    pub fn release_pending_balance(self: &mut Self, amount: i32) -> Result<i32, BalanceError> {
+        if amount > self.pending_balance {
+            return Err(BalanceError::PendingExceeded);
+        }
+        self.pending_balance -= amount;
+        self.balance += amount;
+        return Ok(self.balance)
+    }
+
+    pub fn remove_pending_balance(self: &mut Self, amount: i32) -> i32 {
+        self.pending_balance -= amount;
+        return self.pending_balance;
+    }
+
     pub fn get_bank_account(self: &Self) -> Rc<RefCell<BankAccount>> {
         // should always exist, or we have a problem...
         return self.linked_bank_account.upgrade().unwrap();
@@ -72,8 +119,22 @@ pub enum UserActions {
     Deposit,
     Withdraw,
     ShowBalance,
+    ShowHistory,
+    Statements,
+    ExportTransactions,
+    StandingOrders,
+    PayBill,
+    ManagePayees,
+    TermDeposits,
+    Loans,
+    CreditCards,
+    Profile,
     CreateSavings,
     CreateCheckings,
+    ManageUsers,
+    PaymentApprovals,
+    Children,
+    ChangePassword,
     Logout,
     Exit,
     Length,
@@ -85,8 +146,22 @@ impl UserActions {
             UserActions::Deposit => "Deposit money",
             UserActions::Withdraw => "Withdraw money",
             UserActions::ShowBalance => "Show balance",
+            UserActions::ShowHistory => "Show transaction history",
+            UserActions::Statements => "Statements",
+            UserActions::ExportTransactions => "Export transactions",
+            UserActions::StandingOrders => "Manage standing orders",
+            UserActions::PayBill => "Pay a bill",
+            UserActions::ManagePayees => "Manage payees",
+            UserActions::TermDeposits => "Manage term deposits",
+            UserActions::Loans => "Manage loans",
+            UserActions::CreditCards => "Manage credit cards",
+            UserActions::Profile => "Manage profile",
             UserActions::CreateSavings => "Create savings account",
             UserActions::CreateCheckings => "Create checkings account",
+            UserActions::ManageUsers => "Manage business users",
+            UserActions::PaymentApprovals => "Review payment approvals",
+            UserActions::Children => "Manage children's accounts",
+            UserActions::ChangePassword => "Change password",
             UserActions::Logout => "Logout",
             UserActions::Exit => "Exit",
             UserActions::Length => "NONE",
@@ -101,11 +176,24 @@ pub struct BankAccount {
     pub accounts: Vec<Rc<RefCell<MoneyAccount>>>,
     pub accounts_map: HashMap<MoneyAccountID, Weak<RefCell<MoneyAccount>>>,
     pub userid: String,
-    pub password: String, //TODO make hash
+    pub password: PasswordHash,
+    pub password_history: Vec<PasswordHash>, // oldest first, the current password last
+    pub password_changed_at: Option<NaiveDateTime>,
+    pub must_change_password: bool, // set when staff reset a password
+    pub two_factor: Option<TwoFactor>,
+    pub payees: Vec<Payee>,
+    pub contact: ContactDetails,
+    pub id_document: String,
+    pub onboarding_status: OnboardingStatus,
+    pub onboarding_note: Option<String>, // why verification did not pass, if it did not
+    pub profile_history: Vec<ProfileChange>,
+    pub business: Option<BusinessDetails>, // None for personal customers
+    pub business_users: Vec<BusinessUser>,
+    pub guardian: Option<BankAccountID>, // set while the customer is a minor
 }
 
 impl BankAccount {
-    pub fn new(account_number: u32, name: String, dob: NaiveDate, userid: String, password: String) -> Self {
+    pub fn new(account_number: u32, name: String, dob: NaiveDate, userid: String, password: PasswordHash) -> Self {
         return Self {
             id: account_number as BankAccountID,
             name: name,
@@ -113,10 +201,31 @@ impl BankAccount {
             accounts: vec!(),
             accounts_map: HashMap::new(),
             userid: userid,
+            password_history: vec![password.clone()],
             password: password,
+            password_changed_at: None,
+            must_change_password: false,
+            two_factor: None,
+            payees: vec![],
+            contact: ContactDetails { email: String::new(), phone: String::new(), address: String::new() },
+            id_document: String::new(),
+            onboarding_status: OnboardingStatus::Pending,
+            onboarding_note: None,
+            profile_history: vec![],
+            business: None,
+            business_users: vec![],
+            guardian: None,
         }
     }
 
+    pub fn business_user(self: &Self, id: BusinessUserID) -> Option<&BusinessUser> {
+        return self.business_users.iter().find(|user| user.id == id);
+    }
+
+    pub fn business_user_mut(self: &mut Self, id: BusinessUserID) -> Option<&mut BusinessUser> {
+        return self.business_users.iter_mut().find(|user| user.id == id);
+    }
+
     pub fn get_associated_money_account(self: &Self, id: MoneyAccountID) -> Option<Rc<RefCell<MoneyAccount>>> {
         return Some(self.accounts_map.get(&id)?.upgrade()?.clone());
     }
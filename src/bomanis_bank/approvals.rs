@@ -0,0 +1,335 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::audit::AuditActor;
+use crate::bomanis_bank::business::BusinessUserID;
+use crate::bomanis_bank::clearing::DepositID;
+use crate::bomanis_bank::currency::{Currency, CurrencyAmounts};
+use crate::bomanis_bank::ledger::{ReasonCode, TransactionID};
+use crate::bomanis_bank::payments::PayeeID;
+use crate::bomanis_bank::scheduler::Frequency;
+use crate::bomanis_bank::staff::StaffID;
+
+// Type Aliases
+pub type ApprovalID = u32;
+
+// Amounts are in minor units of the account's currency.
+#[derive(Copy, Clone)]
+pub struct ApprovalPolicy {
+    pub outflow_threshold: CurrencyAmounts, // withdrawals, bill payments, standing orders and transfers to others above this need a second person
+    pub adjustment_threshold: CurrencyAmounts, // manual adjustments, reversals, fee waivers and returned cheques above this either way need a second person
+    pub expires_after: Duration, // requests nobody has decided by then lapse
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        return ApprovalPolicy {
+            outflow_threshold: CurrencyAmounts::new([
+                (Currency::Usd, 500000),
+                (Currency::Eur, 500000),
+                (Currency::Gbp, 500000),
+                (Currency::Sek, 5000000),
+                (Currency::Chf, 500000),
+                (Currency::Jpy, 750000),
+            ]),
+            adjustment_threshold: CurrencyAmounts::new([
+                (Currency::Usd, 100000),
+                (Currency::Eur, 100000),
+                (Currency::Gbp, 100000),
+                (Currency::Sek, 1000000),
+                (Currency::Chf, 100000),
+                (Currency::Jpy, 150000),
+            ]),
+            expires_after: Duration::hours(48),
+        }
+    }
+}
+
+// Who asked for an operation or decided it. Nobody can approve their own request.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ApprovalParty {
+    Customer(BankAccountID),
+    BusinessUser(BankAccountID, BusinessUserID), // decided within the business, not by the bank
+    Staff(StaffID),
+}
+
+impl ApprovalParty {
+    pub fn description(self: &Self) -> String {
+        return self.audit_actor().description();
+    }
+
+    pub fn audit_actor(self: &Self) -> AuditActor {
+        match self {
+            ApprovalParty::Customer(id) => AuditActor::Customer(*id),
+            ApprovalParty::BusinessUser(owner, user) => AuditActor::BusinessUser(*owner, *user),
+            ApprovalParty::Staff(id) => AuditActor::Staff(*id),
+        }
+    }
+}
+
+// Who decides a request.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Reviewer {
+    Bank, // staff allowed to approve
+    Business(BankAccountID), // the business customer's own approvers
+    Guardian(BankAccountID), // a minor's guardian
+}
+
+// What happens to the money once approved.
+#[derive(Clone)]
+pub enum ApprovalOperation {
+    Withdrawal { id: MoneyAccountID, amount: i32 },
+    Adjustment { id: MoneyAccountID, amount: i32, reason: String },
+    // staff corrections, `amount` is what they post to the account
+    Reversal { transaction: TransactionID, id: MoneyAccountID, amount: i32, reason: ReasonCode },
+    FeeWaiver { transaction: TransactionID, id: MoneyAccountID, amount: i32 },
+    ChequeReturn { deposit: DepositID, id: MoneyAccountID, amount: i32 },
+    BillPayment { owner: BankAccountID, from: MoneyAccountID, payee: PayeeID, amount: i32, reference: Option<String> },
+    StandingOrder { owner: BankAccountID, from: MoneyAccountID, to: MoneyAccountID, amount: i32, frequency: Frequency, start: NaiveDate },
+}
+
+impl ApprovalOperation {
+    pub fn money_account(self: &Self) -> MoneyAccountID {
+        match self {
+            ApprovalOperation::Withdrawal { id, .. } | ApprovalOperation::Adjustment { id, .. } => *id,
+            ApprovalOperation::Reversal { id, .. } | ApprovalOperation::FeeWaiver { id, .. } | ApprovalOperation::ChequeReturn { id, .. } => *id,
+            ApprovalOperation::BillPayment { from, .. } | ApprovalOperation::StandingOrder { from, .. } => *from,
+        }
+    }
+
+    pub fn amount(self: &Self) -> i32 {
+        match self {
+            ApprovalOperation::Withdrawal { amount, .. } => -amount,
+            ApprovalOperation::Adjustment { amount, .. } | ApprovalOperation::Reversal { amount, .. } |
+            ApprovalOperation::FeeWaiver { amount, .. } | ApprovalOperation::ChequeReturn { amount, .. } => *amount,
+            ApprovalOperation::BillPayment { amount, .. } | ApprovalOperation::StandingOrder { amount, .. } => -amount,
+        }
+    }
+
+    // Staff corrections are decided by the bank, never by a business or a guardian.
+    pub fn is_staff_operation(self: &Self) -> bool {
+        return matches!(self, ApprovalOperation::Adjustment { .. } | ApprovalOperation::Reversal { .. } |
+            ApprovalOperation::FeeWaiver { .. } | ApprovalOperation::ChequeReturn { .. });
+    }
+
+    pub fn description(self: &Self) -> String {
+        match self {
+            ApprovalOperation::Withdrawal { id, .. } => format!("Withdrawal from {}", id),
+            ApprovalOperation::Adjustment { id, reason, .. } => format!("Manual adjustment on {}: {}", id, reason),
+            ApprovalOperation::Reversal { transaction, id, reason, .. } => format!("Reversal of #{} on {}: {}", transaction, id, reason.description()),
+            ApprovalOperation::FeeWaiver { transaction, id, .. } => format!("Refund of fee #{} on {}", transaction, id),
+            ApprovalOperation::ChequeReturn { deposit, id, .. } => format!("Returned cheque deposit #{} on {}", deposit, id),
+            ApprovalOperation::BillPayment { from, payee, .. } => format!("Payment from {} to payee {}", from, payee),
+            ApprovalOperation::StandingOrder { from, to, frequency, .. } => format!("{} standing order from {} to {}", frequency.description(), from, to),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+    Failed, // approved, but the money could not be moved
+}
+
+impl ApprovalStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "Pending approval",
+            ApprovalStatus::Approved => "Approved",
+            ApprovalStatus::Rejected => "Rejected",
+            ApprovalStatus::Expired => "Expired",
+            ApprovalStatus::Failed => "Approved but failed",
+        }
+    }
+}
+
+pub struct ApprovalRequest {
+    pub id: ApprovalID,
+    pub operation: ApprovalOperation,
+    pub requested_by: ApprovalParty,
+    pub reviewer: Reviewer,
+    pub requested_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub status: ApprovalStatus,
+    pub decided_by: Option<ApprovalParty>,
+    pub earlier_approvals: Vec<ApprovalParty>, // business approvers or guardians, before it went on to the bank
+    pub decided_at: Option<NaiveDateTime>,
+    pub note: Option<String>, // why it was rejected or failed
+    pub requester_notified: bool, // staff requesters see the outcome at their next console visit
+}
+
+impl ApprovalRequest {
+    pub fn is_pending(self: &Self) -> bool {
+        return self.status == ApprovalStatus::Pending;
+    }
+
+    // What the requester is told once it is decided.
+    pub fn outcome_message(self: &Self, amount: &str) -> String {
+        let note = self.note.as_ref().map(|note| format!(" ({})", note)).unwrap_or_default();
+        return format!("Request {} ({}, {}): {}{}.", self.id, self.operation.description(), amount, self.status.description().to_lowercase(), note);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::{ApprovalError, BalanceError, ClearingError, PaymentError, ReversalError, ScheduleError, StaffError};
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::StaffRole;
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // Monday 2 March 2026. Admin set up Sam, another admin, and Sue, a supervisor.
+    fn bank_with_staff() -> (Rc<ManualClock>, DatabaseHandler, StaffID, StaffID, StaffID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let sam = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Admin).unwrap();
+        let sue = database.create_staff(admin, String::from("sue"), String::from("Sue"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        return (clock, database, admin, sam, sue);
+    }
+
+    // A verified adult customer with one checking account.
+    fn open_customer(database: &mut DatabaseHandler, user_id: &str, currency: Currency) -> (BankAccountID, MoneyAccountID) {
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from(user_id),
+            password: SecretString::from(PASSWORD),
+            name: format!("Test {}", user_id),
+            dob: date(1990, 6, 15),
+            contact: ContactDetails { email: format!("{}@example.com", user_id), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: format!("{}1234", user_id.to_uppercase()),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, currency).borrow().id;
+        return (owner, id);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    fn last_entry(database: &DatabaseHandler, id: MoneyAccountID) -> TransactionID {
+        return database.get_transaction_history(id).last().unwrap().id;
+    }
+
+    #[test]
+    fn staff_cannot_approve_their_own_or_related_staffs_requests() {
+        let (_, mut database, admin, sam, sue) = bank_with_staff();
+        let (_, id) = open_customer(&mut database, "alice", Currency::Usd);
+        let puppet = database.create_staff(sam, String::from("puppet"), String::from("Puppet"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+
+        assert!(matches!(database.post_adjustment(sam, id, 200000, "Correction"), Err(StaffError::ApprovalRequired)));
+        let request = database.request_adjustment(sam, id, 200000, "Correction").unwrap();
+        assert!(matches!(database.approve_request(sam, request), Err(ApprovalError::SelfApproval)));
+        assert!(matches!(database.approve_request(puppet, request), Err(ApprovalError::RelatedStaff)));
+        assert!(matches!(database.approve_request(admin, request), Err(ApprovalError::RelatedStaff)));
+        assert!(database.approve_request(sue, request).is_ok());
+        assert_eq!(balance(&database, id), 200000);
+    }
+
+    #[test]
+    fn large_reversals_refunds_and_returned_cheques_need_a_second_person() {
+        let (_, mut database, _, sam, sue) = bank_with_staff();
+        let (_, id) = open_customer(&mut database, "alice", Currency::Usd);
+
+        database.deposit(id, 5000, DepositChannel::Cash, None).unwrap();
+        database.reverse_transaction(sam, last_entry(&database, id), None, ReasonCode::ProcessingError, None).unwrap();
+        database.deposit(id, 200000, DepositChannel::Cash, None).unwrap();
+        let deposit = last_entry(&database, id);
+        assert_eq!(database.reverse_transaction(sam, deposit, None, ReasonCode::ProcessingError, None), Err(ReversalError::ApprovalRequired));
+        let first = database.request_reversal(sam, deposit, None, ReasonCode::ProcessingError).unwrap();
+        let second = database.request_reversal(sam, deposit, None, ReasonCode::ProcessingError).unwrap();
+        assert!(matches!(database.approve_request(sam, first), Err(ApprovalError::SelfApproval)));
+        assert_eq!(balance(&database, id), 200000);
+        database.approve_request(sue, first).unwrap();
+        assert_eq!(balance(&database, id), 0);
+        // checked again when approved, the deposit has been reversed in the meantime
+        assert!(matches!(database.approve_request(sue, second), Err(ApprovalError::Failed)));
+        assert!(database.get_approval_request(second).unwrap().status == ApprovalStatus::Failed);
+
+        database.deposit(id, 300000, DepositChannel::Cheque, None).unwrap();
+        let cheque = database.get_pending_deposits(id)[0].id;
+        assert!(matches!(database.bounce_deposit(sam, cheque), Err(ClearingError::ApprovalRequired)));
+        let request = database.request_deposit_return(sam, cheque).unwrap();
+        assert!(database.get_approval_request(request).unwrap().operation.amount() == -300000);
+        database.approve_request(sue, request).unwrap();
+        assert!(database.get_pending_deposits(id).is_empty());
+    }
+
+    #[test]
+    fn fee_refunds_above_the_threshold_need_a_second_person() {
+        let (clock, mut database, _, sam, sue) = bank_with_staff();
+        let (_, id) = open_customer(&mut database, "alice", Currency::Usd);
+        database.deposit(id, 10000, DepositChannel::Cash, None).unwrap();
+        clock.advance(Duration::days(60)); // first charged cycle is April
+        database.assess_maintenance_fees();
+        let fee = last_entry(&database, id);
+        let mut policy = database.get_approval_policy();
+        policy.adjustment_threshold.set(Currency::Usd, 100);
+        database.set_approval_policy(policy);
+
+        assert_eq!(database.waive_fee(sam, fee), Err(ReversalError::ApprovalRequired));
+        let request = database.request_fee_waiver(sam, fee).unwrap();
+        assert!(matches!(database.approve_request(sam, request), Err(ApprovalError::SelfApproval)));
+        database.approve_request(sue, request).unwrap();
+        assert_eq!(balance(&database, id), 10000);
+        assert_eq!(database.waive_fee(sam, fee), Err(ReversalError::AlreadyReversed));
+    }
+
+    #[test]
+    fn undecided_request_expires() {
+        let (clock, mut database, _, _, sue) = bank_with_staff();
+        let (owner, id) = open_customer(&mut database, "alice", Currency::Usd);
+        database.deposit(id, 1000000, DepositChannel::Cash, None).unwrap();
+        assert!(matches!(database.withdraw(id, 600000, None), Err(BalanceError::ApprovalRequired)));
+        let request = database.request_withdrawal(owner, id, 600000).unwrap();
+        clock.advance(Duration::hours(48));
+        assert!(matches!(database.approve_request(sue, request), Err(ApprovalError::Expired)));
+        assert!(database.get_approval_request(request).unwrap().status == ApprovalStatus::Expired);
+        assert!(matches!(database.approve_request(sue, request), Err(ApprovalError::NotPending)));
+        assert_eq!(balance(&database, id), 1000000);
+    }
+
+    #[test]
+    fn large_payments_and_standing_orders_wait_for_the_bank() {
+        let (_, mut database, _, _, sue) = bank_with_staff();
+        let (owner, id) = open_customer(&mut database, "alice", Currency::Usd);
+        let (_, other) = open_customer(&mut database, "bob", Currency::Usd);
+        database.deposit(id, 2000000, DepositChannel::Cash, None).unwrap();
+        let payee = database.add_payee(owner, String::from("Landlord"), "GB82 WEST 1234 5698 7654 32", String::new()).unwrap();
+        let today = date(2026, 3, 2);
+
+        assert!(matches!(database.pay_bill(owner, id, payee, 600000, None, None), Err(PaymentError::ApprovalRequired)));
+        assert!(matches!(database.create_standing_order(owner, id, other, 600000, Frequency::MonthlyOnDay(2), today), Err(ScheduleError::ApprovalRequired)));
+        assert!(matches!(database.transfer(id, other, 600000, None), Err(BalanceError::ApprovalRequired)));
+
+        let payment = database.request_bill_payment(owner, id, payee, 600000, None).unwrap();
+        let order = database.request_standing_order(owner, id, other, 600000, Frequency::MonthlyOnDay(2), today).unwrap();
+        assert!(database.approve_request(sue, payment).is_ok());
+        assert!(database.approve_request(sue, order).is_ok());
+        assert_eq!(database.get_outbound_payments(owner).len(), 1);
+        assert_eq!(database.get_standing_orders(owner).len(), 1);
+
+        // same-currency limit in SEK is ten times higher
+        let (swede, kronor) = open_customer(&mut database, "sven", Currency::Sek);
+        let payee = database.add_payee(swede, String::from("Landlord"), "GB82 WEST 1234 5698 7654 32", String::new()).unwrap();
+        database.deposit(kronor, 2000000, DepositChannel::Cash, None).unwrap();
+        assert!(database.pay_bill(swede, kronor, payee, 600000, None, None).is_ok());
+    }
+}
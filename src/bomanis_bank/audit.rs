@@ -0,0 +1,326 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use chrono::NaiveDateTime;
+
+use crate::bomanis_bank::account::BankAccountID;
+use crate::bomanis_bank::business::BusinessUserID;
+use crate::bomanis_bank::staff::StaffID;
+use crate::bomanis_bank::integrity::{self, BrokenLinkReason, EntryHash, GENESIS_HASH};
+
+// Type Aliases
+pub type AuditEventID = u64;
+
+// Who did it.
+#[derive(Clone, PartialEq)]
+pub enum AuditActor {
+    Anonymous, // nobody logged in yet, e.g. registering
+    Customer(BankAccountID),
+    BusinessUser(BankAccountID, BusinessUserID), // someone acting for a business customer
+    Staff(StaffID),
+    System, // scheduled jobs
+}
+
+impl AuditActor {
+    pub fn description(self: &Self) -> String {
+        match self {
+            AuditActor::Anonymous => String::from("anonymous"),
+            AuditActor::Customer(id) => format!("customer {}", id),
+            AuditActor::BusinessUser(owner, user) => format!("customer {} user {}", owner, user),
+            AuditActor::Staff(id) => format!("staff {}", id),
+            AuditActor::System => String::from("system"),
+        }
+    }
+}
+
+// Where the request came from.
+#[derive(Copy, Clone, PartialEq)]
+pub enum AuditChannel {
+    Cli,
+    StaffConsole,
+    Api, // callers using DatabaseHandler directly
+    Scheduler,
+}
+
+impl AuditChannel {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            AuditChannel::Cli => "CLI",
+            AuditChannel::StaffConsole => "Staff console",
+            AuditChannel::Api => "API",
+            AuditChannel::Scheduler => "Scheduler",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum AuditAction {
+    Login,
+    SecondFactor,
+    Logout,
+    Registration,
+    OnboardingStatusChange,
+    MoneyAccountOpened,
+    PasswordChange,
+    ProfileChange,
+    TwoFactorChange,
+    BalanceChange,
+    StaffLogin,
+    StaffChange,
+    CustomerSearch,
+    CustomerViewed,
+    ManualAdjustment,
+    AccountLockChange,
+    PasswordReset,
+    IntegrityCheck,
+    AuditExport,
+    ApprovalRequested,
+    ApprovalDecision,
+    BusinessUserChange,
+    MinorConversion,
+}
+
+impl AuditAction {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            AuditAction::Login => "Login",
+            AuditAction::SecondFactor => "Second factor",
+            AuditAction::Logout => "Logout",
+            AuditAction::Registration => "Registration",
+            AuditAction::OnboardingStatusChange => "Onboarding status change",
+            AuditAction::MoneyAccountOpened => "Account opened",
+            AuditAction::PasswordChange => "Password change",
+            AuditAction::ProfileChange => "Profile change",
+            AuditAction::TwoFactorChange => "Two-factor change",
+            AuditAction::BalanceChange => "Balance change",
+            AuditAction::StaffLogin => "Staff login",
+            AuditAction::StaffChange => "Staff change",
+            AuditAction::CustomerSearch => "Customer search",
+            AuditAction::CustomerViewed => "Customer viewed",
+            AuditAction::ManualAdjustment => "Manual adjustment",
+            AuditAction::AccountLockChange => "Account lock change",
+            AuditAction::PasswordReset => "Password reset",
+            AuditAction::IntegrityCheck => "Integrity check",
+            AuditAction::AuditExport => "Audit export",
+            AuditAction::ApprovalRequested => "Approval requested",
+            AuditAction::ApprovalDecision => "Approval decision",
+            AuditAction::BusinessUserChange => "Business user change",
+            AuditAction::MinorConversion => "Minor account conversion",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "Success",
+            AuditOutcome::Failure => "Failure",
+        }
+    }
+}
+
+// Who is acting and through which channel, for everything recorded until it is changed.
+#[derive(Clone)]
+pub struct AuditContext {
+    pub actor: AuditActor,
+    pub channel: AuditChannel,
+}
+
+#[derive(Clone)]
+pub struct AuditEvent {
+    pub id: AuditEventID,
+    pub at: NaiveDateTime,
+    pub actor: AuditActor,
+    pub customer: Option<BankAccountID>, // whose data it concerns, if anyone's
+    pub channel: AuditChannel,
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub detail: String, // never secrets
+    pub prev_hash: EntryHash,
+    pub hash: EntryHash,
+}
+
+impl AuditEvent {
+    // What the hash covers.
+    pub fn content(self: &Self) -> String {
+        return format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}",
+            self.id, self.at.format("%Y-%m-%dT%H:%M:%S%.f"), integrity::text_field(&self.actor.description()),
+            self.customer.map(|id| id.to_string()).unwrap_or_default(), self.channel.description(),
+            self.action.description(), self.outcome.description(), integrity::text_field(&self.detail),
+        );
+    }
+}
+
+// Only ever appended to. There is deliberately no way to change or remove an event.
+pub struct AuditLog {
+    events: Vec<AuditEvent>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        return AuditLog { events: vec![] }
+    }
+
+    pub fn append(self: &mut Self, at: NaiveDateTime, context: &AuditContext, customer: Option<BankAccountID>, action: AuditAction, outcome: AuditOutcome, detail: String) -> AuditEventID {
+        let id = self.events.len() as AuditEventID + 1;
+        let mut event = AuditEvent {
+            id: id,
+            at: at,
+            actor: context.actor.clone(),
+            customer: customer,
+            channel: context.channel,
+            action: action,
+            outcome: outcome,
+            detail: detail,
+            prev_hash: self.head_hash(),
+            hash: GENESIS_HASH,
+        };
+        event.hash = integrity::chain_hash(&event.prev_hash, &event.content());
+        self.events.push(event);
+        return id;
+    }
+
+    pub fn head_hash(self: &Self) -> EntryHash {
+        return self.hash_after(self.events.len()).unwrap_or(GENESIS_HASH);
+    }
+
+    // Hash of the chain after its first `events` events.
+    pub fn hash_after(self: &Self, events: usize) -> Option<EntryHash> {
+        if events == 0 {
+            return Some(GENESIS_HASH);
+        }
+        return self.events.get(events - 1).map(|event| event.hash);
+    }
+
+    // 1-based position of the first event that has been tampered with.
+    pub fn first_broken_entry(self: &Self) -> Option<(usize, BrokenLinkReason)> {
+        let links: Vec<(EntryHash, EntryHash, EntryHash)> = self.events.iter()
+            .map(|event| (event.prev_hash, event.hash, integrity::chain_hash(&event.prev_hash, &event.content())))
+            .collect();
+        return integrity::first_broken_entry(&links);
+    }
+
+    pub fn events(self: &Self) -> &[AuditEvent] {
+        return &self.events;
+    }
+
+    // Events in [from, to), oldest first. Leave any filter out to match everything.
+    pub fn query(self: &Self, customer: Option<BankAccountID>, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Vec<AuditEvent> {
+        return self.events.iter()
+            .filter(|event| customer.is_none() || event.customer == customer)
+            .filter(|event| from.is_none_or(|from| event.at >= from))
+            .filter(|event| to.is_none_or(|to| event.at < to))
+            .cloned()
+            .collect();
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        return AuditLog::new();
+    }
+}
+
+// One row per event with a header, for compliance review.
+pub fn export_csv(events: &[AuditEvent], path: &Path) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "id,timestamp,actor,customer,channel,action,outcome,detail,hash")?;
+    for event in events {
+        writeln!(
+            file, "{},{},{},{},{},{},{},{},{}",
+            event.id,
+            event.at.format("%Y-%m-%dT%H:%M:%S"),
+            csv_field(&event.actor.description()),
+            event.customer.map(|id| id.to_string()).unwrap_or_default(),
+            event.channel.description(),
+            csv_field(event.action.description()),
+            event.outcome.description(),
+            csv_field(&event.detail),
+            integrity::to_hex(&event.hash),
+        )?;
+    }
+    return Ok(());
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return String::from(value);
+}
+
+// For user IDs that match nobody, which are often a password typed into the wrong field. Keeps
+// enough to spot repeated attempts against one ID without storing what was typed.
+pub fn redact_user_id(user_id: &str) -> String {
+    let length = user_id.chars().count();
+    let shown: String = user_id.chars().take(if length > 4 { 2 } else { 0 }).collect();
+    return format!("{}*** ({} characters)", shown, length);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::NaiveDate;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::StaffError;
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn log_with_three_events() -> AuditLog {
+        let context = AuditContext { actor: AuditActor::Anonymous, channel: AuditChannel::Cli };
+        let at = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let mut log = AuditLog::new();
+        log.append(at, &context, Some(123456), AuditAction::Login, AuditOutcome::Success, String::from("User ID \"alice\""));
+        log.append(at, &context, Some(123456), AuditAction::SecondFactor, AuditOutcome::Failure, String::from("Authentication code is not valid"));
+        log.append(at, &context, Some(123456), AuditAction::Logout, AuditOutcome::Success, String::new());
+        return log;
+    }
+
+    #[test]
+    fn edited_event_breaks_the_chain_at_that_event() {
+        let mut log = log_with_three_events();
+        assert!(log.first_broken_entry().is_none());
+        log.events[1].outcome = AuditOutcome::Success;
+        assert!(matches!(log.first_broken_entry(), Some((2, BrokenLinkReason::ContentChanged))));
+    }
+
+    #[test]
+    fn removed_event_breaks_the_chain_after_it() {
+        let mut log = log_with_three_events();
+        let head = log.head_hash();
+        log.events.remove(1);
+        assert!(matches!(log.first_broken_entry(), Some((2, BrokenLinkReason::ChainBroken))));
+        assert!(log.head_hash() == head); // which is why checkpoints keep the length too
+    }
+
+    #[test]
+    fn redacted_user_id_keeps_only_a_prefix() {
+        assert_eq!(redact_user_id("Correct-Horse-42"), "Co*** (16 characters)");
+        assert_eq!(redact_user_id("bob"), "*** (3 characters)");
+    }
+
+    #[test]
+    fn unknown_staff_user_id_is_not_logged_verbatim() {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let password = SecretString::from("Correct-Horse-42");
+        database.provision_admin(String::from("admin"), String::from("Admin"), password.clone()).unwrap();
+
+        // a password typed into the user ID field
+        assert_eq!(database.authenticate_staff("Correct-Horse-42", &password).err(), Some(StaffError::InvalidCredentials));
+        assert!(database.authenticate_staff("admin", &password).is_ok());
+        let details: Vec<String> = database.get_audit_events(None, None, None).into_iter().map(|event| event.detail).collect();
+        assert!(details.iter().all(|detail| !detail.contains("Correct-Horse-42")));
+        assert!(details.contains(&String::from("Unknown user ID \"Co*** (16 characters)\"")));
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::NaiveDateTime;
+use crate::bomanis_bank::passwords::PasswordHash;
+
+// Type Aliases
+pub type BusinessUserID = u32;
+
+#[derive(Clone)]
+pub struct BusinessDetails {
+    pub company_name: String,
+    pub registration_number: String,
+}
+
+// Each level can do everything the one before it can.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub enum BusinessPermission {
+    ViewOnly,
+    InitiatePayments,
+    ApprovePayments,
+}
+
+impl BusinessPermission {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            BusinessPermission::ViewOnly => "View only",
+            BusinessPermission::InitiatePayments => "Initiate payments",
+            BusinessPermission::ApprovePayments => "Approve payments",
+        }
+    }
+}
+
+pub const BUSINESS_PERMISSIONS: [BusinessPermission; 3] = [
+    BusinessPermission::ViewOnly,
+    BusinessPermission::InitiatePayments,
+    BusinessPermission::ApprovePayments,
+];
+
+// Someone acting for a business customer with their own login. The customer's own login
+// administers the users and is not limited.
+pub struct BusinessUser {
+    pub id: BusinessUserID,
+    pub user_id: String,
+    pub name: String,
+    pub password: PasswordHash,
+    pub password_history: Vec<PasswordHash>, // oldest first, the current password last
+    pub password_changed_at: Option<NaiveDateTime>,
+    pub must_change_password: bool, // new users start with a temporary password
+    pub permission: BusinessPermission,
+    pub spending_limit: Option<i32>, // per payment, in minor units. Above it another user has to approve
+    pub active: bool, // removed users keep their record for the audit trail
+}
+
+impl BusinessUser {
+    pub fn can_initiate(self: &Self) -> bool {
+        return self.active && self.permission >= BusinessPermission::InitiatePayments;
+    }
+
+    pub fn can_approve(self: &Self) -> bool {
+        return self.active && self.permission >= BusinessPermission::ApprovePayments;
+    }
+
+    pub fn within_limit(self: &Self, amount: i32) -> bool {
+        return self.spending_limit.is_none_or(|limit| amount <= limit);
+    }
+}
+
+// Company registration numbers: 6 to 12 letters and digits.
+pub fn is_valid_registration_number(registration_number: &str) -> bool {
+    let registration_number = registration_number.trim();
+    return (6..=12).contains(&registration_number.len()) && registration_number.chars().all(|c| c.is_ascii_alphanumeric());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::{Duration, NaiveDate};
+    use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID, MoneyAccountType};
+    use crate::bomanis_bank::approvals::{ApprovalOperation, ApprovalParty, Reviewer};
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::{ApprovalError, BusinessError};
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::StaffRole;
+    use crate::bomanis_bank::totp::TwoFactorPolicy;
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    // Monday 2 March 2026, with a verified business customer that has a USD operating account.
+    fn bank_with_business() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("acme"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Ada Acme"),
+            dob: NaiveDate::from_ymd_opt(1980, 1, 10).unwrap(),
+            contact: ContactDetails { email: String::from("acme@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ACME1234"),
+            business: Some(BusinessDetails { company_name: String::from("Acme Ltd"), registration_number: String::from("AB123456") }),
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Operating"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (clock, database, owner, id);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn business_user_payments_are_authorised_by_the_business_before_the_bank() {
+        let (_, mut database, owner, id) = bank_with_business();
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let sam = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        database.deposit(id, 2000000, DepositChannel::Cash, None).unwrap();
+        let (clerk, _) = database.add_business_user(owner, String::from("clerk"), String::from("Clerk"), BusinessPermission::InitiatePayments, Some(1000000)).unwrap();
+        let (boss, _) = database.add_business_user(owner, String::from("boss"), String::from("Boss"), BusinessPermission::ApprovePayments, None).unwrap();
+
+        assert!(database.authorize_business_payment(owner, clerk, &ApprovalOperation::Withdrawal { id: id, amount: 50000 }).is_ok());
+        // within the clerk's limit, but large enough that customers have to step up
+        assert!(matches!(database.authorize_business_payment(owner, clerk, &ApprovalOperation::Withdrawal { id: id, amount: 200000 }), Err(BusinessError::ApprovalRequired)));
+        // staff corrections are never the business's to decide
+        assert!(matches!(database.request_business_approval(owner, clerk, ApprovalOperation::Adjustment { id: id, amount: 600000, reason: String::from("Correction") }), Err(BusinessError::PermissionDenied)));
+
+        let request = database.request_business_approval(owner, clerk, ApprovalOperation::Withdrawal { id: id, amount: 600000 }).unwrap();
+        assert!(database.get_approval_request(request).unwrap().reviewer == Reviewer::Business(owner));
+        assert!(matches!(database.approve_business_request(owner, Some(boss), request), Err(ApprovalError::OverLimit)));
+        assert!(database.approve_business_request(owner, None, request).is_ok());
+
+        // the business has agreed, now the bank's threshold applies
+        let approval = database.get_approval_request(request).unwrap();
+        assert!(approval.reviewer == Reviewer::Bank);
+        assert!(approval.requested_by == ApprovalParty::BusinessUser(owner, clerk));
+        assert_eq!(balance(&database, id), 2000000);
+        assert!(database.approve_request(sam, request).is_ok());
+        assert_eq!(balance(&database, id), 1400000);
+    }
+
+    #[test]
+    fn business_user_within_their_limit_goes_straight_to_the_bank() {
+        let (_, mut database, owner, id) = bank_with_business();
+        database.deposit(id, 2000000, DepositChannel::Cash, None).unwrap();
+        let mut policy = TwoFactorPolicy::default();
+        policy.step_up_amount.set(Currency::Usd, 10000000);
+        database.set_two_factor_policy(policy).unwrap();
+        let (clerk, _) = database.add_business_user(owner, String::from("clerk"), String::from("Clerk"), BusinessPermission::InitiatePayments, Some(1000000)).unwrap();
+
+        let request = database.request_business_approval(owner, clerk, ApprovalOperation::Withdrawal { id: id, amount: 600000 }).unwrap();
+        let approval = database.get_approval_request(request).unwrap();
+        assert!(approval.reviewer == Reviewer::Bank);
+        assert!(approval.requested_by == ApprovalParty::BusinessUser(owner, clerk));
+    }
+
+    #[test]
+    fn business_user_passwords_keep_history_and_expire() {
+        let (clock, mut database, owner, _) = bank_with_business();
+        let (clerk, temporary_password) = database.add_business_user(owner, String::from("clerk"), String::from("Clerk"), BusinessPermission::ViewOnly, None).unwrap();
+        assert!(database.business_password_change_required(owner, clerk));
+
+        let first = SecretString::from("Battery-Staple-77");
+        assert!(database.change_business_user_password(owner, clerk, &temporary_password, first.clone()).is_ok());
+        assert!(!database.business_password_change_required(owner, clerk));
+        assert!(matches!(database.change_business_user_password(owner, clerk, &first, temporary_password.clone()), Err(BusinessError::WeakPassword)));
+        assert!(matches!(database.change_business_user_password(owner, clerk, &first, first.clone()), Err(BusinessError::WeakPassword)));
+
+        clock.advance(Duration::days(91));
+        assert!(database.business_password_change_required(owner, clerk));
+        assert!(database.change_business_user_password(owner, clerk, &first, SecretString::from("Purple-Monkey-19")).is_ok());
+        assert!(!database.business_password_change_required(owner, clerk));
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::bomanis_bank::account::MoneyAccountID;
+use crate::bomanis_bank::currency::{Currency, CurrencyAmounts};
+
+// Type Aliases
+pub type DepositID = u32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DepositChannel {
+    Cash,
+    Cheque,
+    Transfer,
+}
+
+impl DepositChannel {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            DepositChannel::Cash => "Cash",
+            DepositChannel::Cheque => "Cheque",
+            DepositChannel::Transfer => "Incoming transfer",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DepositStatus {
+    Pending,
+    Cleared,
+    Bounced,
+}
+
+// How long non-cash deposits are held before the funds become available.
+#[derive(Copy, Clone)]
+pub struct ClearingPolicy {
+    pub cheque_hold_days: u32, // business days
+    pub transfer_hold_days: u32, // business days
+    pub immediate_availability: CurrencyAmounts, // how much of each held deposit is available straight away
+}
+
+impl ClearingPolicy {
+    pub fn hold_days_for(self: &Self, channel: DepositChannel) -> u32 {
+        match channel {
+            DepositChannel::Cash => 0,
+            DepositChannel::Cheque => self.cheque_hold_days,
+            DepositChannel::Transfer => self.transfer_hold_days,
+        }
+    }
+}
+
+impl Default for ClearingPolicy {
+    fn default() -> Self {
+        return ClearingPolicy {
+            cheque_hold_days: 2,
+            transfer_hold_days: 1,
+            immediate_availability: CurrencyAmounts::new([
+                (Currency::Usd, 20000),
+                (Currency::Eur, 20000),
+                (Currency::Gbp, 20000),
+                (Currency::Sek, 200000),
+                (Currency::Chf, 20000),
+                (Currency::Jpy, 30000),
+            ]),
+        }
+    }
+}
+
+pub struct PendingDeposit {
+    pub id: DepositID,
+    pub money_account: MoneyAccountID,
+    pub channel: DepositChannel,
+    pub amount: i32, // full deposit amount
+    pub released_amount: i32, // portion already credited to the available balance
+    pub deposited_on: NaiveDate,
+    pub available_on: NaiveDate,
+    pub status: DepositStatus,
+}
+
+impl PendingDeposit {
+    pub fn held_amount(self: &Self) -> i32 {
+        return self.amount - self.released_amount;
+    }
+
+    pub fn is_due(self: &Self, today: NaiveDate) -> bool {
+        return self.status == DepositStatus::Pending && today >= self.available_on;
+    }
+}
+
+pub fn is_business_day(date: NaiveDate) -> bool {
+    return !matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+}
+
+// Weekends are skipped, public holidays are not tracked.
+pub fn add_business_days(date: NaiveDate, days: u32) -> NaiveDate {
+    let mut result = date;
+    let mut remaining = days;
+    while remaining > 0 {
+        result += Duration::days(1);
+        if is_business_day(result) {
+            remaining -= 1;
+        }
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::{BankAccountID, MoneyAccountType};
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::ClearingError;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::{StaffID, StaffRole};
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // Monday 2 March 2026, with one verified customer who has a checking account in `currency`.
+    fn bank_with_account(currency: Currency) -> (Rc<ManualClock>, DatabaseHandler, BankAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Alice Example"),
+            dob: date(1990, 6, 15),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, currency).borrow().id;
+        return (clock, database, owner, id);
+    }
+
+    // A supervisor, who may return cheques, and a teller, who may not.
+    fn back_office(database: &mut DatabaseHandler) -> (StaffID, StaffID) {
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let supervisor = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        let teller = database.create_staff(admin, String::from("tess"), String::from("Tess"), SecretString::from(PASSWORD), StaffRole::Teller).unwrap();
+        return (supervisor, teller);
+    }
+
+    // (available, held)
+    fn balances(database: &DatabaseHandler, id: MoneyAccountID) -> (i32, i32) {
+        let ptr_money_account = database.get_money_account_with_id_u32(id).unwrap();
+        let money_account = ptr_money_account.borrow();
+        return (money_account.get_balance(), money_account.get_pending_balance());
+    }
+
+    #[test]
+    fn business_days_skip_weekends() {
+        assert_eq!(add_business_days(date(2026, 3, 2), 2), date(2026, 3, 4)); // Monday to Wednesday
+        assert_eq!(add_business_days(date(2026, 3, 5), 2), date(2026, 3, 9)); // Thursday to Monday
+        assert_eq!(add_business_days(date(2026, 3, 7), 1), date(2026, 3, 9)); // Saturday to Monday
+        assert_eq!(add_business_days(date(2026, 3, 7), 0), date(2026, 3, 7));
+    }
+
+    #[test]
+    fn cash_is_never_held() {
+        let policy = ClearingPolicy::default();
+        assert_eq!(policy.hold_days_for(DepositChannel::Cash), 0);
+        assert_eq!(policy.hold_days_for(DepositChannel::Cheque), policy.cheque_hold_days);
+        assert_eq!(policy.hold_days_for(DepositChannel::Transfer), policy.transfer_hold_days);
+    }
+
+    #[test]
+    fn deposit_is_due_from_its_available_date() {
+        let deposit = PendingDeposit {
+            id: 1,
+            money_account: 123456,
+            channel: DepositChannel::Cheque,
+            amount: 50000,
+            released_amount: 20000,
+            deposited_on: date(2026, 3, 2),
+            available_on: date(2026, 3, 4),
+            status: DepositStatus::Pending,
+        };
+        assert_eq!(deposit.held_amount(), 30000);
+        assert!(!deposit.is_due(date(2026, 3, 3)));
+        assert!(deposit.is_due(date(2026, 3, 4)));
+        assert!(!PendingDeposit { status: DepositStatus::Bounced, ..deposit }.is_due(date(2026, 3, 5)));
+    }
+
+    #[test]
+    fn cheque_is_held_until_it_clears() {
+        let (clock, mut database, _, id) = bank_with_account(Currency::Usd);
+        assert_eq!(database.deposit(id, 50000, DepositChannel::Cheque, None), Ok(20000));
+        assert_eq!(balances(&database, id), (20000, 30000));
+
+        clock.advance(Duration::days(1));
+        database.process_clearing();
+        assert_eq!(balances(&database, id), (20000, 30000));
+
+        clock.advance(Duration::days(1));
+        database.process_clearing();
+        assert_eq!(balances(&database, id), (50000, 0));
+        assert!(database.get_pending_deposits(id).is_empty());
+    }
+
+    #[test]
+    fn cash_is_available_straight_away() {
+        let (_, mut database, _, id) = bank_with_account(Currency::Usd);
+        assert_eq!(database.deposit(id, 50000, DepositChannel::Cash, None), Ok(50000));
+        assert_eq!(balances(&database, id), (50000, 0));
+    }
+
+    #[test]
+    fn returned_cheque_takes_back_what_was_released() {
+        let (clock, mut database, owner, id) = bank_with_account(Currency::Usd);
+        let (supervisor, teller) = back_office(&mut database);
+        database.deposit(id, 50000, DepositChannel::Cheque, None).unwrap();
+        let deposit_id = database.get_pending_deposits(id)[0].id;
+
+        assert!(matches!(database.bounce_deposit(teller, deposit_id), Err(ClearingError::PermissionDenied)));
+        let fee = database.fee_schedule_for(id).unwrap().returned_payment_fee("").map_or(0, |fee| fee.amount);
+        assert!(matches!(database.bounce_deposit(supervisor, deposit_id), Ok(balance) if balance == -fee));
+        assert_eq!(balances(&database, id), (-fee, 0));
+        assert!(database.take_notifications(owner).iter().any(|message| message.contains("returned unpaid")));
+
+        // nothing left to clear, and it cannot be returned twice
+        clock.advance(Duration::days(2));
+        database.process_clearing();
+        assert_eq!(balances(&database, id), (-fee, 0));
+        assert!(matches!(database.bounce_deposit(supervisor, deposit_id), Err(ClearingError::AlreadySettled)));
+    }
+
+    #[test]
+    fn only_cheques_can_be_returned() {
+        let (_, mut database, _, id) = bank_with_account(Currency::Usd);
+        let (supervisor, _) = back_office(&mut database);
+        database.deposit(id, 50000, DepositChannel::Transfer, None).unwrap();
+        let deposit_id = database.get_pending_deposits(id)[0].id;
+        assert!(matches!(database.bounce_deposit(supervisor, deposit_id), Err(ClearingError::NotBounceable)));
+    }
+
+    #[test]
+    fn immediate_availability_is_set_per_currency() {
+        for (currency, amount) in [(Currency::Sek, 500000), (Currency::Jpy, 50000)] {
+            let (_, mut database, _, id) = bank_with_account(currency);
+            let policy = database.get_clearing_policy();
+            database.deposit(id, amount, DepositChannel::Cheque, None).unwrap();
+            let available = policy.immediate_availability.get(currency);
+            assert_eq!(balances(&database, id), (available, amount - available));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::cell::Cell;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+
+// Source of "now" for everything time dependent, so it can be pinned or moved in simulations.
+pub trait Clock {
+    fn now(self: &Self) -> NaiveDateTime;
+
+    fn today(self: &Self) -> NaiveDate {
+        return self.now().date();
+    }
+
+    // Seconds since the Unix epoch, reading `now` as local time.
+    fn unix_timestamp(self: &Self) -> i64 {
+        let now = self.now();
+        match Local.from_local_datetime(&now).earliest() {
+            Some(local) => return local.timestamp(),
+            None => return now.and_utc().timestamp(), // inside a DST gap
+        }
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(self: &Self) -> NaiveDateTime {
+        return Local::now().naive_local();
+    }
+}
+
+// Always reports the same instant.
+pub struct FixedClock {
+    now: NaiveDateTime,
+}
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        return FixedClock { now: now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(self: &Self) -> NaiveDateTime {
+        return self.now;
+    }
+}
+
+// Stands still until told to move. Shared behind an Rc so a simulation can keep a handle to it.
+pub struct ManualClock {
+    now: Cell<NaiveDateTime>,
+}
+
+impl ManualClock {
+    pub fn new(start: NaiveDateTime) -> Self {
+        return ManualClock { now: Cell::new(start) }
+    }
+
+    pub fn set(self: &Self, now: NaiveDateTime) {
+        self.now.set(now);
+    }
+
+    pub fn advance(self: &Self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(self: &Self) -> NaiveDateTime {
+        return self.now.get();
+    }
+}
+
+// Source of randomness for account numbers and generated secrets.
+pub trait RandomSource {
+    // Uniform in [low, high).
+    fn gen_range(self: &mut Self, low: u32, high: u32) -> u32;
+    fn fill_bytes(self: &mut Self, dest: &mut [u8]);
+}
+
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn gen_range(self: &mut Self, low: u32, high: u32) -> u32 {
+        return rand::thread_rng().gen_range(low..high);
+    }
+
+    fn fill_bytes(self: &mut Self, dest: &mut [u8]) {
+        rand::thread_rng().fill_bytes(dest);
+    }
+}
+
+// Same seed, same sequence.
+pub struct SeededRandom {
+    rng: StdRng,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        return SeededRandom { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn gen_range(self: &mut Self, low: u32, high: u32) -> u32 {
+        return self.rng.gen_range(low..high);
+    }
+
+    fn fill_bytes(self: &mut Self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let draw = |seed: u64| {
+            let mut random = SeededRandom::new(seed);
+            let mut bytes = [0u8; 16];
+            random.fill_bytes(&mut bytes);
+            return ((0..8).map(|_| random.gen_range(0, 999999)).collect::<Vec<u32>>(), bytes);
+        };
+        assert_eq!(draw(7), draw(7));
+        assert_ne!(draw(7), draw(8));
+        assert!(draw(7).0.iter().all(|number| *number < 999999));
+    }
+
+    #[test]
+    fn manual_clock_moves_only_when_told() {
+        let clock = ManualClock::new(at(2, 9));
+        assert_eq!(clock.now(), at(2, 9));
+        assert_eq!(clock.now(), at(2, 9));
+        clock.advance(Duration::hours(27));
+        assert_eq!(clock.now(), at(3, 12));
+        assert_eq!(clock.today(), NaiveDate::from_ymd_opt(2026, 3, 3).unwrap());
+        clock.set(at(1, 0));
+        assert_eq!(clock.now(), at(1, 0));
+    }
+}
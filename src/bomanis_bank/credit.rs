@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::term_deposits::add_months;
+
+// Which part of the balance a payment pays down first, after interest and fees.
+#[derive(Copy, Clone, PartialEq)]
+pub enum PaymentAllocation {
+    HighestRateFirst,
+    LowestRateFirst,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CreditSegment {
+    Purchase,
+    CashAdvance,
+    InterestAndFees,
+}
+
+#[derive(Copy, Clone)]
+pub struct CreditTerms {
+    pub limit: i32, // minor units of the account's currency
+    pub purchase_apr: f64, // e.g. 0.2199 for 21.99%
+    pub cash_advance_apr: f64,
+}
+
+#[derive(Copy, Clone)]
+pub struct CreditPolicy {
+    pub max_limit: i32,
+    pub purchase_apr: f64, // offered to customers opening a card in the session
+    pub cash_advance_apr: f64,
+    pub payment_due_days: u32, // days from the statement date to the payment due date
+    pub min_payment_rate: f64, // share of the statement balance due, on top of the cycle's interest and fees
+    pub min_payment_floor: i32,
+    pub late_fee: i32,
+    pub allocation: PaymentAllocation,
+}
+
+impl Default for CreditPolicy {
+    fn default() -> Self {
+        return CreditPolicy {
+            max_limit: 1000000,
+            purchase_apr: 0.2199,
+            cash_advance_apr: 0.2799,
+            payment_due_days: 25,
+            min_payment_rate: 0.01,
+            min_payment_floor: 2500,
+            late_fee: 2900,
+            allocation: PaymentAllocation::HighestRateFirst,
+        }
+    }
+}
+
+pub struct CreditStatement {
+    pub closing_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub balance: i32, // owed at closing
+    pub minimum_payment: i32,
+    pub interest_charged: i32,
+    pub payments: i32, // received since closing
+    pub paid_by_due_date: Option<bool>, // known once the due date has passed
+}
+
+impl CreditStatement {
+    pub fn minimum_outstanding(self: &Self) -> i32 {
+        return (self.minimum_payment - self.payments).max(0);
+    }
+}
+
+pub struct CreditLine {
+    pub money_account: MoneyAccountID,
+    pub owner: BankAccountID,
+    pub terms: CreditTerms,
+    pub opened_on: NaiveDate,
+    pub purchase_balance: i32,
+    pub cash_advance_balance: i32,
+    pub interest_and_fees_balance: i32,
+    pub accrued_purchase_interest: f64, // minor units, not yet charged
+    pub accrued_cash_advance_interest: f64,
+    pub accrued_through: NaiveDate, // interest has accrued for every day before this
+    pub statements: Vec<CreditStatement>,
+}
+
+impl CreditLine {
+    pub fn next_statement_date(self: &Self) -> NaiveDate {
+        return add_months(self.opened_on, self.statements.len() as u32 + 1).unwrap_or(NaiveDate::MAX);
+    }
+
+    pub fn last_statement(self: &Self) -> Option<&CreditStatement> {
+        return self.statements.last();
+    }
+
+    // Purchases are interest free for the cycle if the previous statement was paid in full by its due date.
+    pub fn grace_period_applies(self: &Self) -> bool {
+        match self.last_statement() {
+            Some(statement) => statement.balance <= 0 || statement.paid_by_due_date.unwrap_or(statement.payments >= statement.balance),
+            None => true,
+        }
+    }
+
+    pub fn add_charge(self: &mut Self, segment: CreditSegment, amount: i32) {
+        match segment {
+            CreditSegment::Purchase => self.purchase_balance += amount,
+            CreditSegment::CashAdvance => self.cash_advance_balance += amount,
+            CreditSegment::InterestAndFees => self.interest_and_fees_balance += amount,
+        }
+    }
+
+    // Interest and fees are always paid first, then the segments in the order the policy sets.
+    // Anything left over is simply a credit balance on the account.
+    pub fn apply_payment(self: &mut Self, amount: i32, allocation: PaymentAllocation) {
+        let cash_first = match allocation {
+            PaymentAllocation::HighestRateFirst => self.terms.cash_advance_apr >= self.terms.purchase_apr,
+            PaymentAllocation::LowestRateFirst => self.terms.cash_advance_apr < self.terms.purchase_apr,
+        };
+        let order = if cash_first {
+            [CreditSegment::InterestAndFees, CreditSegment::CashAdvance, CreditSegment::Purchase]
+        } else {
+            [CreditSegment::InterestAndFees, CreditSegment::Purchase, CreditSegment::CashAdvance]
+        };
+        let mut remaining = amount;
+        for segment in order {
+            let balance = match segment {
+                CreditSegment::Purchase => &mut self.purchase_balance,
+                CreditSegment::CashAdvance => &mut self.cash_advance_balance,
+                CreditSegment::InterestAndFees => &mut self.interest_and_fees_balance,
+            };
+            let paid = remaining.min(*balance).max(0);
+            *balance -= paid;
+            remaining -= paid;
+        }
+        if let Some(statement) = self.statements.last_mut() {
+            statement.payments += amount;
+        }
+    }
+
+    // One day's interest on each segment at its own rate.
+    pub fn accrue_day(self: &mut Self) {
+        self.accrued_purchase_interest += self.purchase_balance.max(0) as f64 * self.terms.purchase_apr / 365.0;
+        self.accrued_cash_advance_interest += self.cash_advance_balance.max(0) as f64 * self.terms.cash_advance_apr / 365.0;
+    }
+}
+
+pub fn minimum_payment(balance: i32, interest_and_fees: i32, policy: &CreditPolicy) -> i32 {
+    if balance <= 0 {
+        return 0;
+    }
+    let minimum = (balance as f64 * policy.min_payment_rate).round() as i32 + interest_and_fees;
+    return minimum.max(policy.min_payment_floor).min(balance);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::Duration;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    #[test]
+    fn credit_statement_closes_with_interest_and_a_minimum_payment() {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let owner = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap().borrow().id;
+        let card = database.open_credit_line(owner, String::from("Card"), Currency::Usd, CreditTerms { limit: 200000, purchase_apr: 0.2, cash_advance_apr: 0.25 }).unwrap();
+        database.card_purchase(card, 50000, "Shop", None).unwrap();
+        clock.advance(Duration::days(40));
+        database.process_credit_lines();
+
+        let statement = &database.get_credit_line(card).unwrap().statements[0];
+        assert_eq!(statement.balance, 50000);
+        assert_eq!(statement.interest_charged, 0); // first statement is inside the grace period
+        assert!(statement.minimum_payment > 0 && statement.minimum_payment <= statement.balance);
+    }
+}
@@ -0,0 +1,347 @@
+use std::fs;
+use std::path::Path;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::bomanis_bank::errors::CurrencyError;
+
+// Amounts throughout the bank are integers in the minor unit of the account's currency
+// (cents for USD, whole yen for JPY).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Currency {
+    Usd,
+    Eur,
+    Gbp,
+    Sek,
+    Chf,
+    Jpy,
+}
+
+pub const SUPPORTED_CURRENCIES: [Currency; 6] = [
+    Currency::Usd,
+    Currency::Eur,
+    Currency::Gbp,
+    Currency::Sek,
+    Currency::Chf,
+    Currency::Jpy,
+];
+
+impl Currency {
+    pub fn code(self: &Self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Sek => "SEK",
+            Currency::Chf => "CHF",
+            Currency::Jpy => "JPY",
+        }
+    }
+
+    pub fn symbol(self: &Self) -> &'static str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+            Currency::Sek => "kr ",
+            Currency::Chf => "CHF ",
+            Currency::Jpy => "¥",
+        }
+    }
+
+    pub fn minor_units(self: &Self) -> u32 {
+        match self {
+            Currency::Jpy => 0,
+            _ => 2,
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Currency> {
+        let code = code.trim().to_uppercase();
+        return SUPPORTED_CURRENCIES.iter().copied().find(|currency| currency.code() == code);
+    }
+
+    // e.g. 123456 in USD is "$1234.56", 123456 in JPY is "¥123456".
+    pub fn format(self: &Self, amount: i32) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let abs = (amount as i64).abs();
+        let minor_units = self.minor_units();
+        if minor_units == 0 {
+            return format!("{}{}{}", sign, self.symbol(), abs);
+        }
+        let scale = 10_i64.pow(minor_units);
+        return format!("{}{}{}.{:0width$}", sign, self.symbol(), abs / scale, abs % scale, width = minor_units as usize);
+    }
+
+    // e.g. 123456 in USD is "1234.56", for files other programs read.
+    pub fn format_decimal(self: &Self, amount: i32) -> String {
+        return self.format(amount).replacen(self.symbol(), "", 1);
+    }
+
+    // Reads "12", "12.5" or "12.50" as minor units. More decimals than the currency has are rejected.
+    pub fn parse_amount(self: &Self, input: &str) -> Option<i32> {
+        let input = input.trim().trim_start_matches(self.symbol().trim());
+        let (negative, input) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let (major_str, minor_str) = match input.split_once('.') {
+            Some((major, minor)) => (major, minor),
+            None => (input, ""),
+        };
+        let minor_units = self.minor_units() as usize;
+        if major_str.is_empty() || minor_str.len() > minor_units
+            || !major_str.chars().all(|c| c.is_ascii_digit()) || !minor_str.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let major: i32 = major_str.parse().ok()?;
+        let minor: i32 = if minor_str.is_empty() { 0 } else { format!("{:0<width$}", minor_str, width = minor_units).parse().ok()? };
+        let amount = major.checked_mul(10_i32.pow(minor_units as u32))?.checked_add(minor)?;
+        return Some(if negative { -amount } else { amount });
+    }
+}
+
+// A limit or threshold set separately for each currency, since the same number of minor units
+// is worth very different amounts in each.
+#[derive(Copy, Clone)]
+pub struct CurrencyAmounts {
+    amounts: [i32; SUPPORTED_CURRENCIES.len()],
+}
+
+impl CurrencyAmounts {
+    // Every supported currency has to be given.
+    pub fn new(amounts: [(Currency, i32); SUPPORTED_CURRENCIES.len()]) -> Self {
+        let mut result = CurrencyAmounts { amounts: [0; SUPPORTED_CURRENCIES.len()] };
+        for (currency, amount) in amounts {
+            result.set(currency, amount);
+        }
+        return result;
+    }
+
+    pub fn get(self: &Self, currency: Currency) -> i32 {
+        return self.amounts[currency_index(currency)];
+    }
+
+    pub fn set(self: &mut Self, currency: Currency, amount: i32) {
+        self.amounts[currency_index(currency)] = amount;
+    }
+}
+
+fn currency_index(currency: Currency) -> usize {
+    return SUPPORTED_CURRENCIES.iter().position(|supported| *supported == currency).unwrap();
+}
+
+// Converts minor units of one currency into minor units of another at `rate` (units of `to` per unit of `from`).
+pub fn convert(amount: i32, from: Currency, to: Currency, rate: f64) -> i32 {
+    let major = amount as f64 / 10_f64.powi(from.minor_units() as i32);
+    return (major * rate * 10_f64.powi(to.minor_units() as i32)).round() as i32;
+}
+
+#[derive(Copy, Clone)]
+pub struct ExchangeRate {
+    pub from: Currency,
+    pub to: Currency,
+    pub rate: f64, // mid-market, units of `to` per unit of `from`
+    pub effective_from: NaiveDateTime,
+}
+
+pub struct RateTable {
+    rates: Vec<ExchangeRate>,
+}
+
+impl RateTable {
+    pub fn new() -> Self {
+        return RateTable { rates: vec![] }
+    }
+
+    // One rate per line: `effective_from,FROM,TO,rate`, where effective_from is
+    // `YYYY-MM-DD` or `YYYY-MM-DD HH:MM:SS`. Blank lines and lines starting with # are skipped.
+    pub fn load_from_file(path: &Path) -> Result<Self, CurrencyError> {
+        let contents = fs::read_to_string(path).map_err(|_| CurrencyError::RateFileUnreadable)?;
+        return RateTable::parse(&contents);
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, CurrencyError> {
+        let mut table = RateTable::new();
+        for line in contents.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            if fields.len() != 4 {
+                return Err(CurrencyError::RateFileInvalid);
+            }
+            let effective_from = NaiveDateTime::parse_from_str(fields[0], "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| NaiveDate::parse_from_str(fields[0], "%Y-%m-%d").map(|date| date.and_hms_opt(0, 0, 0).unwrap()))
+                .map_err(|_| CurrencyError::RateFileInvalid)?;
+            let from = Currency::from_code(fields[1]).ok_or(CurrencyError::UnknownCurrency)?;
+            let to = Currency::from_code(fields[2]).ok_or(CurrencyError::UnknownCurrency)?;
+            let rate: f64 = fields[3].parse().map_err(|_| CurrencyError::RateFileInvalid)?;
+            if rate <= 0.0 || from == to {
+                return Err(CurrencyError::RateFileInvalid);
+            }
+            table.add_rate(ExchangeRate { from: from, to: to, rate: rate, effective_from: effective_from });
+        }
+        return Ok(table);
+    }
+
+    pub fn add_rate(self: &mut Self, rate: ExchangeRate) {
+        self.rates.push(rate);
+    }
+
+    // Latest rate in effect at `at`, quoted either way round.
+    pub fn rate(self: &Self, from: Currency, to: Currency, at: NaiveDateTime) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        return self.rates.iter()
+            .filter(|rate| rate.effective_from <= at)
+            .filter_map(|rate| {
+                if rate.from == from && rate.to == to {
+                    Some((rate.effective_from, rate.rate))
+                } else if rate.from == to && rate.to == from {
+                    Some((rate.effective_from, 1.0 / rate.rate))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(effective_from, _)| *effective_from)
+            .map(|(_, rate)| rate);
+    }
+}
+
+impl Default for RateTable {
+    fn default() -> Self {
+        return RateTable::new();
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct FxPolicy {
+    pub spread: f64, // fraction taken off the mid rate, e.g. 0.01 for 1%
+}
+
+impl Default for FxPolicy {
+    fn default() -> Self {
+        return FxPolicy { spread: 0.01 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::{MoneyAccountID, MoneyAccountType};
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::BalanceError;
+    use crate::bomanis_bank::ledger::IncomeKind;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn at(day: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    }
+
+    // A customer holding a funded USD account and an empty one in `other`.
+    fn bank_with_accounts(other: Currency) -> (DatabaseHandler, MoneyAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(at(2)));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let usd = database.make_new_money_account(ptr_bank_account.clone(), String::from("Dollars"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        let foreign = database.make_new_money_account(ptr_bank_account, String::from("Foreign"), MoneyAccountType::Checking, other).borrow().id;
+        database.deposit(usd, 20000, DepositChannel::Cash, None).unwrap();
+        return (database, usd, foreign);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn amounts_are_formatted_and_parsed_in_minor_units() {
+        assert_eq!(Currency::Usd.format(123456), "$1234.56");
+        assert_eq!(Currency::Usd.format(-5), "-$0.05");
+        assert_eq!(Currency::Jpy.format(123456), "¥123456");
+        assert_eq!(Currency::Sek.format_decimal(150), "1.50");
+        assert_eq!(Currency::Usd.parse_amount("12.5"), Some(1250));
+        assert_eq!(Currency::Usd.parse_amount("$12"), Some(1200));
+        assert_eq!(Currency::Usd.parse_amount("-0.01"), Some(-1));
+        assert_eq!(Currency::Usd.parse_amount("12.345"), None);
+        assert_eq!(Currency::Jpy.parse_amount("12.5"), None);
+        assert_eq!(Currency::Usd.parse_amount("99999999999"), None);
+    }
+
+    #[test]
+    fn conversion_accounts_for_minor_units() {
+        assert_eq!(convert(10000, Currency::Usd, Currency::Eur, 0.9), 9000);
+        assert_eq!(convert(10000, Currency::Usd, Currency::Jpy, 150.0), 15000);
+        assert_eq!(convert(15000, Currency::Jpy, Currency::Usd, 1.0 / 150.0), 10000);
+        assert_eq!(convert(1, Currency::Usd, Currency::Eur, 0.994), 1); // rounds to the nearest minor unit
+    }
+
+    #[test]
+    fn latest_rate_applies_either_way_round() {
+        let table = RateTable::parse("# mid rates\n2026-03-01,USD,EUR,0.90\n2026-03-03 12:00:00,USD,EUR,0.80\n").unwrap();
+        assert_eq!(table.rate(Currency::Usd, Currency::Eur, at(2)), Some(0.90));
+        assert_eq!(table.rate(Currency::Usd, Currency::Eur, at(3)), Some(0.80));
+        assert_eq!(table.rate(Currency::Eur, Currency::Usd, at(3)), Some(1.0 / 0.80));
+        assert_eq!(table.rate(Currency::Usd, Currency::Usd, at(1)), Some(1.0));
+        assert_eq!(table.rate(Currency::Usd, Currency::Gbp, at(3)), None);
+        assert_eq!(RateTable::parse("2026-03-01,USD,EUR,0.90").unwrap().rate(Currency::Usd, Currency::Eur, at(1) - chrono::Duration::days(1)), None);
+    }
+
+    #[test]
+    fn bad_rate_files_are_refused() {
+        assert!(matches!(RateTable::parse("2026-03-01,USD,EUR"), Err(CurrencyError::RateFileInvalid)));
+        assert!(matches!(RateTable::parse("2026-03-01,USD,XXX,1.0"), Err(CurrencyError::UnknownCurrency)));
+        assert!(matches!(RateTable::parse("2026-03-01,USD,EUR,-1"), Err(CurrencyError::RateFileInvalid)));
+        assert!(matches!(RateTable::parse("2026-03-01,USD,USD,1"), Err(CurrencyError::RateFileInvalid)));
+    }
+
+    #[test]
+    fn currency_amounts_are_kept_apart() {
+        let mut amounts = CurrencyAmounts::new([
+            (Currency::Usd, 100),
+            (Currency::Eur, 200),
+            (Currency::Gbp, 300),
+            (Currency::Sek, 400),
+            (Currency::Chf, 500),
+            (Currency::Jpy, 600),
+        ]);
+        assert_eq!(amounts.get(Currency::Sek), 400);
+        amounts.set(Currency::Sek, 401);
+        assert_eq!(amounts.get(Currency::Sek), 401);
+        assert_eq!(amounts.get(Currency::Jpy), 600);
+    }
+
+    #[test]
+    fn cross_currency_transfer_keeps_the_spread() {
+        let (mut database, usd, eur) = bank_with_accounts(Currency::Eur);
+        database.set_rate_table(RateTable::parse("2026-03-01,USD,EUR,0.90").unwrap());
+        database.set_fx_policy(FxPolicy { spread: 0.01 });
+
+        assert_eq!(database.transfer(usd, eur, 10000, None), Ok(10000));
+        assert_eq!(balance(&database, eur), 8910); // 90.00 less 1%
+        let income = database.get_income_entries().last().unwrap();
+        assert!(income.kind == IncomeKind::FxSpread && income.currency == Currency::Eur);
+        assert_eq!(income.amount, 90);
+    }
+
+    #[test]
+    fn transfer_without_a_rate_is_refused() {
+        let (mut database, usd, gbp) = bank_with_accounts(Currency::Gbp);
+        assert_eq!(database.transfer(usd, gbp, 10000, None), Err(BalanceError::NoExchangeRate));
+        assert_eq!(balance(&database, usd), 20000);
+    }
+}
@@ -1,40 +1,151 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::fs::File;
 use std::path::Path;
-use rand::Rng;
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
 
 use crate::bomanis_bank::account::{BankAccount, BankAccountID, MoneyAccountID, MoneyAccount};
+use crate::bomanis_bank::audit::{self, AuditAction, AuditActor, AuditChannel, AuditContext, AuditEvent, AuditLog, AuditOutcome};
+use crate::bomanis_bank::clock::{Clock, RandomSource, SystemClock, ThreadRandom};
+use crate::bomanis_bank::fees::{FeeKind, FeeQuote, FeeSchedule, FeeSchedules, WithdrawalLimit};
+use crate::bomanis_bank::currency::{self, Currency, FxPolicy, RateTable};
+use crate::bomanis_bank::clearing::{self, ClearingPolicy, DepositChannel, DepositID, DepositStatus, PendingDeposit};
+use crate::bomanis_bank::credit::{self, CreditLine, CreditPolicy, CreditSegment, CreditStatement, CreditTerms};
+use crate::bomanis_bank::errors::{BalanceError, ClearingError, CreditError, CurrencyError, ExportError, GuardianError, LoanError, OnboardingError, PaymentError, ProfileError, ReversalError, ScheduleError, StaffError, StatementError, ApprovalError, BusinessError, TermDepositError, TwoFactorError};
+use crate::bomanis_bank::integrity::{BrokenLink, BrokenLinkReason, Chain, Checkpoint, IntegrityReport};
+use crate::bomanis_bank::idempotency::{IdempotencyStore, Replay, StoredOutcome};
+use crate::bomanis_bank::notifications::Notifications;
+use crate::bomanis_bank::passwords::{self, CommonPasswordList, PasswordHash, PasswordPolicy, PasswordRule};
+use crate::bomanis_bank::profile::{ProfileChange, ProfileField};
+use crate::bomanis_bank::secret::{SecretBytes, SecretString};
+use crate::bomanis_bank::onboarding::{self, Application, ContactDetails, GuardianPolicy, IdentityVerifier, LocalIdentityVerifier, OnboardingPolicy, OnboardingStatus, VerificationOutcome};
+use crate::bomanis_bank::payments::{self, LocalPaymentNetwork, NetworkResponse, OutboundPayment, Payee, PayeeID, PaymentID, PaymentNetwork, PaymentStatus};
+use crate::bomanis_bank::approvals::{ApprovalID, ApprovalOperation, ApprovalParty, ApprovalPolicy, ApprovalRequest, ApprovalStatus, Reviewer};
+use crate::bomanis_bank::business::{self, BusinessPermission, BusinessUser, BusinessUserID};
+use crate::bomanis_bank::export::{ExportFormat, TransactionExport};
+use crate::bomanis_bank::statements::{Statement, StatementFormat, StatementHolder, StatementID, StatementLine};
+use crate::bomanis_bank::staff::{StaffID, StaffMember, StaffPermission, StaffRole};
+use crate::bomanis_bank::scheduler::{Frequency, OrderStatus, SchedulerPolicy, StandingOrder, StandingOrderID};
+use crate::bomanis_bank::ledger::{IncomeEntry, IncomeKind, Ledger, ReasonCode, Transaction, TransactionID, TransactionKind};
+use crate::bomanis_bank::loans::{self, Installment, InstallmentStatus, Loan, LoanPolicy, LoanStatus, LoanTerms, PayoffQuote};
+use crate::bomanis_bank::totp::{self, TwoFactor, TwoFactorEnrollment, TwoFactorPolicy};
+use crate::bomanis_bank::term_deposits::{self, EarlyClosureQuote, InterestPayment, MaturityInstruction, TermDeposit, TermDepositPolicy, TermDepositStatus, TermDepositTerms};
 
 use super::account::MoneyAccountType;
 
+// Ways money leaves an account that count towards savings withdrawal limits.
+const OUTGOING_KINDS: [TransactionKind; 3] = [TransactionKind::Withdrawal, TransactionKind::TransferOut, TransactionKind::BillPayment];
+
 pub struct DatabaseHandler {
     bank_accounts: HashMap<BankAccountID, Rc<RefCell<BankAccount>>>,
+    onboarding_policy: OnboardingPolicy,
+    identity_verifier: Box<dyn IdentityVerifier>,
+    password_policy: PasswordPolicy,
+    common_passwords: CommonPasswordList,
+    two_factor_policy: TwoFactorPolicy,
+    audit_log: AuditLog,
+    audit_context: AuditContext,
+    checkpoints: Vec<Checkpoint>,
+    signing_key: Option<SecretBytes>, // from configuration, never generated or stored with the data
+    staff: HashMap<StaffID, StaffMember>,
+    next_staff_id: StaffID,
+    approval_policy: ApprovalPolicy,
+    approval_requests: HashMap<ApprovalID, ApprovalRequest>,
+    next_approval_id: ApprovalID,
+    guardian_policy: GuardianPolicy,
     money_accounts: HashMap<MoneyAccountID, BankAccountID>,
+    clearing_policy: ClearingPolicy,
+    pending_deposits: HashMap<DepositID, PendingDeposit>,
+    next_deposit_id: DepositID,
+    ledger: Ledger,
+    idempotency: IdempotencyStore,
+    standing_orders: HashMap<StandingOrderID, StandingOrder>,
+    next_standing_order_id: StandingOrderID,
+    scheduler_policy: SchedulerPolicy,
+    notifications: Notifications,
+    next_payee_id: PayeeID,
+    outbound_payments: Vec<OutboundPayment>,
+    payment_network: Box<dyn PaymentNetwork>,
+    rate_table: RateTable,
+    fx_policy: FxPolicy,
+    fee_schedules: FeeSchedules,
+    savings_withdrawal_limit: WithdrawalLimit,
+    maintenance_due: HashMap<MoneyAccountID, NaiveDate>, // first day of the month after the next unassessed cycle
+    statements: Vec<Statement>, // archive, never changed once issued
+    statement_due: HashMap<MoneyAccountID, NaiveDate>, // first day after the next cycle without a statement
+    term_deposits: HashMap<MoneyAccountID, TermDeposit>,
+    term_deposit_policy: TermDepositPolicy,
+    loans: HashMap<MoneyAccountID, Loan>,
+    loan_policy: LoanPolicy,
+    credit_lines: HashMap<MoneyAccountID, CreditLine>,
+    credit_policy: CreditPolicy,
+    clock: Rc<dyn Clock>,
+    rng: Box<dyn RandomSource>,
 }
 
 impl DatabaseHandler {
     pub fn new() -> Self {
+        return DatabaseHandler::with_providers(Rc::new(SystemClock), Box::new(ThreadRandom));
+    }
+
+    pub fn with_providers(clock: Rc<dyn Clock>, rng: Box<dyn RandomSource>) -> Self {
         return DatabaseHandler {
             bank_accounts: HashMap::new(),
+            onboarding_policy: OnboardingPolicy::default(),
+            identity_verifier: Box::new(LocalIdentityVerifier::new()),
+            password_policy: PasswordPolicy::default(),
+            common_passwords: CommonPasswordList::new(),
+            two_factor_policy: TwoFactorPolicy::default(),
+            audit_log: AuditLog::new(),
+            audit_context: AuditContext { actor: AuditActor::Anonymous, channel: AuditChannel::Api },
+            checkpoints: vec![],
+            signing_key: None,
+            staff: HashMap::new(),
+            next_staff_id: 1,
+            approval_policy: ApprovalPolicy::default(),
+            approval_requests: HashMap::new(),
+            next_approval_id: 1,
+            guardian_policy: GuardianPolicy::default(),
             money_accounts: HashMap::new(),
+            clearing_policy: ClearingPolicy::default(),
+            pending_deposits: HashMap::new(),
+            next_deposit_id: 1,
+            ledger: Ledger::new(),
+            idempotency: IdempotencyStore::new(Duration::hours(24)),
+            standing_orders: HashMap::new(),
+            next_standing_order_id: 1,
+            scheduler_policy: SchedulerPolicy::default(),
+            notifications: Notifications::new(),
+            next_payee_id: 1,
+            outbound_payments: vec![],
+            payment_network: Box::new(LocalPaymentNetwork::new()),
+            rate_table: RateTable::new(),
+            fx_policy: FxPolicy::default(),
+            fee_schedules: FeeSchedules::default(),
+            savings_withdrawal_limit: WithdrawalLimit::default(),
+            maintenance_due: HashMap::new(),
+            statements: vec![],
+            statement_due: HashMap::new(),
+            term_deposits: HashMap::new(),
+            term_deposit_policy: TermDepositPolicy::default(),
+            loans: HashMap::new(),
+            loan_policy: LoanPolicy::default(),
+            credit_lines: HashMap::new(),
+            credit_policy: CreditPolicy::default(),
+            clock: clock,
+            rng: rng,
         }
     }
     
     // Bank Accounts
-    pub fn get_bank_account_with_id_u32(self: &Self, id: BankAccountID) -> Result<Rc<RefCell<BankAccount>>, ()> {
-        match self.bank_accounts.get(&id) {
-            Some(acc) => return Ok(acc.clone()),
-            None => Err(())
-        }
+    pub fn get_bank_account_with_id_u32(self: &Self, id: BankAccountID) -> Option<Rc<RefCell<BankAccount>>> {
+        return self.bank_accounts.get(&id).cloned();
     }
 
-    pub fn get_bank_account_with_id_string(self: &Self, id: String) -> Result<Rc<RefCell<BankAccount>>, ()> {
+    pub fn get_bank_account_with_id_string(self: &Self, id: String) -> Option<Rc<RefCell<BankAccount>>> {
         match id.parse::<u32>() {
             Ok(id) => return self.get_bank_account_with_id_u32(id),
-            Err(_) => Err(())
+            Err(_) => None
         }
     }
 
@@ -51,11 +162,12 @@ impl DatabaseHandler {
         return None;
     }
 
-    pub fn make_new_bank_account(self: &mut Self, name: String, dob: NaiveDate, userid: String, password: String) -> Rc<RefCell<BankAccount>> {
-        let mut new_account_number: u32 = rand::thread_rng().gen_range(0..999999);
+    pub fn make_new_bank_account(self: &mut Self, name: String, dob: NaiveDate, userid: String, password: SecretString) -> Rc<RefCell<BankAccount>> {
+        let mut new_account_number: u32 = self.rng.gen_range(0, 999999);
         while self.check_if_bank_account_id_exists(new_account_number) {
-            new_account_number = rand::thread_rng().gen_range(0..999999);
+            new_account_number = self.rng.gen_range(0, 999999);
         }
+        let password = self.hash_password(&password);
         let new_bank_account = BankAccount::new(
             new_account_number,
             name,
@@ -63,15 +175,430 @@ impl DatabaseHandler {
             userid,
             password,
         );
+        let mut new_bank_account = new_bank_account;
+        new_bank_account.password_changed_at = Some(self.clock.now());
         let new_bank_account_ptr = Rc::new(RefCell::new(new_bank_account));
         self.bank_accounts.insert(new_account_number, new_bank_account_ptr.clone());
         return new_bank_account_ptr.clone();
     }
 
+    // Onboarding
+    pub fn set_onboarding_policy(self: &mut Self, policy: OnboardingPolicy) {
+        self.onboarding_policy = policy;
+    }
+
+    pub fn set_identity_verifier(self: &mut Self, verifier: Box<dyn IdentityVerifier>) {
+        self.identity_verifier = verifier;
+    }
+
+    pub fn check_date_of_birth(self: &Self, dob: NaiveDate) -> Result<(), OnboardingError> {
+        return onboarding::check_date_of_birth(dob, self.clock.today(), &self.onboarding_policy);
+    }
+
+    // Validates the application, opens the customer's bank account and runs identity verification.
+    // Customers whose identity is not verified can log in but not transact.
+    pub fn register_customer(self: &mut Self, application: Application) -> Result<Rc<RefCell<BankAccount>>, OnboardingError> {
+        let user_id = application.user_id.clone();
+        let result = self.open_customer(application, None);
+        match &result {
+            Ok(acc) => {
+                let (id, status) = (acc.borrow().id, acc.borrow().onboarding_status);
+                self.audit(Some(id), AuditAction::Registration, AuditOutcome::Success, format!("User ID \"{}\", identity {}", user_id, status.description().to_lowercase()));
+            },
+            Err(err) => self.audit(None, AuditAction::Registration, AuditOutcome::Failure, format!("User ID \"{}\": {}", user_id, err.as_str())),
+        }
+        return result;
+    }
+
+    // `guardian` is set for accounts a guardian opens for a child.
+    fn open_customer(self: &mut Self, application: Application, guardian: Option<BankAccountID>) -> Result<Rc<RefCell<BankAccount>>, OnboardingError> {
+        if application.user_id.trim().is_empty() || self.user_id_in_use(&application.user_id) {
+            return Err(OnboardingError::UserIdTaken);
+        }
+        if !self.check_new_password(None, application.password.expose()).is_empty() {
+            return Err(OnboardingError::WeakPassword);
+        }
+        if application.name.trim().is_empty() {
+            return Err(OnboardingError::MissingName);
+        }
+        if let Some(business) = &application.business {
+            if business.company_name.trim().is_empty() {
+                return Err(OnboardingError::MissingCompanyName);
+            }
+            if !business::is_valid_registration_number(&business.registration_number) {
+                return Err(OnboardingError::InvalidRegistrationNumber);
+            }
+        }
+        match guardian {
+            Some(_) => onboarding::check_minor_date_of_birth(application.dob, self.clock.today(), &self.onboarding_policy)?,
+            None => self.check_date_of_birth(application.dob)?,
+        }
+        onboarding::check_contact_details(&application.contact)?;
+        if !onboarding::is_valid_id_document(&application.id_document) {
+            return Err(OnboardingError::InvalidIdDocument);
+        }
+
+        let outcome = self.identity_verifier.verify(&application);
+        let ptr_bank_account = self.make_new_bank_account(application.name, application.dob, application.user_id, application.password);
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        bank_account.contact = application.contact;
+        bank_account.id_document = application.id_document;
+        bank_account.business = application.business;
+        bank_account.guardian = guardian;
+        (bank_account.onboarding_status, bank_account.onboarding_note) = match outcome {
+            VerificationOutcome::Verified => (OnboardingStatus::Verified, None),
+            VerificationOutcome::NeedsReview(reason) => (OnboardingStatus::PendingReview, Some(reason)),
+            VerificationOutcome::Rejected(reason) => (OnboardingStatus::Rejected, Some(reason)),
+        };
+        drop(bank_account);
+        return Ok(ptr_bank_account);
+    }
+
+    // For staff settling a manual review.
+    pub fn set_onboarding_status(self: &mut Self, owner: BankAccountID, status: OnboardingStatus, note: Option<String>) -> Result<(), OnboardingError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(OnboardingError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let detail = format!("{} to {}", bank_account.onboarding_status.description(), status.description());
+        bank_account.onboarding_status = status;
+        bank_account.onboarding_note = note;
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::OnboardingStatusChange, AuditOutcome::Success, detail);
+        return Ok(());
+    }
+
+    pub fn is_verified(self: &Self, owner: BankAccountID) -> bool {
+        match self.bank_accounts.get(&owner) {
+            Some(acc) => return acc.borrow().onboarding_status == OnboardingStatus::Verified,
+            None => return false,
+        }
+    }
+
+    fn owner_is_verified(self: &Self, id: MoneyAccountID) -> bool {
+        match self.money_accounts.get(&id) {
+            Some(owner) => return self.is_verified(*owner),
+            None => return false,
+        }
+    }
+
+    // Passwords
+    pub fn set_password_policy(self: &mut Self, policy: PasswordPolicy) {
+        self.password_policy = policy;
+    }
+
+    pub fn get_password_policy(self: &Self) -> PasswordPolicy {
+        return self.password_policy;
+    }
+
+    pub fn load_common_passwords(self: &mut Self, path: &Path) -> std::io::Result<()> {
+        self.common_passwords = CommonPasswordList::load_from_file(path)?;
+        return Ok(());
+    }
+
+    pub fn set_common_passwords(self: &mut Self, common_passwords: CommonPasswordList) {
+        self.common_passwords = common_passwords;
+    }
+
+    fn hash_password(self: &mut Self, password: &SecretString) -> PasswordHash {
+        return PasswordHash::new(password, self.password_policy.hash_iterations, self.rng.as_mut());
+    }
+
+    // Rules a new password breaks, for a new customer (None) or an existing one whose history counts.
+    pub fn check_new_password(self: &Self, owner: Option<BankAccountID>, password: &str) -> Vec<PasswordRule> {
+        let history = match owner.and_then(|owner| self.bank_accounts.get(&owner)) {
+            Some(acc) => acc.borrow().password_history.clone(),
+            None => vec![],
+        };
+        return passwords::check_password(password, &self.password_policy, &self.common_passwords, &history);
+    }
+
+    // True once the password has expired or staff have asked for it to be changed.
+    pub fn password_change_required(self: &Self, owner: BankAccountID) -> bool {
+        let bank_account = match self.bank_accounts.get(&owner) {
+            Some(acc) => acc.borrow(),
+            None => return false,
+        };
+        if bank_account.must_change_password {
+            return true;
+        }
+        match (self.password_policy.max_age_days, bank_account.password_changed_at) {
+            (Some(max_age_days), Some(changed_at)) => return self.clock.now() - changed_at > Duration::days(max_age_days),
+            _ => return false,
+        }
+    }
+
+    // Two-factor authentication
+    pub fn set_two_factor_policy(self: &mut Self, policy: TwoFactorPolicy) -> Result<(), TwoFactorError> {
+        if !policy.is_valid() {
+            return Err(TwoFactorError::InvalidPolicy);
+        }
+        self.two_factor_policy = policy;
+        return Ok(());
+    }
+
+    pub fn get_two_factor_policy(self: &Self) -> TwoFactorPolicy {
+        return self.two_factor_policy;
+    }
+
+    pub fn two_factor_enabled(self: &Self, owner: BankAccountID) -> bool {
+        match self.bank_accounts.get(&owner) {
+            Some(acc) => return acc.borrow().two_factor.as_ref().is_some_and(|two_factor| two_factor.confirmed),
+            None => return false,
+        }
+    }
+
+    // Withdrawals, payments and transfers of at least the policy amount need a fresh code from customers using two-factor.
+    pub fn requires_step_up(self: &Self, owner: BankAccountID, amount: i32, currency: Currency) -> bool {
+        return self.two_factor_enabled(owner) && amount >= self.two_factor_policy.step_up_amount.get(currency);
+    }
+
+    // Generates a new secret. Nothing changes at login until the enrollment has been confirmed with a code.
+    pub fn begin_two_factor_enrollment(self: &mut Self, owner: BankAccountID) -> Result<TwoFactorEnrollment, TwoFactorError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TwoFactorError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        if bank_account.two_factor.as_ref().is_some_and(|two_factor| two_factor.confirmed) {
+            return Err(TwoFactorError::AlreadyEnabled);
+        }
+        let two_factor = TwoFactor::new(totp::generate_secret(self.rng.as_mut()));
+        let enrollment = TwoFactorEnrollment {
+            secret: two_factor.secret_base32(),
+            uri: totp::otpauth_uri(&two_factor, &bank_account.userid, &self.two_factor_policy),
+        };
+        bank_account.two_factor = Some(two_factor);
+        return Ok(enrollment);
+    }
+
+    // Turns two-factor on once the customer proves their app works. Returns the recovery codes, shown only this once.
+    pub fn confirm_two_factor_enrollment(self: &mut Self, owner: BankAccountID, code: &str) -> Result<Vec<SecretString>, TwoFactorError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TwoFactorError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let now = self.clock.unix_timestamp();
+        let two_factor = match bank_account.two_factor.as_mut() {
+            Some(two_factor) if two_factor.confirmed => return Err(TwoFactorError::AlreadyEnabled),
+            Some(two_factor) => two_factor,
+            None => return Err(TwoFactorError::NotEnrolled),
+        };
+        let step = two_factor.matching_step(code, now, &self.two_factor_policy).ok_or(TwoFactorError::InvalidCode)?;
+        let recovery_codes = totp::generate_recovery_codes(self.rng.as_mut(), self.two_factor_policy.recovery_code_count);
+        two_factor.confirmed = true;
+        two_factor.last_used_step = Some(step);
+        two_factor.recovery_codes = recovery_codes.clone();
+        self.record_profile_change(&mut bank_account, ProfileField::TwoFactor, Some(String::from("Off")), Some(String::from("On")));
+        return Ok(recovery_codes);
+    }
+
+    // Accepts a code from the app or one of the recovery codes, which is then used up. Too many
+    // wrong codes in a row lock the second factor for a while, recovery codes included.
+    pub fn verify_second_factor(self: &mut Self, owner: BankAccountID, code: &str) -> Result<(), TwoFactorError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TwoFactorError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let now = self.clock.now();
+        let two_factor = match bank_account.two_factor.as_mut() {
+            Some(two_factor) if two_factor.confirmed => two_factor,
+            _ => return Err(TwoFactorError::NotEnabled),
+        };
+        if two_factor.is_locked(now) {
+            drop(bank_account);
+            self.audit(Some(owner), AuditAction::SecondFactor, AuditOutcome::Failure, String::from(TwoFactorError::Locked.as_str()));
+            return Err(TwoFactorError::Locked);
+        }
+        if let Some(step) = two_factor.matching_step(code, self.clock.unix_timestamp(), &self.two_factor_policy) {
+            two_factor.last_used_step = Some(step);
+            two_factor.failed_attempts = 0;
+            drop(bank_account);
+            self.audit(Some(owner), AuditAction::SecondFactor, AuditOutcome::Success, String::from("Authenticator code"));
+            return Ok(());
+        }
+        if two_factor.use_recovery_code(code) {
+            two_factor.failed_attempts = 0;
+            let remaining = two_factor.recovery_codes.len();
+            drop(bank_account);
+            self.audit(Some(owner), AuditAction::SecondFactor, AuditOutcome::Success, format!("Recovery code, {} left", remaining));
+            return Ok(());
+        }
+        two_factor.failed_attempts += 1;
+        if two_factor.failed_attempts < self.two_factor_policy.max_failed_attempts {
+            drop(bank_account);
+            self.audit(Some(owner), AuditAction::SecondFactor, AuditOutcome::Failure, String::from(TwoFactorError::InvalidCode.as_str()));
+            return Err(TwoFactorError::InvalidCode);
+        }
+        let attempts = two_factor.failed_attempts;
+        two_factor.failed_attempts = 0;
+        two_factor.locked_until = Some(now + Duration::minutes(self.two_factor_policy.lockout_minutes));
+        drop(bank_account);
+        self.notifications.push(owner, String::from("Your second factor was locked after too many wrong codes. If this was not you, please contact us."), now);
+        self.audit(Some(owner), AuditAction::SecondFactor, AuditOutcome::Failure, format!("Locked after {} wrong codes", attempts));
+        return Err(TwoFactorError::Locked);
+    }
+
+    pub fn remaining_recovery_codes(self: &Self, owner: BankAccountID) -> usize {
+        match self.bank_accounts.get(&owner) {
+            Some(acc) => return acc.borrow().two_factor.as_ref().map_or(0, |two_factor| two_factor.recovery_codes.len()),
+            None => return 0,
+        }
+    }
+
+    pub fn regenerate_recovery_codes(self: &mut Self, owner: BankAccountID, code: &str) -> Result<Vec<SecretString>, TwoFactorError> {
+        self.verify_second_factor(owner, code)?;
+        let recovery_codes = totp::generate_recovery_codes(self.rng.as_mut(), self.two_factor_policy.recovery_code_count);
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TwoFactorError::NotFound)?;
+        if let Some(two_factor) = ptr_bank_account.borrow_mut().two_factor.as_mut() {
+            two_factor.recovery_codes = recovery_codes.clone();
+        }
+        return Ok(recovery_codes);
+    }
+
+    pub fn disable_two_factor(self: &mut Self, owner: BankAccountID, code: &str) -> Result<(), TwoFactorError> {
+        self.verify_second_factor(owner, code)?;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TwoFactorError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        bank_account.two_factor = None;
+        self.record_profile_change(&mut bank_account, ProfileField::TwoFactor, Some(String::from("On")), Some(String::from("Off")));
+        return Ok(());
+    }
+
+    // Profile
+    pub fn change_password(self: &mut Self, owner: BankAccountID, current_password: &SecretString, new_password: SecretString) -> Result<(), ProfileError> {
+        let result = self.apply_password_change(owner, current_password, new_password);
+        if let Err(err) = result {
+            self.audit(Some(owner), AuditAction::PasswordChange, AuditOutcome::Failure, String::from(err.as_str()));
+        }
+        return result;
+    }
+
+    // Makes `password` the current one and keeps it in the history, which is trimmed to the policy's size.
+    fn store_password(self: &Self, bank_account: &mut BankAccount, password: PasswordHash) {
+        bank_account.password_history.push(password.clone());
+        let excess = bank_account.password_history.len().saturating_sub(self.password_policy.history_size.max(1));
+        bank_account.password_history.drain(..excess);
+        bank_account.password = password;
+        bank_account.password_changed_at = Some(self.clock.now());
+    }
+
+    fn apply_password_change(self: &mut Self, owner: BankAccountID, current_password: &SecretString, new_password: SecretString) -> Result<(), ProfileError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(ProfileError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        if !bank_account.password.verify(current_password.expose()) {
+            return Err(ProfileError::WrongPassword);
+        }
+        let failed_rules = passwords::check_password(new_password.expose(), &self.password_policy, &self.common_passwords, &bank_account.password_history);
+        if !failed_rules.is_empty() {
+            return Err(ProfileError::WeakPassword);
+        }
+        let new_password = PasswordHash::new(&new_password, self.password_policy.hash_iterations, self.rng.as_mut());
+        self.store_password(&mut bank_account, new_password);
+        bank_account.must_change_password = false;
+        self.record_profile_change(&mut bank_account, ProfileField::Password, None, None);
+        return Ok(());
+    }
+
+    pub fn update_name(self: &mut Self, owner: BankAccountID, name: String) -> Result<(), ProfileError> {
+        let name = String::from(name.trim());
+        if name.is_empty() {
+            return Err(ProfileError::MissingName);
+        }
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(ProfileError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        if bank_account.name != name {
+            let old_name = std::mem::replace(&mut bank_account.name, name.clone());
+            self.record_profile_change(&mut bank_account, ProfileField::Name, Some(old_name), Some(name));
+        }
+        return Ok(());
+    }
+
+    // Replaces all contact details at once, logging each field that actually changed.
+    pub fn update_contact_details(self: &mut Self, owner: BankAccountID, contact: ContactDetails) -> Result<(), ProfileError> {
+        onboarding::check_contact_details(&contact).map_err(|err| match err {
+            OnboardingError::InvalidEmail => ProfileError::InvalidEmail,
+            OnboardingError::InvalidPhone => ProfileError::InvalidPhone,
+            _ => ProfileError::MissingAddress,
+        })?;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(ProfileError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let old_contact = std::mem::replace(&mut bank_account.contact, contact.clone());
+        let changes = [
+            (ProfileField::Email, old_contact.email, contact.email),
+            (ProfileField::Phone, old_contact.phone, contact.phone),
+            (ProfileField::Address, old_contact.address, contact.address),
+        ];
+        for (field, old_value, new_value) in changes {
+            if old_value != new_value {
+                self.record_profile_change(&mut bank_account, field, Some(old_value), Some(new_value));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn change_user_id(self: &mut Self, owner: BankAccountID, new_user_id: String) -> Result<(), ProfileError> {
+        if new_user_id.is_empty() || new_user_id.contains(char::is_whitespace) {
+            return Err(ProfileError::InvalidUserId);
+        }
+        if self.user_id_in_use(&new_user_id) {
+            return Err(ProfileError::UserIdTaken);
+        }
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(ProfileError::NotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let old_user_id = std::mem::replace(&mut bank_account.userid, new_user_id.clone());
+        self.record_profile_change(&mut bank_account, ProfileField::UserId, Some(old_user_id), Some(new_user_id));
+        return Ok(());
+    }
+
+    pub fn get_profile_history(self: &Self, owner: BankAccountID) -> Vec<ProfileChange> {
+        match self.get_bank_account_with_id_u32(owner) {
+            Some(acc) => return acc.borrow().profile_history.clone(),
+            None => return vec![],
+        }
+    }
+
+    fn record_profile_change(self: &mut Self, bank_account: &mut BankAccount, field: ProfileField, old_value: Option<String>, new_value: Option<String>) {
+        bank_account.profile_history.push(ProfileChange {
+            field: field,
+            old_value: old_value,
+            new_value: new_value.clone(),
+            changed_at: self.clock.now(),
+        });
+        // values are personal data, the audit log only says what changed
+        let (action, detail) = match field {
+            ProfileField::Password => (AuditAction::PasswordChange, String::from("Password changed")),
+            ProfileField::TwoFactor => (AuditAction::TwoFactorChange, format!("Two-factor authentication turned {}", new_value.unwrap_or_default().to_lowercase())),
+            _ => (AuditAction::ProfileChange, format!("{} changed", field.description())),
+        };
+        self.audit(Some(bank_account.id), action, AuditOutcome::Success, detail);
+    }
+
+    // Audit
+    pub fn set_audit_context(self: &mut Self, context: AuditContext) {
+        self.audit_context = context;
+    }
+
+    pub fn get_audit_context(self: &Self) -> AuditContext {
+        return self.audit_context.clone();
+    }
+
+    // Records an event as the current actor, through the current channel.
+    pub fn audit(self: &mut Self, customer: Option<BankAccountID>, action: AuditAction, outcome: AuditOutcome, detail: String) {
+        self.audit_log.append(self.clock.now(), &self.audit_context, customer, action, outcome, detail);
+    }
+
+    pub fn get_audit_events(self: &Self, customer: Option<BankAccountID>, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Vec<AuditEvent> {
+        return self.audit_log.query(customer, from, to);
+    }
+
+    // A customer's events from the last `days` days.
+    pub fn get_recent_audit_events(self: &Self, owner: BankAccountID, days: i64) -> Vec<AuditEvent> {
+        return self.audit_log.query(Some(owner), Some(self.clock.now() - Duration::days(days)), None);
+    }
+
+    // Writes the matching events to a CSV file and returns how many there were.
+    pub fn export_audit_log(self: &Self, path: &Path, customer: Option<BankAccountID>, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> std::io::Result<usize> {
+        let events = self.audit_log.query(customer, from, to);
+        audit::export_csv(&events, path)?;
+        return Ok(events.len());
+    }
+
     // Money Account
     pub fn get_money_account_with_id_u32(self: &Self, id: MoneyAccountID) -> Option<Rc<RefCell<MoneyAccount>>> {
         let bank_account_id = self.money_accounts.get(&id)?;
-        let bank_account = self.bank_accounts.get(&bank_account_id)?;
+        let bank_account = self.bank_accounts.get(bank_account_id)?;
         return Some(bank_account.borrow().get_associated_money_account(id)?.clone());
     }
 
@@ -98,10 +625,10 @@ impl DatabaseHandler {
         return self.money_accounts.contains_key(&id);
     }
 
-    pub fn make_new_money_account(self: &mut Self, ptr_bank_account: Rc<RefCell<BankAccount>>, name: String, acc_type: MoneyAccountType) -> Rc<RefCell<MoneyAccount>> {
-        let mut new_account_number: u32 = rand::thread_rng().gen_range(0..999999);
+    pub fn make_new_money_account(self: &mut Self, ptr_bank_account: Rc<RefCell<BankAccount>>, name: String, acc_type: MoneyAccountType, currency: Currency) -> Rc<RefCell<MoneyAccount>> {
+        let mut new_account_number: u32 = self.rng.gen_range(0, 999999);
         while self.check_if_money_account_id_exists(new_account_number) {
-            new_account_number = rand::thread_rng().gen_range(0..999999);
+            new_account_number = self.rng.gen_range(0, 999999);
         }
 
         let mut bank_account = ptr_bank_account.borrow_mut();
@@ -110,14 +637,2776 @@ impl DatabaseHandler {
             account_type: acc_type, 
             id: new_account_number, 
             name: name, 
+            currency: currency,
             balance: 0, 
-            can_overdraft: false
+            pending_balance: 0,
+            can_overdraft: false,
+            credit_limit: 0,
+            locked: false,
         };
         let ptr_new_acc = Rc::new(RefCell::new(new_acc));
         bank_account.accounts.push(ptr_new_acc.clone());
         bank_account.accounts_map.insert(new_account_number, Rc::downgrade(&ptr_new_acc));
         self.money_accounts.insert(new_account_number, bank_account.id);
+        // the month the account is opened in is never charged, and products carry their own charges
+        let today = self.clock.today();
+        if !matches!(acc_type, MoneyAccountType::Loan | MoneyAccountType::TermDeposit | MoneyAccountType::Credit) {
+            self.maintenance_due.insert(new_account_number, first_of_next_month(first_of_next_month(today)));
+        }
+        self.statement_due.insert(new_account_number, first_of_next_month(today));
+        let (owner, detail) = (bank_account.id, format!("{} {} ({})", currency.code(), ptr_new_acc.borrow().get_str(), new_account_number));
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::MoneyAccountOpened, AuditOutcome::Success, detail);
         return ptr_new_acc;
     }
 
-}
\ No newline at end of file
+    // Deposits
+    pub fn get_clearing_policy(self: &Self) -> ClearingPolicy {
+        return self.clearing_policy;
+    }
+
+    pub fn set_clearing_policy(self: &mut Self, policy: ClearingPolicy) {
+        self.clearing_policy = policy;
+    }
+
+    // Cash is credited straight away. Other channels credit up to the policy's immediate
+    // availability and hold the rest until it clears.
+    pub fn deposit(self: &mut Self, id: MoneyAccountID, amount: i32, channel: DepositChannel, idempotency_key: Option<&str>) -> Result<i32, BalanceError> {
+        let fingerprint = format!("deposit:{}:{}:{}", id, amount, channel.description());
+        let now = self.clock.now();
+        if let Some(result) = self.replay_balance_result(idempotency_key, &fingerprint, now) {
+            return result;
+        }
+        let result = self.post_deposit(id, amount, channel);
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Balance(result), now);
+        return result;
+    }
+
+    fn post_deposit(self: &mut Self, id: MoneyAccountID, amount: i32, channel: DepositChannel) -> Result<i32, BalanceError> {
+        let today = self.clock.today();
+        if amount <= 0 {
+            return Err(BalanceError::InvalidAmount);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(BalanceError::NotFound)?;
+        if !self.owner_is_verified(id) {
+            return Err(BalanceError::NotVerified);
+        }
+        let mut money_account = ptr_money_account.borrow_mut();
+        if money_account.locked {
+            return Err(BalanceError::Locked);
+        }
+
+        let hold_days = self.clearing_policy.hold_days_for(channel);
+        if hold_days == 0 {
+            let new_balance = money_account.add_balance(amount)?;
+            let memo = format!("{} deposit", channel.description());
+            self.record_entry(id, TransactionKind::Deposit, amount, new_balance, self.clock.now(), memo);
+            return Ok(new_balance);
+        }
+
+        let released_amount = amount.min(self.clearing_policy.immediate_availability.get(money_account.currency).max(0));
+        let new_deposit = PendingDeposit {
+            id: self.next_deposit_id,
+            money_account: id,
+            channel: channel,
+            amount: amount,
+            released_amount: released_amount,
+            deposited_on: today,
+            available_on: clearing::add_business_days(today, hold_days),
+            status: DepositStatus::Pending,
+        };
+        self.next_deposit_id += 1;
+        money_account.add_pending_balance(new_deposit.held_amount());
+        let new_balance = money_account.add_balance(released_amount)?;
+        let memo = format!("{} deposit of {}, {} on hold until {}", channel.description(), money_account.format_amount(amount), money_account.format_amount(new_deposit.held_amount()), new_deposit.available_on);
+        self.record_entry(id, TransactionKind::Deposit, released_amount, new_balance, self.clock.now(), memo);
+        self.pending_deposits.insert(new_deposit.id, new_deposit);
+        return Ok(new_balance);
+    }
+
+    pub fn get_pending_deposits(self: &Self, id: MoneyAccountID) -> Vec<&PendingDeposit> {
+        let mut deposits: Vec<&PendingDeposit> = self.pending_deposits.values()
+            .filter(|deposit| deposit.money_account == id && deposit.status == DepositStatus::Pending)
+            .collect();
+        deposits.sort_by_key(|deposit| deposit.id);
+        return deposits;
+    }
+
+    // Releases every held deposit whose hold period has ended.
+    pub fn process_clearing(self: &mut Self) {
+        let today = self.clock.today();
+        let mut due: Vec<DepositID> = self.pending_deposits.values()
+            .filter(|deposit| deposit.is_due(today))
+            .map(|deposit| deposit.id)
+            .collect();
+        due.sort();
+        for deposit_id in due {
+            let (money_account_id, held_amount) = {
+                let deposit = &self.pending_deposits[&deposit_id];
+                (deposit.money_account, deposit.held_amount())
+            };
+            if let Some(ptr_money_account) = self.get_money_account_with_id_u32(money_account_id) {
+                if let Ok(new_balance) = ptr_money_account.borrow_mut().release_pending_balance(held_amount) {
+                    let memo = format!("Deposit #{} cleared", deposit_id);
+                    self.record_entry(money_account_id, TransactionKind::DepositCleared, held_amount, new_balance, self.clock.now(), memo);
+                }
+            }
+            let deposit = self.pending_deposits.get_mut(&deposit_id).unwrap();
+            deposit.released_amount = deposit.amount;
+            deposit.status = DepositStatus::Cleared;
+        }
+    }
+
+    // Reverses a cheque the paying bank returned: drops the held portion and debits whatever was
+    // already made available, even if that overdraws the account. Staff post these, and cheques
+    // above the adjustment threshold have to go through request_deposit_return instead.
+    pub fn bounce_deposit(self: &mut Self, acting: StaffID, deposit_id: DepositID) -> Result<i32, ClearingError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ClearingError::PermissionDenied)?;
+        let (money_account, amount) = self.check_bounce(deposit_id)?;
+        if self.adjustment_needs_approval(money_account, amount) {
+            return Err(ClearingError::ApprovalRequired);
+        }
+        return self.apply_bounce(acting, deposit_id);
+    }
+
+    pub fn request_deposit_return(self: &mut Self, acting: StaffID, deposit_id: DepositID) -> Result<ApprovalID, ClearingError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ClearingError::PermissionDenied)?;
+        let (money_account, amount) = self.check_bounce(deposit_id)?;
+        let operation = ApprovalOperation::ChequeReturn { deposit: deposit_id, id: money_account, amount: amount };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Staff(acting), Reviewer::Bank));
+    }
+
+    // The account and the cheque's full amount, as a debit: that is what the customer loses.
+    fn check_bounce(self: &Self, deposit_id: DepositID) -> Result<(MoneyAccountID, i32), ClearingError> {
+        let deposit = self.pending_deposits.get(&deposit_id).ok_or(ClearingError::NotFound)?;
+        if deposit.channel != DepositChannel::Cheque {
+            return Err(ClearingError::NotBounceable);
+        }
+        if deposit.status != DepositStatus::Pending {
+            return Err(ClearingError::AlreadySettled);
+        }
+        return Ok((deposit.money_account, -deposit.amount));
+    }
+
+    fn apply_bounce(self: &mut Self, acting: StaffID, deposit_id: DepositID) -> Result<i32, ClearingError> {
+        self.check_bounce(deposit_id)?;
+        let deposit = &self.pending_deposits[&deposit_id];
+        let held_amount = deposit.held_amount();
+        let released_amount = deposit.released_amount;
+        let ptr_money_account = self.get_money_account_with_id_u32(deposit.money_account).ok_or(ClearingError::NotFound)?;
+        let mut money_account = ptr_money_account.borrow_mut();
+        money_account.remove_pending_balance(held_amount);
+        let new_balance = money_account.get_balance() - released_amount;
+        let _ = money_account.set_balance(new_balance);
+        let memo = format!("Returned cheque, deposit #{}", deposit_id);
+        self.record_entry(money_account.id, TransactionKind::DepositReturned, -released_amount, new_balance, self.clock.now(), memo);
+        self.pending_deposits.get_mut(&deposit_id).unwrap().status = DepositStatus::Bounced;
+
+        let (money_account_id, currency, schedule) = (money_account.id, money_account.currency, self.fee_schedules.for_type(money_account.account_type));
+        drop(money_account);
+        if let Some(fee) = schedule.returned_payment_fee(&format!("cheque deposit #{}", deposit_id)) {
+            self.post_fee(money_account_id, &fee);
+        }
+        let owner = *self.money_accounts.get(&money_account_id).unwrap();
+        let amount = self.pending_deposits[&deposit_id].amount;
+        self.notifications.push(owner, format!("Your cheque deposit #{} of {} to account {} was returned unpaid.", deposit_id, currency.format(amount), money_account_id), self.clock.now());
+        self.audit_staff(acting, Some(owner), AuditAction::ManualAdjustment, AuditOutcome::Success, format!("Returned cheque deposit #{} on {}", deposit_id, money_account_id));
+        return Ok(ptr_money_account.borrow().get_balance());
+    }
+
+    // Card purchases
+    pub fn card_purchase(self: &mut Self, id: MoneyAccountID, amount: i32, merchant: &str, idempotency_key: Option<&str>) -> Result<i32, BalanceError> {
+        let fingerprint = format!("purchase:{}:{}:{}", id, amount, merchant);
+        let now = self.clock.now();
+        if let Some(result) = self.replay_balance_result(idempotency_key, &fingerprint, now) {
+            return result;
+        }
+        let result = self.post_purchase(id, amount, merchant);
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Balance(result), now);
+        return result;
+    }
+
+    fn post_purchase(self: &mut Self, id: MoneyAccountID, amount: i32, merchant: &str) -> Result<i32, BalanceError> {
+        if amount <= 0 {
+            return Err(BalanceError::InvalidAmount);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(BalanceError::NotFound)?;
+        if !self.owner_is_verified(id) {
+            return Err(BalanceError::NotVerified);
+        }
+        let new_balance = ptr_money_account.borrow_mut().subtract_balance(amount)?;
+        self.record_entry(id, TransactionKind::Purchase, -amount, new_balance, self.clock.now(), format!("Card purchase at {}", merchant));
+        return Ok(new_balance);
+    }
+
+    // Withdrawals
+    pub fn withdraw(self: &mut Self, id: MoneyAccountID, amount: i32, idempotency_key: Option<&str>) -> Result<i32, BalanceError> {
+        // see request_withdrawal
+        match self.outflow_reviewer(id, amount) {
+            Some(Reviewer::Bank) => return Err(BalanceError::ApprovalRequired),
+            Some(_) => return Err(BalanceError::GuardianApprovalRequired),
+            None => (),
+        }
+        let fingerprint = format!("withdraw:{}:{}", id, amount);
+        let now = self.clock.now();
+        if let Some(result) = self.replay_balance_result(idempotency_key, &fingerprint, now) {
+            return result;
+        }
+        let result = self.post_withdrawal(id, amount);
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Balance(result), now);
+        return result;
+    }
+
+    fn post_withdrawal(self: &mut Self, id: MoneyAccountID, amount: i32) -> Result<i32, BalanceError> {
+        if amount <= 0 {
+            return Err(BalanceError::InvalidAmount);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(BalanceError::NotFound)?;
+        if !self.owner_is_verified(id) {
+            return Err(BalanceError::NotVerified);
+        }
+        self.check_withdrawal_limit(id)?;
+        let fees = self.preview_fees(id, TransactionKind::Withdrawal, amount);
+        let new_balance = ptr_money_account.borrow_mut().subtract_balance(amount)?;
+        self.record_entry(id, TransactionKind::Withdrawal, -amount, new_balance, self.clock.now(), String::from("Withdrawal"));
+        for fee in fees {
+            self.post_fee(id, &fee);
+        }
+        return Ok(ptr_money_account.borrow().get_balance());
+    }
+
+    // Transfers
+    // Returns the new balance of the source account. Transfers to another customer count as
+    // outflows for approval, between the customer's own accounts they do not.
+    pub fn transfer(self: &mut Self, from: MoneyAccountID, to: MoneyAccountID, amount: i32, idempotency_key: Option<&str>) -> Result<i32, BalanceError> {
+        if self.money_accounts.get(&from) != self.money_accounts.get(&to) {
+            match self.outflow_reviewer(from, amount) {
+                Some(Reviewer::Bank) => return Err(BalanceError::ApprovalRequired),
+                Some(_) => return Err(BalanceError::GuardianApprovalRequired),
+                None => (),
+            }
+        }
+        let fingerprint = format!("transfer:{}:{}:{}", from, to, amount);
+        let now = self.clock.now();
+        if let Some(result) = self.replay_balance_result(idempotency_key, &fingerprint, now) {
+            return result;
+        }
+        let result = self.post_transfer(from, to, amount, "Transfer");
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Balance(result), now);
+        return result;
+    }
+
+    fn post_transfer(self: &mut Self, from: MoneyAccountID, to: MoneyAccountID, amount: i32, description: &str) -> Result<i32, BalanceError> {
+        if amount <= 0 {
+            return Err(BalanceError::InvalidAmount);
+        }
+        if from == to {
+            return Err(BalanceError::SameAccount);
+        }
+        let ptr_from_account = self.get_money_account_with_id_u32(from).ok_or(BalanceError::NotFound)?;
+        let ptr_to_account = self.get_money_account_with_id_u32(to).ok_or(BalanceError::NotFound)?;
+        if ptr_to_account.borrow().locked {
+            return Err(BalanceError::Locked);
+        }
+        if !self.owner_is_verified(from) {
+            return Err(BalanceError::NotVerified);
+        }
+        let from_currency = ptr_from_account.borrow().currency;
+        let to_currency = ptr_to_account.borrow().currency;
+        let now = self.clock.now();
+
+        // the customer gets the mid rate less the spread, the difference is the bank's
+        let mid_rate = self.rate_table.rate(from_currency, to_currency, now).ok_or(BalanceError::NoExchangeRate)?;
+        let customer_rate = if from_currency == to_currency { 1.0 } else { mid_rate * (1.0 - self.fx_policy.spread) };
+        let credited_amount = currency::convert(amount, from_currency, to_currency, customer_rate);
+        let spread_amount = currency::convert(amount, from_currency, to_currency, mid_rate) - credited_amount;
+
+        self.check_withdrawal_limit(from)?;
+        let fees = self.preview_fees(from, TransactionKind::TransferOut, amount);
+        let from_balance = ptr_from_account.borrow_mut().subtract_balance(amount)?;
+        let to_balance = ptr_to_account.borrow_mut().add_balance(credited_amount)?;
+        let conversion = if from_currency == to_currency {
+            String::new()
+        } else {
+            format!(" ({} at {:.4} {}/{})", from_currency.format(amount), customer_rate, to_currency.code(), from_currency.code())
+        };
+        self.record_entry(from, TransactionKind::TransferOut, -amount, from_balance, now, format!("{} to {}{}", description, to, conversion));
+        let credit_transaction = self.record_entry(to, TransactionKind::TransferIn, credited_amount, to_balance, now, format!("{} from {}{}", description, from, conversion));
+        if spread_amount > 0 {
+            let memo = format!("Spread on {} to {} conversion", from_currency.code(), to_currency.code());
+            self.ledger.record_income(IncomeKind::FxSpread, to_currency, spread_amount, now, memo, Some(credit_transaction));
+        }
+        for fee in fees {
+            self.post_fee(from, &fee);
+        }
+        return Ok(ptr_from_account.borrow().get_balance());
+    }
+
+    // Transaction history
+    pub fn get_transaction(self: &Self, id: TransactionID) -> Option<&Transaction> {
+        return self.ledger.get(id);
+    }
+
+    pub fn get_transaction_history(self: &Self, id: MoneyAccountID) -> Vec<&Transaction> {
+        return self.ledger.history_for(id);
+    }
+
+    pub fn get_reversals_of(self: &Self, id: TransactionID) -> Vec<&Transaction> {
+        return self.ledger.reversals_of(id);
+    }
+
+    // Staff correcting a posted entry. Partial reversals leave the rest open for later ones.
+    pub fn reverse_transaction(self: &mut Self, acting: StaffID, id: TransactionID, amount: Option<i32>, reason: ReasonCode, idempotency_key: Option<&str>) -> Result<TransactionID, ReversalError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ReversalError::PermissionDenied)?;
+        let fingerprint = format!("reverse:{}:{:?}:{}", id, amount, reason.description());
+        let now = self.clock.now();
+        if let Some(key) = idempotency_key {
+            match self.idempotency.check(key, &fingerprint, now) {
+                Replay::Outcome(StoredOutcome::Reversal(result)) => return result,
+                Replay::Outcome(_) | Replay::Mismatch => return Err(ReversalError::KeyReused),
+                Replay::Fresh => (),
+            }
+        }
+        let result = match self.check_reversal(id, amount) {
+            Ok((money_account, compensating_amount)) if self.adjustment_needs_approval(money_account, compensating_amount) => Err(ReversalError::ApprovalRequired),
+            Ok(_) => self.apply_reversal(acting, id, amount, reason),
+            Err(err) => Err(err),
+        };
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Reversal(result), now);
+        return result;
+    }
+
+    // Queues a reversal above the adjustment threshold for a second staff member to approve.
+    pub fn request_reversal(self: &mut Self, acting: StaffID, id: TransactionID, amount: Option<i32>, reason: ReasonCode) -> Result<ApprovalID, ReversalError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ReversalError::PermissionDenied)?;
+        let (money_account, compensating_amount) = self.check_reversal(id, amount)?;
+        let operation = ApprovalOperation::Reversal { transaction: id, id: money_account, amount: compensating_amount, reason: reason };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Staff(acting), Reviewer::Bank));
+    }
+
+    // The account a reversal would post to and the signed amount it would post.
+    fn check_reversal(self: &Self, id: TransactionID, amount: Option<i32>) -> Result<(MoneyAccountID, i32), ReversalError> {
+        if self.is_pending_payment_debit(id) {
+            return Err(ReversalError::PaymentPending);
+        }
+        if !self.reversible_by_hand(id) {
+            return Err(ReversalError::NotReversible);
+        }
+        return self.reversal_amount(id, amount);
+    }
+
+    fn apply_reversal(self: &mut Self, acting: StaffID, id: TransactionID, amount: Option<i32>, reason: ReasonCode) -> Result<TransactionID, ReversalError> {
+        let reversal = self.post_reversal(id, amount, reason)?;
+        let (money_account, reversed) = {
+            let entry = self.ledger.get(reversal).unwrap();
+            (entry.money_account, entry.amount)
+        };
+        let owner = self.money_accounts.get(&money_account).copied();
+        if let Some(owner) = owner {
+            let currency = self.get_money_account_with_id_u32(money_account).unwrap().borrow().currency;
+            self.notifications.push(owner, format!("Transaction #{} on account {} was reversed ({}): {}.", id, money_account, reason.description().to_lowercase(), currency.format(reversed)), self.clock.now());
+        }
+        self.audit_staff(acting, owner, AuditAction::ManualAdjustment, AuditOutcome::Success, format!("Reversed #{} as #{}: {}", id, reversal, reason.description()));
+        return Ok(reversal);
+    }
+
+    // The money is already on its way: it comes back only if the payment network rejects it.
+    fn is_pending_payment_debit(self: &Self, id: TransactionID) -> bool {
+        return self.outbound_payments.iter().any(|payment| payment.debit_transaction == id && payment.status == PaymentStatus::Pending);
+    }
+
+    // A transfer has a leg on each account, and for loans and term deposits the product's own
+    // records track the money too. Reversing one entry on its own would leave the other side out
+    // of step, so those are corrected with an adjustment instead.
+    fn reversible_by_hand(self: &Self, id: TransactionID) -> bool {
+        let original = match self.ledger.get(id) {
+            Some(original) => original,
+            None => return true, // reported as not found
+        };
+        if matches!(original.kind, TransactionKind::TransferIn | TransactionKind::TransferOut) {
+            return false;
+        }
+        return self.get_money_account_with_id_u32(original.money_account)
+            .is_none_or(|acc| !matches!(acc.borrow().account_type, MoneyAccountType::Loan | MoneyAccountType::TermDeposit));
+    }
+
+    fn post_reversal(self: &mut Self, id: TransactionID, amount: Option<i32>, reason: ReasonCode) -> Result<TransactionID, ReversalError> {
+        let (money_account, compensating_amount) = self.reversal_amount(id, amount)?;
+        let ptr_money_account = self.get_money_account_with_id_u32(money_account).ok_or(ReversalError::AccountNotFound)?;
+        let result = if compensating_amount < 0 {
+            ptr_money_account.borrow_mut().subtract_balance(-compensating_amount)
+        } else {
+            ptr_money_account.borrow_mut().add_balance(compensating_amount)
+        };
+        let new_balance = result.map_err(|err| match err {
+            BalanceError::Locked => ReversalError::Locked,
+            _ => ReversalError::NotEnough,
+        })?;
+        return self.record_reversal_entry(id, compensating_amount, new_balance, reason).ok_or(ReversalError::NotFound);
+    }
+
+    // Reversing `amount` of an entry (whatever is left of it if None) posts the opposite sign.
+    fn reversal_amount(self: &Self, id: TransactionID, amount: Option<i32>) -> Result<(MoneyAccountID, i32), ReversalError> {
+        let original = self.ledger.get(id).ok_or(ReversalError::NotFound)?;
+        if original.is_reversal() {
+            return Err(ReversalError::NotReversible);
+        }
+        let remaining = original.reversible_amount();
+        if remaining <= 0 {
+            return Err(ReversalError::AlreadyReversed);
+        }
+        let amount = amount.unwrap_or(remaining);
+        if amount <= 0 {
+            return Err(ReversalError::InvalidAmount);
+        }
+        if amount > remaining {
+            return Err(ReversalError::ExceedsOriginal);
+        }
+        return Ok((original.money_account, if original.amount > 0 { -amount } else { amount }));
+    }
+
+    // Idempotency
+    pub fn set_idempotency_retention(self: &mut Self, retention: Duration) {
+        self.idempotency.set_retention(retention);
+    }
+
+    // Some(result) means the call must not run again: either it is a replay or the key clashes.
+    fn replay_balance_result(self: &mut Self, idempotency_key: Option<&str>, fingerprint: &str, now: NaiveDateTime) -> Option<Result<i32, BalanceError>> {
+        match self.idempotency.check(idempotency_key?, fingerprint, now) {
+            Replay::Outcome(StoredOutcome::Balance(result)) => Some(result),
+            Replay::Outcome(_) | Replay::Mismatch => Some(Err(BalanceError::KeyReused)),
+            Replay::Fresh => None,
+        }
+    }
+
+    // Errors that may clear up are not kept, so retrying with the same key runs the call again.
+    fn remember_outcome(self: &mut Self, idempotency_key: Option<&str>, fingerprint: String, outcome: StoredOutcome, now: NaiveDateTime) {
+        match idempotency_key {
+            Some(key) if outcome.is_final() => self.idempotency.store(key, fingerprint, outcome, now),
+            _ => (),
+        }
+    }
+
+    // Standing orders
+    pub fn set_scheduler_policy(self: &mut Self, policy: SchedulerPolicy) {
+        self.scheduler_policy = policy;
+    }
+
+    // Orders whose every payment is above the approval threshold go through
+    // request_standing_order instead.
+    pub fn create_standing_order(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, to: MoneyAccountID, amount: i32, frequency: Frequency, start: NaiveDate) -> Result<StandingOrderID, ScheduleError> {
+        self.check_standing_order(owner, from, to, amount, start)?;
+        match self.outflow_reviewer(from, amount) {
+            Some(Reviewer::Bank) => return Err(ScheduleError::ApprovalRequired),
+            Some(_) => return Err(ScheduleError::GuardianApprovalRequired),
+            None => (),
+        }
+        return Ok(self.insert_standing_order(owner, from, to, amount, frequency, start));
+    }
+
+    pub fn request_standing_order(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, to: MoneyAccountID, amount: i32, frequency: Frequency, start: NaiveDate) -> Result<ApprovalID, ScheduleError> {
+        self.check_standing_order(owner, from, to, amount, start)?;
+        let reviewer = self.outflow_reviewer(from, amount).unwrap_or(Reviewer::Bank);
+        let operation = ApprovalOperation::StandingOrder { owner: owner, from: from, to: to, amount: amount, frequency: frequency, start: start };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Customer(owner), reviewer));
+    }
+
+    fn check_standing_order(self: &Self, owner: BankAccountID, from: MoneyAccountID, to: MoneyAccountID, amount: i32, start: NaiveDate) -> Result<(), ScheduleError> {
+        if self.money_accounts.get(&from) != Some(&owner) || !self.check_if_money_account_id_exists(to) {
+            return Err(ScheduleError::AccountNotFound);
+        }
+        if from == to {
+            return Err(ScheduleError::SameAccount);
+        }
+        if amount <= 0 {
+            return Err(ScheduleError::InvalidAmount);
+        }
+        if start < self.clock.today() {
+            return Err(ScheduleError::InvalidDate);
+        }
+        return Ok(());
+    }
+
+    fn insert_standing_order(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, to: MoneyAccountID, amount: i32, frequency: Frequency, start: NaiveDate) -> StandingOrderID {
+        let id = self.next_standing_order_id;
+        self.next_standing_order_id += 1;
+        self.standing_orders.insert(id, StandingOrder {
+            id: id,
+            owner: owner,
+            from: from,
+            to: to,
+            amount: amount,
+            frequency: frequency,
+            next_run: frequency.first_run_from(start),
+            retry_on: None,
+            failed_attempts: 0,
+            status: OrderStatus::Active,
+        });
+        return id;
+    }
+
+    pub fn get_standing_orders(self: &Self, owner: BankAccountID) -> Vec<&StandingOrder> {
+        let mut orders: Vec<&StandingOrder> = self.standing_orders.values()
+            .filter(|order| order.owner == owner)
+            .collect();
+        orders.sort_by_key(|order| order.id);
+        return orders;
+    }
+
+    // Raising the amount above the approval threshold needs a new order through request_standing_order.
+    pub fn edit_standing_order(self: &mut Self, owner: BankAccountID, id: StandingOrderID, amount: Option<i32>, next_run: Option<NaiveDate>) -> Result<(), ScheduleError> {
+        let today = self.clock.today();
+        let from = self.standing_orders.get(&id).filter(|order| order.owner == owner).ok_or(ScheduleError::NotFound)?.from;
+        match amount.and_then(|amount| self.outflow_reviewer(from, amount)) {
+            Some(Reviewer::Bank) => return Err(ScheduleError::ApprovalRequired),
+            Some(_) => return Err(ScheduleError::GuardianApprovalRequired),
+            None => (),
+        }
+        let order = self.standing_orders.get_mut(&id)
+            .filter(|order| order.owner == owner)
+            .ok_or(ScheduleError::NotFound)?;
+        if order.status != OrderStatus::Active {
+            return Err(ScheduleError::NotActive);
+        }
+        if amount.is_some_and(|amount| amount <= 0) {
+            return Err(ScheduleError::InvalidAmount);
+        }
+        if next_run.is_some_and(|date| date < today) {
+            return Err(ScheduleError::InvalidDate);
+        }
+        if let Some(amount) = amount {
+            order.amount = amount;
+        }
+        if let Some(date) = next_run {
+            order.next_run = order.frequency.first_run_from(date);
+            order.retry_on = None;
+            order.failed_attempts = 0;
+        }
+        return Ok(());
+    }
+
+    pub fn cancel_standing_order(self: &mut Self, owner: BankAccountID, id: StandingOrderID) -> Result<(), ScheduleError> {
+        let order = self.standing_orders.get_mut(&id)
+            .filter(|order| order.owner == owner)
+            .ok_or(ScheduleError::NotFound)?;
+        if order.status != OrderStatus::Active {
+            return Err(ScheduleError::NotActive);
+        }
+        order.status = OrderStatus::Cancelled;
+        return Ok(());
+    }
+
+    // Runs every occurrence that has fallen due, catching up if the clock jumped ahead.
+    // Short of funds, an occurrence is retried the next day until the policy's retries run out.
+    pub fn run_standing_orders(self: &mut Self) {
+        let today = self.clock.today();
+        let mut due: Vec<StandingOrderID> = self.standing_orders.values()
+            .filter(|order| order.is_due(today))
+            .map(|order| order.id)
+            .collect();
+        due.sort();
+        for order_id in due {
+            while self.standing_orders[&order_id].is_due(today) {
+                let (owner, from, to, amount, frequency) = {
+                    let order = &self.standing_orders[&order_id];
+                    (order.owner, order.from, order.to, order.amount, order.frequency)
+                };
+                let description = format!("Standing order #{}", order_id);
+                let result = self.post_transfer(from, to, amount, &description);
+                let now = self.clock.now();
+                let order = self.standing_orders.get_mut(&order_id).unwrap();
+                match result {
+                    Ok(_) => order.advance(),
+                    Err(BalanceError::NotEnough) if order.failed_attempts < self.scheduler_policy.max_retries => {
+                        order.failed_attempts += 1;
+                        let retry_on = today + Duration::days(1);
+                        order.retry_on = Some(retry_on);
+                        let message = format!("{} of {} could not be paid due to insufficient funds. We will try again on {}.", description, self.format_for_account(from, amount), retry_on);
+                        self.notifications.push(owner, message, now);
+                    },
+                    // the limit resets with the next cycle: a one-off payment waits for it, a
+                    // recurring one skips this occurrence
+                    Err(BalanceError::WithdrawalLimitReached) if frequency == Frequency::Once => {
+                        let retry_on = first_of_next_month(today);
+                        order.retry_on = Some(retry_on);
+                        let message = format!("{} of {} could not be paid because the account's withdrawal limit for this cycle has been reached. We will try again on {}.", description, self.format_for_account(from, amount), retry_on);
+                        self.notifications.push(owner, message, now);
+                    },
+                    Err(BalanceError::WithdrawalLimitReached) => {
+                        order.advance();
+                        let next_run = order.next_run;
+                        let message = format!("{} of {} was skipped because the account's withdrawal limit for this cycle has been reached. The next payment is due on {}.", description, self.format_for_account(from, amount), next_run);
+                        self.notifications.push(owner, message, now);
+                    },
+                    Err(err) => {
+                        order.advance();
+                        if frequency == Frequency::Once || err != BalanceError::NotEnough {
+                            order.status = OrderStatus::Failed;
+                        }
+                        let message = format!("{} of {} failed: {}.", description, self.format_for_account(from, amount), err.as_str());
+                        self.notifications.push(owner, message, now);
+                    },
+                }
+            }
+        }
+    }
+
+    // Payees
+    pub fn add_payee(self: &mut Self, owner: BankAccountID, name: String, external_account: &str, reference: String) -> Result<PayeeID, PaymentError> {
+        if name.trim().is_empty() || !payments::is_valid_external_account(external_account) {
+            return Err(PaymentError::InvalidPayee);
+        }
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(PaymentError::AccountNotFound)?;
+        let id = self.next_payee_id;
+        self.next_payee_id += 1;
+        ptr_bank_account.borrow_mut().payees.push(Payee {
+            id: id,
+            name: name,
+            external_account: payments::normalize_external_account(external_account),
+            reference: reference,
+        });
+        return Ok(id);
+    }
+
+    pub fn remove_payee(self: &mut Self, owner: BankAccountID, id: PayeeID) -> Result<(), PaymentError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(PaymentError::AccountNotFound)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let index = bank_account.payees.iter().position(|payee| payee.id == id).ok_or(PaymentError::PayeeNotFound)?;
+        bank_account.payees.remove(index);
+        return Ok(());
+    }
+
+    pub fn get_payees(self: &Self, owner: BankAccountID) -> Vec<Payee> {
+        match self.get_bank_account_with_id_u32(owner) {
+            Some(acc) => return acc.borrow().payees.clone(),
+            None => return vec![],
+        }
+    }
+
+    // Outbound payments
+    pub fn set_payment_network(self: &mut Self, network: Box<dyn PaymentNetwork>) {
+        self.payment_network = network;
+    }
+
+    // Debits the account straight away and queues the payment for the payment network.
+    // `reference` falls back to the payee's default reference. Payments above the approval
+    // threshold go through request_bill_payment instead.
+    pub fn pay_bill(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, payee_id: PayeeID, amount: i32, reference: Option<String>, idempotency_key: Option<&str>) -> Result<PaymentID, PaymentError> {
+        match self.outflow_reviewer(from, amount) {
+            Some(Reviewer::Bank) => return Err(PaymentError::ApprovalRequired),
+            Some(_) => return Err(PaymentError::GuardianApprovalRequired),
+            None => (),
+        }
+        let fingerprint = format!("pay:{}:{}:{}:{}:{:?}", owner, from, payee_id, amount, reference);
+        let now = self.clock.now();
+        if let Some(key) = idempotency_key {
+            match self.idempotency.check(key, &fingerprint, now) {
+                Replay::Outcome(StoredOutcome::Payment(result)) => return result,
+                Replay::Outcome(_) | Replay::Mismatch => return Err(PaymentError::KeyReused),
+                Replay::Fresh => (),
+            }
+        }
+        let result = self.post_bill_payment(owner, from, payee_id, amount, reference);
+        self.remember_outcome(idempotency_key, fingerprint, StoredOutcome::Payment(result), now);
+        return result;
+    }
+
+    pub fn request_bill_payment(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, payee_id: PayeeID, amount: i32, reference: Option<String>) -> Result<ApprovalID, PaymentError> {
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if self.money_accounts.get(&from) != Some(&owner) {
+            return Err(PaymentError::AccountNotFound);
+        }
+        if !self.get_payees(owner).iter().any(|payee| payee.id == payee_id) {
+            return Err(PaymentError::PayeeNotFound);
+        }
+        let reviewer = self.outflow_reviewer(from, amount).unwrap_or(Reviewer::Bank);
+        let operation = ApprovalOperation::BillPayment { owner: owner, from: from, payee: payee_id, amount: amount, reference: reference };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Customer(owner), reviewer));
+    }
+
+    fn post_bill_payment(self: &mut Self, owner: BankAccountID, from: MoneyAccountID, payee_id: PayeeID, amount: i32, reference: Option<String>) -> Result<PaymentID, PaymentError> {
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if self.money_accounts.get(&from) != Some(&owner) {
+            return Err(PaymentError::AccountNotFound);
+        }
+        if !self.is_verified(owner) {
+            return Err(PaymentError::NotVerified);
+        }
+        let payee = self.get_payees(owner).into_iter()
+            .find(|payee| payee.id == payee_id)
+            .ok_or(PaymentError::PayeeNotFound)?;
+        let ptr_money_account = self.get_money_account_with_id_u32(from).ok_or(PaymentError::AccountNotFound)?;
+        self.check_withdrawal_limit(from).map_err(|_| PaymentError::WithdrawalLimitReached)?;
+        let fees = self.preview_fees(from, TransactionKind::BillPayment, amount);
+        let new_balance = ptr_money_account.borrow_mut().subtract_balance(amount).map_err(|err| match err {
+            BalanceError::Locked => PaymentError::AccountLocked,
+            _ => PaymentError::NotEnough,
+        })?;
+        let currency = ptr_money_account.borrow().currency;
+
+        let now = self.clock.now();
+        let reference = reference.unwrap_or(payee.reference.clone());
+        let memo = format!("To {} ({}) ref {}", payee.name, payee.external_account, reference);
+        let debit_transaction = self.record_entry(from, TransactionKind::BillPayment, -amount, new_balance, now, memo);
+        let id = self.outbound_payments.len() as PaymentID + 1;
+        self.outbound_payments.push(OutboundPayment {
+            id: id,
+            owner: owner,
+            from: from,
+            payee: payee,
+            amount: amount,
+            currency: currency,
+            reference: reference,
+            status: PaymentStatus::Pending,
+            created_at: now,
+            debit_transaction: debit_transaction,
+            rejection_reason: None,
+        });
+        for fee in fees {
+            self.post_fee(from, &fee);
+        }
+        return Ok(id);
+    }
+
+    pub fn get_outbound_payment(self: &Self, id: PaymentID) -> Option<&OutboundPayment> {
+        return self.outbound_payments.iter().find(|payment| payment.id == id);
+    }
+
+    pub fn get_outbound_payments(self: &Self, owner: BankAccountID) -> Vec<&OutboundPayment> {
+        return self.outbound_payments.iter().filter(|payment| payment.owner == owner).collect();
+    }
+
+    // The outbound queue, oldest first.
+    pub fn get_pending_outbound_payments(self: &Self) -> Vec<&OutboundPayment> {
+        return self.outbound_payments.iter().filter(|payment| payment.status == PaymentStatus::Pending).collect();
+    }
+
+    pub fn acknowledge_outbound_payment(self: &mut Self, id: PaymentID) -> Result<(), PaymentError> {
+        let payment = self.outbound_payments.iter_mut().find(|payment| payment.id == id).ok_or(PaymentError::NotFound)?;
+        if payment.status != PaymentStatus::Pending {
+            return Err(PaymentError::NotPending);
+        }
+        payment.status = PaymentStatus::Sent;
+        return Ok(());
+    }
+
+    // Marks the payment rejected and credits the money back with a reversal of the original debit.
+    pub fn reject_outbound_payment(self: &mut Self, id: PaymentID, reason: String) -> Result<(), PaymentError> {
+        let payment = self.outbound_payments.iter().find(|payment| payment.id == id).ok_or(PaymentError::NotFound)?;
+        if payment.status != PaymentStatus::Pending {
+            return Err(PaymentError::NotPending);
+        }
+        let (owner, from, debit_transaction, amount) = (payment.owner, payment.from, payment.debit_transaction, payment.amount);
+        let message = format!("Your payment of {} to {} was rejected ({}). The money has been returned to your account.", payment.currency.format(amount), payment.payee.name, reason);
+        self.post_reversal(debit_transaction, None, ReasonCode::PaymentRejected).map_err(|err| match err {
+            ReversalError::AccountNotFound => PaymentError::AccountNotFound,
+            _ => PaymentError::DebitNotReversible,
+        })?;
+
+        let payment = self.outbound_payments.iter_mut().find(|payment| payment.id == id).unwrap();
+        payment.status = PaymentStatus::Rejected;
+        payment.rejection_reason = Some(reason);
+        let now = self.clock.now();
+        self.notifications.push(owner, message, now);
+        if let Some(fee) = self.fee_schedule_for(from).and_then(|schedule| schedule.returned_payment_fee(&format!("payment #{}", id))) {
+            self.post_fee(from, &fee);
+        }
+        return Ok(());
+    }
+
+    // Submits the queue to the payment network and applies its responses.
+    pub fn settle_outbound_payments(self: &mut Self) {
+        let mut responses: Vec<(PaymentID, NetworkResponse)> = vec![];
+        for payment in self.outbound_payments.iter().filter(|payment| payment.status == PaymentStatus::Pending) {
+            responses.push((payment.id, self.payment_network.submit(payment)));
+        }
+        for (id, response) in responses {
+            let _ = match response {
+                NetworkResponse::Acknowledged => self.acknowledge_outbound_payment(id),
+                NetworkResponse::Rejected(reason) => self.reject_outbound_payment(id, reason),
+            };
+        }
+    }
+
+    // Fees
+    pub fn set_fee_schedules(self: &mut Self, schedules: FeeSchedules) {
+        self.fee_schedules = schedules;
+    }
+
+    pub fn fee_schedule_for(self: &Self, id: MoneyAccountID) -> Option<FeeSchedule> {
+        let ptr_money_account = self.get_money_account_with_id_u32(id)?;
+        let acc_type = ptr_money_account.borrow().account_type;
+        return Some(self.fee_schedules.for_type(acc_type));
+    }
+
+    // Fees a debit of `amount` would incur right now, so the customer can see them before confirming.
+    pub fn preview_fees(self: &Self, id: MoneyAccountID, kind: TransactionKind, amount: i32) -> Vec<FeeQuote> {
+        let mut fees: Vec<FeeQuote> = vec![];
+        let (schedule, ptr_money_account) = match (self.fee_schedule_for(id), self.get_money_account_with_id_u32(id)) {
+            (Some(schedule), Some(acc)) => (schedule, acc),
+            _ => return fees,
+        };
+        if kind == TransactionKind::Withdrawal {
+            let withdrawals = self.count_transactions_this_month(id, &[TransactionKind::Withdrawal]);
+            if let Some(fee) = schedule.withdrawal_fee(withdrawals) {
+                fees.push(fee);
+            }
+        }
+        if OUTGOING_KINDS.contains(&kind) && self.get_remaining_withdrawals(id) == Some(0) {
+            if let Some(fee) = self.savings_withdrawal_limit.excess_withdrawal_fee() {
+                fees.push(fee);
+            }
+        }
+        let money_account = ptr_money_account.borrow();
+        if money_account.can_overdraft {
+            let fees_so_far: i32 = fees.iter().map(|fee| fee.amount).sum();
+            if let Some(fee) = schedule.overdraft_fee(money_account.balance - amount - fees_so_far) {
+                fees.push(fee);
+            }
+        }
+        return fees;
+    }
+
+    // Fees always post, even when they take the account below zero.
+    fn post_fee(self: &mut Self, id: MoneyAccountID, fee: &FeeQuote) -> Option<TransactionID> {
+        let ptr_money_account = self.get_money_account_with_id_u32(id)?;
+        let new_balance = ptr_money_account.borrow_mut().add_balance(-fee.amount).ok()?;
+        let currency = ptr_money_account.borrow().currency;
+        let now = self.clock.now();
+        let transaction = self.record_entry(id, TransactionKind::Fee, -fee.amount, new_balance, now, fee.memo.clone());
+        self.ledger.record_income(IncomeKind::Fee, currency, fee.amount, now, fee.memo.clone(), Some(transaction));
+        return Some(transaction);
+    }
+
+    // Refunds a fee in full and takes it back out of fee income. Staff decide these, and refunds
+    // above the adjustment threshold have to go through request_fee_waiver instead.
+    pub fn waive_fee(self: &mut Self, acting: StaffID, id: TransactionID) -> Result<TransactionID, ReversalError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ReversalError::PermissionDenied)?;
+        let (money_account, refund) = self.check_fee_waiver(id)?;
+        if self.adjustment_needs_approval(money_account, refund) {
+            return Err(ReversalError::ApprovalRequired);
+        }
+        return self.apply_fee_waiver(acting, id);
+    }
+
+    pub fn request_fee_waiver(self: &mut Self, acting: StaffID, id: TransactionID) -> Result<ApprovalID, ReversalError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment).map_err(|_| ReversalError::PermissionDenied)?;
+        let (money_account, refund) = self.check_fee_waiver(id)?;
+        let operation = ApprovalOperation::FeeWaiver { transaction: id, id: money_account, amount: refund };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Staff(acting), Reviewer::Bank));
+    }
+
+    fn check_fee_waiver(self: &Self, id: TransactionID) -> Result<(MoneyAccountID, i32), ReversalError> {
+        if self.ledger.get(id).ok_or(ReversalError::NotFound)?.kind != TransactionKind::Fee {
+            return Err(ReversalError::NotAFee);
+        }
+        return self.reversal_amount(id, None);
+    }
+
+    fn apply_fee_waiver(self: &mut Self, acting: StaffID, id: TransactionID) -> Result<TransactionID, ReversalError> {
+        self.check_fee_waiver(id)?;
+        let fee = self.ledger.get(id).unwrap();
+        let (money_account, memo) = (fee.money_account, fee.memo.clone());
+        let reversal = self.post_reversal(id, None, ReasonCode::FeeWaived)?;
+        let refunded = self.ledger.get(reversal).map(|txn| txn.amount).unwrap_or(0);
+        if let Some(ptr_money_account) = self.get_money_account_with_id_u32(money_account) {
+            let currency = ptr_money_account.borrow().currency;
+            self.ledger.record_income(IncomeKind::Fee, currency, -refunded, self.clock.now(), format!("Waived: {}", memo), Some(reversal));
+        }
+        let owner = self.money_accounts.get(&money_account).copied();
+        if let Some(owner) = owner {
+            self.notifications.push(owner, format!("We have refunded a fee on account {}: {}.", money_account, memo), self.clock.now());
+        }
+        self.audit_staff(acting, owner, AuditAction::ManualAdjustment, AuditOutcome::Success, format!("Waived fee #{} as #{}", id, reversal));
+        return Ok(reversal);
+    }
+
+    // Charges the maintenance fee for every monthly cycle that has ended since the last run.
+    pub fn assess_maintenance_fees(self: &mut Self) {
+        let today = self.clock.today();
+        let mut due: Vec<MoneyAccountID> = self.maintenance_due.iter()
+            .filter(|(_, due_on)| **due_on <= today)
+            .map(|(id, _)| *id)
+            .collect();
+        due.sort();
+        for id in due {
+            while self.maintenance_due[&id] <= today {
+                let cycle_end = self.maintenance_due[&id];
+                let cycle_start = first_of_previous_month(cycle_end);
+                let average_balance = self.ledger.average_daily_balance(id, cycle_start, cycle_end);
+                let period = cycle_start.format("%B %Y").to_string();
+                if let Some(fee) = self.fee_schedule_for(id).and_then(|schedule| schedule.maintenance_fee(average_balance, &period)) {
+                    self.post_fee(id, &fee);
+                }
+                self.maintenance_due.insert(id, first_of_next_month(cycle_end));
+            }
+        }
+    }
+
+    // Savings withdrawal limits
+    pub fn set_savings_withdrawal_limit(self: &mut Self, limit: WithdrawalLimit) {
+        self.savings_withdrawal_limit = limit;
+    }
+
+    // Withdrawals left in the current statement cycle, or None if the account is not limited.
+    pub fn get_remaining_withdrawals(self: &Self, id: MoneyAccountID) -> Option<u32> {
+        let ptr_money_account = self.get_money_account_with_id_u32(id)?;
+        if ptr_money_account.borrow().account_type != MoneyAccountType::Savings {
+            return None;
+        }
+        let used = self.count_transactions_this_month(id, &OUTGOING_KINDS);
+        return Some(self.savings_withdrawal_limit.max_per_cycle.saturating_sub(used));
+    }
+
+    // Once the limit is used up, money can only leave if the policy charges a fee instead.
+    fn check_withdrawal_limit(self: &Self, id: MoneyAccountID) -> Result<(), BalanceError> {
+        if self.get_remaining_withdrawals(id) == Some(0) && self.savings_withdrawal_limit.excess_fee.is_none() {
+            return Err(BalanceError::WithdrawalLimitReached);
+        }
+        return Ok(());
+    }
+
+    fn count_transactions_this_month(self: &Self, id: MoneyAccountID, kinds: &[TransactionKind]) -> u32 {
+        let today = self.clock.today();
+        return self.ledger.history_for(id).iter()
+            .filter(|txn| kinds.contains(&txn.kind))
+            .filter(|txn| txn.posted_at.year() == today.year() && txn.posted_at.month() == today.month())
+            .count() as u32;
+    }
+
+    // Term deposits
+    pub fn set_term_deposit_policy(self: &mut Self, policy: TermDepositPolicy) {
+        self.term_deposit_policy = policy;
+    }
+
+    pub fn get_term_deposit_policy(self: &Self) -> TermDepositPolicy {
+        return self.term_deposit_policy;
+    }
+
+    // Opens a locked term deposit account funded from one of the owner's checkings accounts,
+    // which also receives interest and payouts. Returns the new account's number.
+    pub fn open_term_deposit(self: &mut Self, owner: BankAccountID, funding: MoneyAccountID, name: String, terms: TermDepositTerms) -> Result<MoneyAccountID, TermDepositError> {
+        if self.money_accounts.get(&funding) != Some(&owner) {
+            return Err(TermDepositError::AccountNotFound);
+        }
+        if !self.is_verified(owner) {
+            return Err(TermDepositError::NotVerified);
+        }
+        let ptr_funding_account = self.get_money_account_with_id_u32(funding).ok_or(TermDepositError::AccountNotFound)?;
+        if ptr_funding_account.borrow().account_type != MoneyAccountType::Checking {
+            return Err(TermDepositError::NotChecking);
+        }
+        let policy = self.term_deposit_policy;
+        if terms.principal < policy.min_principal {
+            return Err(TermDepositError::InvalidAmount);
+        }
+        if terms.term_months < policy.min_term_months || terms.term_months > policy.max_term_months {
+            return Err(TermDepositError::InvalidTerm);
+        }
+        if !(0.0..1.0).contains(&terms.annual_rate) {
+            return Err(TermDepositError::InvalidRate);
+        }
+        // no funding a term deposit from an overdraft
+        if ptr_funding_account.borrow().balance < terms.principal {
+            return Err(TermDepositError::NotEnough);
+        }
+        let today = self.clock.today();
+        let maturity_date = term_deposits::add_months(today, terms.term_months).ok_or(TermDepositError::InvalidTerm)?;
+
+        let currency = ptr_funding_account.borrow().currency;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(TermDepositError::AccountNotFound)?;
+        let ptr_new_acc = self.make_new_money_account(ptr_bank_account, name, MoneyAccountType::TermDeposit, currency);
+        let id = ptr_new_acc.borrow().id;
+        ptr_new_acc.borrow_mut().locked = true;
+        self.post_internal_transfer(funding, id, terms.principal, "Term deposit funding");
+
+        self.term_deposits.insert(id, TermDeposit {
+            money_account: id,
+            owner: owner,
+            linked_account: funding,
+            terms: terms,
+            term_start: today,
+            maturity_date: maturity_date,
+            periods_paid: 0,
+            interest_paid: 0,
+            status: TermDepositStatus::Active,
+        });
+        return Ok(id);
+    }
+
+    pub fn get_term_deposit(self: &Self, id: MoneyAccountID) -> Option<&TermDeposit> {
+        return self.term_deposits.get(&id);
+    }
+
+    pub fn get_term_deposits(self: &Self, owner: BankAccountID) -> Vec<&TermDeposit> {
+        let mut deposits: Vec<&TermDeposit> = self.term_deposits.values()
+            .filter(|deposit| deposit.owner == owner)
+            .collect();
+        deposits.sort_by_key(|deposit| deposit.term_start);
+        return deposits;
+    }
+
+    // Interest earned so far is paid, the policy's penalty is taken, and the rest goes to the linked account.
+    // The penalty can eat into the principal but never below zero.
+    pub fn quote_early_closure(self: &Self, owner: BankAccountID, id: MoneyAccountID) -> Result<EarlyClosureQuote, TermDepositError> {
+        let deposit = self.term_deposits.get(&id)
+            .filter(|deposit| deposit.owner == owner)
+            .ok_or(TermDepositError::NotFound)?;
+        if deposit.status != TermDepositStatus::Active {
+            return Err(TermDepositError::NotActive);
+        }
+        let balance = self.get_money_account_with_id_u32(id).ok_or(TermDepositError::AccountNotFound)?.borrow().balance;
+        let accrued_interest = deposit.accrued_interest(self.clock.today());
+        let penalty = deposit.early_closure_penalty(&self.term_deposit_policy).min(balance + accrued_interest);
+        return Ok(EarlyClosureQuote {
+            accrued_interest: accrued_interest,
+            penalty: penalty,
+            payout: balance + accrued_interest - penalty,
+        });
+    }
+
+    // Returns the amount paid out to the linked account.
+    pub fn close_term_deposit_early(self: &mut Self, owner: BankAccountID, id: MoneyAccountID) -> Result<i32, TermDepositError> {
+        let quote = self.quote_early_closure(owner, id)?;
+        if quote.accrued_interest > 0 {
+            self.post_interest(id, quote.accrued_interest, String::from("Interest to early closure"));
+        }
+        if quote.penalty > 0 {
+            let memo = format!("{} ({} days' interest)", FeeKind::EarlyClosure.description(), self.term_deposit_policy.early_closure_penalty_days);
+            self.post_fee(id, &FeeQuote { kind: FeeKind::EarlyClosure, amount: quote.penalty, memo: memo });
+        }
+        let linked_account = self.term_deposits[&id].linked_account;
+        if quote.payout > 0 {
+            self.post_internal_transfer(id, linked_account, quote.payout, "Term deposit closed early");
+        }
+        self.term_deposits.get_mut(&id).unwrap().status = TermDepositStatus::ClosedEarly;
+        return Ok(quote.payout);
+    }
+
+    // Pays interest that has fallen due and handles maturities, catching up if the clock jumped ahead.
+    pub fn process_term_deposits(self: &mut Self) {
+        let today = self.clock.today();
+        let mut due: Vec<MoneyAccountID> = self.term_deposits.values()
+            .filter(|deposit| deposit.is_due(today))
+            .map(|deposit| deposit.money_account)
+            .collect();
+        due.sort();
+        for id in due {
+            while self.term_deposits[&id].is_due(today) {
+                let deposit = &self.term_deposits[&id];
+                let (interest, paid_on, maturity_date, linked_account) = (deposit.interest_due(), deposit.next_interest_on(), deposit.maturity_date, deposit.linked_account);
+                let memo = format!("Interest on term deposit {} to {}", id, paid_on);
+                let credit_to = match deposit.terms.interest_payment {
+                    InterestPayment::AtMaturity => id,
+                    InterestPayment::Monthly => linked_account,
+                };
+                if interest > 0 {
+                    self.post_interest(credit_to, interest, memo);
+                }
+                let deposit = self.term_deposits.get_mut(&id).unwrap();
+                deposit.interest_paid += interest;
+                deposit.periods_paid += 1;
+                if paid_on >= maturity_date {
+                    self.mature_term_deposit(id);
+                }
+            }
+        }
+    }
+
+    fn mature_term_deposit(self: &mut Self, id: MoneyAccountID) {
+        let balance = match self.get_money_account_with_id_u32(id) {
+            Some(acc) => acc.borrow().balance,
+            None => return,
+        };
+        let now = self.clock.now();
+        let formatted_balance = self.format_for_account(id, balance);
+        let deposit = self.term_deposits.get_mut(&id).unwrap();
+        let (owner, linked_account) = (deposit.owner, deposit.linked_account);
+        // a term that would end past the last representable date is paid out instead
+        let rolled_maturity = match deposit.terms.at_maturity {
+            MaturityInstruction::Rollover => term_deposits::add_months(deposit.maturity_date, deposit.terms.term_months),
+            MaturityInstruction::PayOut => None,
+        };
+        match rolled_maturity {
+            Some(rolled_maturity) => {
+                // interest added at maturity is rolled over with the principal
+                deposit.terms.principal = balance;
+                deposit.term_start = deposit.maturity_date;
+                deposit.maturity_date = rolled_maturity;
+                deposit.periods_paid = 0;
+                deposit.interest_paid = 0;
+                let message = format!("Your term deposit {} has matured and rolled over: {} until {}.", id, formatted_balance, deposit.maturity_date);
+                self.notifications.push(owner, message, now);
+            },
+            None => {
+                deposit.status = TermDepositStatus::Matured;
+                self.post_internal_transfer(id, linked_account, balance, "Term deposit matured");
+                let message = format!("Your term deposit {} has matured. {} has been paid to account {}.", id, formatted_balance, linked_account);
+                self.notifications.push(owner, message, now);
+            },
+        }
+    }
+
+    // Loans
+    pub fn set_loan_policy(self: &mut Self, policy: LoanPolicy) {
+        self.loan_policy = policy;
+    }
+
+    pub fn get_loan_policy(self: &Self) -> LoanPolicy {
+        return self.loan_policy;
+    }
+
+    // Opens a loan account and pays the principal into one of the owner's checkings accounts,
+    // which installments are then collected from. Returns the loan account's number.
+    pub fn originate_loan(self: &mut Self, owner: BankAccountID, disburse_to: MoneyAccountID, name: String, terms: LoanTerms) -> Result<MoneyAccountID, LoanError> {
+        if self.money_accounts.get(&disburse_to) != Some(&owner) {
+            return Err(LoanError::AccountNotFound);
+        }
+        if !self.is_verified(owner) {
+            return Err(LoanError::NotVerified);
+        }
+        if self.is_minor(owner) {
+            return Err(LoanError::Minor);
+        }
+        let ptr_disbursement_account = self.get_money_account_with_id_u32(disburse_to).ok_or(LoanError::AccountNotFound)?;
+        if ptr_disbursement_account.borrow().account_type != MoneyAccountType::Checking {
+            return Err(LoanError::NotChecking);
+        }
+        let policy = self.loan_policy;
+        if terms.principal < policy.min_principal || terms.principal > policy.max_principal {
+            return Err(LoanError::InvalidAmount);
+        }
+        let today = self.clock.today();
+        if terms.term_months < policy.min_term_months || terms.term_months > policy.max_term_months || term_deposits::add_months(today, terms.term_months).is_none() {
+            return Err(LoanError::InvalidTerm);
+        }
+        if !(0.0..1.0).contains(&terms.annual_rate) {
+            return Err(LoanError::InvalidRate);
+        }
+
+        let currency = ptr_disbursement_account.borrow().currency;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(LoanError::AccountNotFound)?;
+        let ptr_new_acc = self.make_new_money_account(ptr_bank_account, name, MoneyAccountType::Loan, currency);
+        let id = ptr_new_acc.borrow().id;
+        ptr_new_acc.borrow_mut().locked = true;
+        self.post_internal_transfer(id, disburse_to, terms.principal, "Loan disbursement");
+
+        self.loans.insert(id, Loan {
+            money_account: id,
+            owner: owner,
+            repayment_account: disburse_to,
+            terms: terms,
+            originated_on: today,
+            installments: loans::amortization_schedule(&terms, today),
+            status: LoanStatus::Active,
+        });
+        return Ok(id);
+    }
+
+    // The schedule a loan on these terms would get if it were taken out today.
+    pub fn preview_loan_schedule(self: &Self, terms: &LoanTerms) -> Vec<Installment> {
+        return loans::amortization_schedule(terms, self.clock.today());
+    }
+
+    pub fn get_loan(self: &Self, id: MoneyAccountID) -> Option<&Loan> {
+        return self.loans.get(&id);
+    }
+
+    pub fn get_loans(self: &Self, owner: BankAccountID) -> Vec<&Loan> {
+        let mut loans: Vec<&Loan> = self.loans.values()
+            .filter(|loan| loan.owner == owner)
+            .collect();
+        loans.sort_by_key(|loan| loan.originated_on);
+        return loans;
+    }
+
+    pub fn quote_loan_payoff(self: &Self, owner: BankAccountID, id: MoneyAccountID) -> Result<PayoffQuote, LoanError> {
+        let loan = self.loans.get(&id)
+            .filter(|loan| loan.owner == owner)
+            .ok_or(LoanError::NotFound)?;
+        if loan.status != LoanStatus::Active {
+            return Err(LoanError::NotActive);
+        }
+        return Ok(loan.payoff_quote(self.clock.today(), &self.loan_policy));
+    }
+
+    // Settles everything owed from `from` and cancels the rest of the schedule. Returns the amount paid.
+    pub fn pay_off_loan(self: &mut Self, owner: BankAccountID, id: MoneyAccountID, from: MoneyAccountID) -> Result<i32, LoanError> {
+        let quote = self.quote_loan_payoff(owner, id)?;
+        if self.money_accounts.get(&from) != Some(&owner) {
+            return Err(LoanError::AccountNotFound);
+        }
+        self.post_loan_repayment(id, from, quote.principal, quote.interest + quote.penalty, "Loan payoff")
+            .map_err(|_| LoanError::NotEnough)?;
+
+        let today = self.clock.today();
+        let loan = self.loans.get_mut(&id).unwrap();
+        for installment in loan.installments.iter_mut().filter(|installment| installment.is_open()) {
+            if installment.due_date <= today {
+                installment.status = InstallmentStatus::Paid;
+                installment.paid_on = Some(today);
+            } else {
+                installment.status = InstallmentStatus::Cancelled;
+            }
+        }
+        loan.status = LoanStatus::PaidOff;
+        return Ok(quote.total);
+    }
+
+    // Collects installments that have fallen due from the repayment account. One that cannot be
+    // collected is tried again on every run, and once past the grace period it is marked overdue
+    // and accrues penalty interest until paid.
+    pub fn collect_loan_repayments(self: &mut Self) {
+        let today = self.clock.today();
+        let policy = self.loan_policy;
+        let mut due: Vec<MoneyAccountID> = self.loans.values()
+            .filter(|loan| loan.status == LoanStatus::Active && loan.next_installment().is_some_and(|installment| installment.due_date <= today))
+            .map(|loan| loan.money_account)
+            .collect();
+        due.sort();
+        for id in due {
+            loop {
+                let loan = &self.loans[&id];
+                let (owner, repayment_account) = (loan.owner, loan.repayment_account);
+                let index = match loan.installments.iter().position(|installment| installment.is_open() && installment.due_date <= today) {
+                    Some(index) => index,
+                    None => break,
+                };
+                let installment = &loan.installments[index];
+                let (number, principal, interest, due_date) = (installment.number, installment.principal, installment.interest, installment.due_date);
+                let penalty = installment.penalty_interest(today, &policy);
+                let days_late = installment.days_late(today, &policy);
+                let memo = format!("Loan installment {}/{}", number, loan.installments.len());
+                match self.post_loan_repayment(id, repayment_account, principal, interest + penalty, &memo) {
+                    Ok(()) => {
+                        let installment = &mut self.loans.get_mut(&id).unwrap().installments[index];
+                        installment.status = InstallmentStatus::Paid;
+                        installment.paid_on = Some(today);
+                        installment.penalty_paid = penalty;
+                    },
+                    Err(_) => {
+                        let installment = &mut self.loans.get_mut(&id).unwrap().installments[index];
+                        if days_late > 0 && installment.status == InstallmentStatus::Scheduled {
+                            installment.status = InstallmentStatus::Overdue;
+                            let message = format!("Your loan installment of {} due on {} is overdue. Penalty interest applies until it is paid.", self.format_for_account(id, principal + interest), due_date);
+                            self.notifications.push(owner, message, self.clock.now());
+                        }
+                        break;
+                    },
+                }
+            }
+            let loan = self.loans.get_mut(&id).unwrap();
+            if loan.next_installment().is_none() {
+                loan.status = LoanStatus::PaidOff;
+                let message = format!("Your loan {} has been repaid in full.", id);
+                self.notifications.push(loan.owner, message, self.clock.now());
+            }
+        }
+    }
+
+    // Debits `principal` plus `charges` from `from`. The principal reduces what is owed on the
+    // loan account and the charges (interest and penalties) are the bank's income.
+    fn post_loan_repayment(self: &mut Self, loan: MoneyAccountID, from: MoneyAccountID, principal: i32, charges: i32, description: &str) -> Result<(), BalanceError> {
+        let ptr_from_account = self.get_money_account_with_id_u32(from).ok_or(BalanceError::NotFound)?;
+        let ptr_loan_account = self.get_money_account_with_id_u32(loan).ok_or(BalanceError::NotFound)?;
+        let from_balance = ptr_from_account.borrow_mut().subtract_balance(principal + charges)?;
+        let loan_balance = ptr_loan_account.borrow_mut().add_balance(principal)?;
+        let currency = ptr_loan_account.borrow().currency;
+        let now = self.clock.now();
+        let debit_transaction = self.record_entry(from, TransactionKind::TransferOut, -(principal + charges), from_balance, now, format!("{} to {}", description, loan));
+        self.record_entry(loan, TransactionKind::TransferIn, principal, loan_balance, now, format!("{} from {}", description, from));
+        if charges > 0 {
+            let memo = format!("Interest on loan {}", loan);
+            self.ledger.record_income(IncomeKind::LoanInterest, currency, charges, now, memo, Some(debit_transaction));
+        }
+        return Ok(());
+    }
+
+    // Credit cards
+    pub fn set_credit_policy(self: &mut Self, policy: CreditPolicy) {
+        self.credit_policy = policy;
+    }
+
+    pub fn get_credit_policy(self: &Self) -> CreditPolicy {
+        return self.credit_policy;
+    }
+
+    // Opens a credit card account that can be spent down to minus its limit. Cash withdrawals
+    // and transfers out count as cash advances, everything else spent is a purchase.
+    pub fn open_credit_line(self: &mut Self, owner: BankAccountID, name: String, currency: Currency, terms: CreditTerms) -> Result<MoneyAccountID, CreditError> {
+        if !self.is_verified(owner) {
+            return Err(CreditError::NotVerified);
+        }
+        if self.is_minor(owner) {
+            return Err(CreditError::Minor);
+        }
+        if terms.limit <= 0 || terms.limit > self.credit_policy.max_limit {
+            return Err(CreditError::InvalidLimit);
+        }
+        if !(0.0..1.0).contains(&terms.purchase_apr) || !(0.0..1.0).contains(&terms.cash_advance_apr) {
+            return Err(CreditError::InvalidRate);
+        }
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(CreditError::AccountNotFound)?;
+        let ptr_new_acc = self.make_new_money_account(ptr_bank_account, name, MoneyAccountType::Credit, currency);
+        let id = ptr_new_acc.borrow().id;
+        ptr_new_acc.borrow_mut().credit_limit = terms.limit;
+        let today = self.clock.today();
+        self.credit_lines.insert(id, CreditLine {
+            money_account: id,
+            owner: owner,
+            terms: terms,
+            opened_on: today,
+            purchase_balance: 0,
+            cash_advance_balance: 0,
+            interest_and_fees_balance: 0,
+            accrued_purchase_interest: 0.0,
+            accrued_cash_advance_interest: 0.0,
+            accrued_through: today,
+            statements: vec![],
+        });
+        return Ok(id);
+    }
+
+    pub fn get_credit_line(self: &Self, id: MoneyAccountID) -> Option<&CreditLine> {
+        return self.credit_lines.get(&id);
+    }
+
+    pub fn get_credit_lines(self: &Self, owner: BankAccountID) -> Vec<&CreditLine> {
+        let mut lines: Vec<&CreditLine> = self.credit_lines.values()
+            .filter(|line| line.owner == owner)
+            .collect();
+        lines.sort_by_key(|line| line.opened_on);
+        return lines;
+    }
+
+    // Accrues interest day by day, closes statements on the cycle date and charges a late fee
+    // when a statement's minimum payment has not arrived by its due date.
+    pub fn process_credit_lines(self: &mut Self) {
+        let today = self.clock.today();
+        let mut ids: Vec<MoneyAccountID> = self.credit_lines.keys().copied()
+            .filter(|id| self.check_if_money_account_id_exists(*id))
+            .collect();
+        ids.sort();
+        for id in ids {
+            loop {
+                let line = &self.credit_lines[&id];
+                if line.next_statement_date() <= line.accrued_through {
+                    self.close_credit_statement(id);
+                    continue;
+                }
+                if line.last_statement().is_some_and(|statement| statement.paid_by_due_date.is_none() && statement.due_date < line.accrued_through) {
+                    self.settle_credit_due_date(id);
+                    continue;
+                }
+                if line.accrued_through >= today {
+                    break;
+                }
+                let line = self.credit_lines.get_mut(&id).unwrap();
+                line.accrue_day();
+                line.accrued_through += Duration::days(1);
+            }
+        }
+    }
+
+    fn close_credit_statement(self: &mut Self, id: MoneyAccountID) {
+        let policy = self.credit_policy;
+        let ptr_money_account = match self.get_money_account_with_id_u32(id) {
+            Some(acc) => acc,
+            None => return,
+        };
+        let line = match self.credit_lines.get_mut(&id) {
+            Some(line) => line,
+            None => return,
+        };
+        let closing_date = line.next_statement_date();
+        let purchase_interest = if line.grace_period_applies() { 0.0 } else { line.accrued_purchase_interest };
+        let interest = (purchase_interest + line.accrued_cash_advance_interest).round() as i32;
+        line.accrued_purchase_interest = 0.0;
+        line.accrued_cash_advance_interest = 0.0;
+        let (owner, interest_and_fees_before) = (line.owner, line.interest_and_fees_balance);
+        if interest > 0 {
+            let new_balance = ptr_money_account.borrow_mut().add_balance(-interest).unwrap_or_default();
+            let currency = ptr_money_account.borrow().currency;
+            let now = self.clock.now();
+            let memo = format!("Interest to {}", closing_date);
+            let transaction = self.record_entry(id, TransactionKind::Interest, -interest, new_balance, now, memo.clone());
+            self.ledger.record_income(IncomeKind::CreditInterest, currency, interest, now, memo, Some(transaction));
+        }
+
+        let balance = (-ptr_money_account.borrow().balance).max(0);
+        let minimum_payment = credit::minimum_payment(balance, interest + interest_and_fees_before, &policy);
+        let due_date = closing_date + Duration::days(policy.payment_due_days as i64);
+        let statement = CreditStatement {
+            closing_date: closing_date,
+            due_date: due_date,
+            balance: balance,
+            minimum_payment: minimum_payment,
+            interest_charged: interest,
+            payments: 0,
+            paid_by_due_date: if balance == 0 { Some(true) } else { None },
+        };
+        if let Some(line) = self.credit_lines.get_mut(&id) {
+            line.statements.push(statement);
+        }
+        if balance > 0 {
+            let message = format!("Your credit card statement for {} is ready: {} owed, minimum payment {} due by {}.", id, self.format_for_account(id, balance), self.format_for_account(id, minimum_payment), due_date);
+            self.notifications.push(owner, message, self.clock.now());
+        }
+    }
+
+    fn settle_credit_due_date(self: &mut Self, id: MoneyAccountID) {
+        let line = self.credit_lines.get_mut(&id).unwrap();
+        let owner = line.owner;
+        let statement = line.statements.last_mut().unwrap();
+        statement.paid_by_due_date = Some(statement.payments >= statement.balance);
+        if statement.minimum_outstanding() == 0 || self.credit_policy.late_fee <= 0 {
+            return;
+        }
+        let memo = format!("{} (minimum payment due {})", FeeKind::LatePayment.description(), statement.due_date);
+        self.post_fee(id, &FeeQuote { kind: FeeKind::LatePayment, amount: self.credit_policy.late_fee, memo: memo });
+        let message = format!("We did not receive the minimum payment on your credit card {}. A late payment fee of {} has been charged.", id, self.format_for_account(id, self.credit_policy.late_fee));
+        self.notifications.push(owner, message, self.clock.now());
+    }
+
+    // Keeps a credit card's segments in step with its ledger: money out is a charge, money in a payment.
+    fn track_credit_movement(self: &mut Self, id: MoneyAccountID, kind: TransactionKind, amount: i32) {
+        let allocation = self.credit_policy.allocation;
+        let line = match self.credit_lines.get_mut(&id) {
+            Some(line) => line,
+            None => return,
+        };
+        if amount > 0 {
+            line.apply_payment(amount, allocation);
+            return;
+        }
+        let segment = match kind {
+            TransactionKind::Withdrawal | TransactionKind::TransferOut => CreditSegment::CashAdvance,
+            TransactionKind::Fee | TransactionKind::Interest => CreditSegment::InterestAndFees,
+            _ => CreditSegment::Purchase,
+        };
+        line.add_charge(segment, -amount);
+    }
+
+    // Every balance movement goes through here so products that track more than a balance stay in step.
+    fn record_entry(self: &mut Self, money_account: MoneyAccountID, kind: TransactionKind, amount: i32, balance_after: i32, posted_at: NaiveDateTime, memo: String) -> TransactionID {
+        let id = self.ledger.record(money_account, kind, amount, balance_after, posted_at, memo);
+        self.entry_posted(id);
+        return id;
+    }
+
+    fn record_reversal_entry(self: &mut Self, original: TransactionID, amount: i32, balance_after: i32, reason: ReasonCode) -> Option<TransactionID> {
+        let id = self.ledger.record_reversal(original, amount, balance_after, self.clock.now(), reason)?;
+        self.entry_posted(id);
+        return Some(id);
+    }
+
+    // Whatever wrote the entry, it is tracked and audited the same way. A reversal counts against
+    // the credit segment of the entry it reverses.
+    fn entry_posted(self: &mut Self, id: TransactionID) {
+        let entry = self.ledger.get(id).unwrap();
+        let (money_account, kind, amount, balance_after) = (entry.money_account, entry.kind, entry.amount, entry.balance_after);
+        let tracked_kind = entry.reverses.and_then(|original| self.ledger.get(original)).map_or(kind, |original| original.kind);
+        self.track_credit_movement(money_account, tracked_kind, amount);
+        // callers may still hold the account borrowed, so amounts stay in minor units
+        let owner = self.money_accounts.get(&money_account).copied();
+        let detail = format!("{} on {}: {} minor units, balance {}", kind.description(), money_account, amount, balance_after);
+        self.audit(owner, AuditAction::BalanceChange, AuditOutcome::Success, detail);
+    }
+
+    fn post_interest(self: &mut Self, id: MoneyAccountID, amount: i32, memo: String) {
+        if let Some(ptr_money_account) = self.get_money_account_with_id_u32(id) {
+            if let Ok(new_balance) = ptr_money_account.borrow_mut().add_balance(amount) {
+                self.record_entry(id, TransactionKind::Interest, amount, new_balance, self.clock.now(), memo);
+            }
+        }
+    }
+
+    // Moves money between two accounts in the same currency on the bank's own behalf, so locks,
+    // limits and fees do not apply.
+    fn post_internal_transfer(self: &mut Self, from: MoneyAccountID, to: MoneyAccountID, amount: i32, description: &str) {
+        let (ptr_from_account, ptr_to_account) = match (self.get_money_account_with_id_u32(from), self.get_money_account_with_id_u32(to)) {
+            (Some(from_acc), Some(to_acc)) => (from_acc, to_acc),
+            _ => return,
+        };
+        let now = self.clock.now();
+        let from_balance = ptr_from_account.borrow().balance - amount;
+        let to_balance = ptr_to_account.borrow().balance + amount;
+        let _ = ptr_from_account.borrow_mut().set_balance(from_balance);
+        let _ = ptr_to_account.borrow_mut().set_balance(to_balance);
+        self.record_entry(from, TransactionKind::TransferOut, -amount, from_balance, now, format!("{} to {}", description, to));
+        self.record_entry(to, TransactionKind::TransferIn, amount, to_balance, now, format!("{} from {}", description, from));
+    }
+
+    // Foreign exchange
+    pub fn load_rate_table(self: &mut Self, path: &Path) -> Result<(), CurrencyError> {
+        self.rate_table = RateTable::load_from_file(path)?;
+        return Ok(());
+    }
+
+    pub fn set_rate_table(self: &mut Self, rate_table: RateTable) {
+        self.rate_table = rate_table;
+    }
+
+    pub fn set_fx_policy(self: &mut Self, policy: FxPolicy) {
+        self.fx_policy = policy;
+    }
+
+    // Mid rate in effect now, before the spread.
+    pub fn get_exchange_rate(self: &Self, from: Currency, to: Currency) -> Result<f64, CurrencyError> {
+        return self.rate_table.rate(from, to, self.clock.now()).ok_or(CurrencyError::NoRate);
+    }
+
+    pub fn get_income_entries(self: &Self) -> &Vec<IncomeEntry> {
+        return self.ledger.income_entries();
+    }
+
+    fn format_for_account(self: &Self, id: MoneyAccountID, amount: i32) -> String {
+        match self.get_money_account_with_id_u32(id) {
+            Some(acc) => return acc.borrow().format_amount(amount),
+            None => return amount.to_string(),
+        }
+    }
+
+    // Everything that happens with the passage of time.
+    pub fn tick(self: &mut Self) {
+        let context = std::mem::replace(&mut self.audit_context, AuditContext { actor: AuditActor::System, channel: AuditChannel::Scheduler });
+        self.process_clearing();
+        self.run_standing_orders();
+        self.settle_outbound_payments();
+        self.assess_maintenance_fees();
+        self.process_term_deposits();
+        self.collect_loan_repayments();
+        self.process_credit_lines();
+        self.issue_statements();
+        self.convert_adult_minors();
+        self.expire_approval_requests();
+        self.take_daily_checkpoints();
+        self.audit_context = context;
+    }
+
+    // Statements
+    // Issues a statement for every monthly cycle that has ended since the last run, starting
+    // with the month the account was opened in.
+    pub fn issue_statements(self: &mut Self) {
+        let today = self.clock.today();
+        let mut due: Vec<MoneyAccountID> = self.statement_due.iter()
+            .filter(|(_, due_on)| **due_on <= today)
+            .map(|(id, _)| *id)
+            .collect();
+        due.sort();
+        for id in due {
+            while self.statement_due[&id] <= today {
+                let cycle_end = self.statement_due[&id];
+                self.issue_statement(id, first_of_previous_month(cycle_end), cycle_end);
+                self.statement_due.insert(id, first_of_next_month(cycle_end));
+            }
+        }
+    }
+
+    fn issue_statement(self: &mut Self, id: MoneyAccountID, period_start: NaiveDate, period_end: NaiveDate) {
+        let ptr_money_account = match self.get_money_account_with_id_u32(id) {
+            Some(acc) => acc,
+            None => return,
+        };
+        let money_account = ptr_money_account.borrow();
+        let ptr_bank_account = match money_account.linked_bank_account.upgrade() {
+            Some(acc) => acc,
+            None => return, // owner gone, nobody to send it to
+        };
+        let bank_account = ptr_bank_account.borrow();
+        let (start, end) = (period_start.and_hms_opt(0, 0, 0).unwrap(), period_end.and_hms_opt(0, 0, 0).unwrap());
+        let opening_balance = self.ledger.balance_at(id, start - Duration::nanoseconds(1));
+        let lines: Vec<StatementLine> = self.ledger.history_for(id).iter()
+            .filter(|txn| txn.posted_at >= start && txn.posted_at < end)
+            .map(|txn| StatementLine {
+                transaction: txn.id,
+                posted_at: txn.posted_at,
+                kind: txn.kind,
+                memo: txn.memo.clone(),
+                amount: txn.amount,
+                balance: txn.balance_after,
+            })
+            .collect();
+        let statement = Statement {
+            id: self.statements.len() as StatementID + 1,
+            money_account: id,
+            account_name: money_account.name.clone(),
+            account_type: money_account.get_str(),
+            currency: money_account.currency,
+            holder: StatementHolder {
+                customer: bank_account.id,
+                name: bank_account.name.clone(),
+                company: bank_account.business.as_ref().map(|business| business.company_name.clone()),
+                address: bank_account.contact.address.clone(),
+                email: bank_account.contact.email.clone(),
+            },
+            period_start: period_start,
+            period_end: period_end,
+            issued_at: self.clock.now(),
+            opening_balance: opening_balance,
+            closing_balance: lines.last().map(|line| line.balance).unwrap_or(opening_balance),
+            lines: lines,
+        };
+        drop(bank_account);
+        drop(money_account);
+        self.statements.push(statement);
+    }
+
+    // Every statement issued for the account, oldest first.
+    pub fn get_statements(self: &Self, id: MoneyAccountID) -> Vec<&Statement> {
+        return self.statements.iter().filter(|statement| statement.money_account == id).collect();
+    }
+
+    pub fn get_statement(self: &Self, id: StatementID) -> Option<&Statement> {
+        // ids are handed out in order, so the statement sits at id - 1
+        return self.statements.get((id as usize).checked_sub(1)?);
+    }
+
+    pub fn render_statement(self: &Self, id: StatementID, format: StatementFormat) -> Result<String, StatementError> {
+        return Ok(self.get_statement(id).ok_or(StatementError::NotFound)?.render(format));
+    }
+
+    pub fn save_statement(self: &Self, id: StatementID, format: StatementFormat, path: &Path) -> Result<(), StatementError> {
+        let rendered = self.render_statement(id, format)?;
+        return std::fs::write(path, rendered).map_err(|_| StatementError::WriteFailed);
+    }
+
+    // Transaction export
+    // A money account's entries posted between `from` and `to`, both inclusive.
+    pub fn get_transaction_export(self: &Self, id: MoneyAccountID, from: NaiveDate, to: NaiveDate) -> Result<TransactionExport<'_>, ExportError> {
+        if from > to {
+            return Err(ExportError::InvalidRange);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(ExportError::AccountNotFound)?;
+        let money_account = ptr_money_account.borrow();
+        // `to` has no following day at the very end of the calendar
+        let (start, end) = (from.and_hms_opt(0, 0, 0).unwrap(), to.succ_opt().ok_or(ExportError::InvalidRange)?.and_hms_opt(0, 0, 0).unwrap());
+        return Ok(TransactionExport {
+            money_account: id,
+            account_type: money_account.account_type,
+            currency: money_account.currency,
+            from: from,
+            to: to,
+            closing_balance: self.ledger.balance_at(id, end - Duration::nanoseconds(1)),
+            generated_at: self.clock.now(),
+            transactions: self.ledger.history_for(id).into_iter().filter(|txn| txn.posted_at >= start && txn.posted_at < end).collect(),
+        });
+    }
+
+    pub fn export_transactions(self: &Self, id: MoneyAccountID, from: NaiveDate, to: NaiveDate, format: ExportFormat) -> Result<String, ExportError> {
+        return Ok(self.get_transaction_export(id, from, to)?.render(format));
+    }
+
+    // Returns how many entries were written.
+    pub fn save_transaction_export(self: &Self, id: MoneyAccountID, from: NaiveDate, to: NaiveDate, format: ExportFormat, path: &Path) -> Result<usize, ExportError> {
+        let export = self.get_transaction_export(id, from, to)?;
+        std::fs::write(path, export.render(format)).map_err(|_| ExportError::WriteFailed)?;
+        return Ok(export.transactions.len());
+    }
+
+    // Integrity
+    pub fn set_signing_key(self: &mut Self, key: SecretBytes) {
+        self.signing_key = Some(key);
+    }
+
+    pub fn get_checkpoints(self: &Self) -> &Vec<Checkpoint> {
+        return &self.checkpoints;
+    }
+
+    // Signs the current head of the ledger and of the audit log. Nothing without a signing key.
+    pub fn take_checkpoints(self: &mut Self) -> Vec<Checkpoint> {
+        let key = match &self.signing_key {
+            Some(key) => key,
+            None => return vec![],
+        };
+        let now = self.clock.now();
+        let checkpoints = vec![
+            Checkpoint::new(Chain::Ledger, self.ledger.len(), self.ledger.head_hash(), now, key),
+            Checkpoint::new(Chain::Audit, self.audit_log.events().len(), self.audit_log.head_hash(), now, key),
+        ];
+        self.checkpoints.extend(checkpoints.iter().cloned());
+        return checkpoints;
+    }
+
+    // At most one pair of checkpoints a day, and only once something has been recorded since the last.
+    fn take_daily_checkpoints(self: &mut Self) {
+        if self.checkpoints.last().is_some_and(|last| last.created_at.date() >= self.clock.today()) {
+            return;
+        }
+        let checkpointed_length = |chain: Chain| {
+            return self.checkpoints.iter().rev().find(|checkpoint| checkpoint.chain == chain).map_or(0, |checkpoint| checkpoint.entries);
+        };
+        if checkpointed_length(Chain::Ledger) == self.ledger.len() && checkpointed_length(Chain::Audit) == self.audit_log.events().len() {
+            return;
+        }
+        self.take_checkpoints();
+    }
+
+    // Walks both hash chains, checks every checkpoint against them and compares each account's
+    // balance with its last ledger entry. Stops at the first problem.
+    pub fn verify_integrity(self: &Self) -> IntegrityReport {
+        let mut report = IntegrityReport {
+            ledger_entries: self.ledger.len(),
+            audit_events: self.audit_log.events().len(),
+            checkpoints: self.checkpoints.len(),
+            signing_key_configured: self.signing_key.is_some(),
+            first_broken_link: None,
+        };
+        report.first_broken_link = self.find_first_broken_link();
+        return report;
+    }
+
+    fn find_first_broken_link(self: &Self) -> Option<BrokenLink> {
+        if let Some((position, reason)) = self.ledger.first_broken_entry() {
+            return Some(BrokenLink { chain: Chain::Ledger, position: position, money_account: None, reason: reason });
+        }
+        if let Some((position, reason)) = self.audit_log.first_broken_entry() {
+            return Some(BrokenLink { chain: Chain::Audit, position: position, money_account: None, reason: reason });
+        }
+        for checkpoint in &self.checkpoints {
+            let broken = BrokenLink { chain: checkpoint.chain, position: checkpoint.entries, money_account: None, reason: BrokenLinkReason::BadSignature };
+            if !self.signing_key.as_ref().is_some_and(|key| checkpoint.is_signature_valid(key)) {
+                return Some(broken);
+            }
+            let hash = match checkpoint.chain {
+                Chain::Ledger => self.ledger.hash_after(checkpoint.entries),
+                Chain::Audit => self.audit_log.hash_after(checkpoint.entries),
+            };
+            if hash != Some(checkpoint.head_hash) {
+                return Some(BrokenLink { reason: BrokenLinkReason::CheckpointMismatch, ..broken });
+            }
+        }
+        let mut money_account_ids: Vec<&MoneyAccountID> = self.money_accounts.keys().collect();
+        money_account_ids.sort();
+        for id in money_account_ids {
+            let balance = match self.get_money_account_with_id_u32(*id) {
+                Some(acc) => acc.borrow().balance,
+                None => continue,
+            };
+            if balance != self.ledger.last_balance(*id).unwrap_or(0) {
+                return Some(BrokenLink { chain: Chain::Ledger, position: self.ledger.len(), money_account: Some(*id), reason: BrokenLinkReason::BalanceMismatch });
+            }
+        }
+        return None;
+    }
+
+    // Staff
+    pub fn has_staff(self: &Self) -> bool {
+        return !self.staff.is_empty();
+    }
+
+    pub fn get_staff_member(self: &Self, id: StaffID) -> Option<&StaffMember> {
+        return self.staff.get(&id);
+    }
+
+    // Staff members are added by an admin, the first admin by provision_admin.
+    pub fn create_staff(self: &mut Self, acting: StaffID, user_id: String, name: String, password: SecretString, role: StaffRole) -> Result<StaffID, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ManageStaff, AuditAction::StaffChange)?;
+        let (id, detail) = self.insert_staff(user_id, name, password, role, Some(acting))?;
+        self.audit_staff(acting, None, AuditAction::StaffChange, AuditOutcome::Success, detail);
+        return Ok(id);
+    }
+
+    // The first admin, set up by whoever runs the bank from its configuration rather than by
+    // anyone logged in. Refused while there is an active admin, who can add staff themselves.
+    pub fn provision_admin(self: &mut Self, user_id: String, name: String, password: SecretString) -> Result<StaffID, StaffError> {
+        if self.staff.values().any(|member| member.active && member.role == StaffRole::Admin) {
+            return Err(StaffError::PermissionDenied);
+        }
+        let (id, detail) = self.insert_staff(user_id, name, password, StaffRole::Admin, None)?;
+        let context = AuditContext { actor: AuditActor::System, channel: self.audit_context.channel };
+        self.audit_log.append(self.clock.now(), &context, None, AuditAction::StaffChange, AuditOutcome::Success, detail);
+        return Ok(id);
+    }
+
+    fn insert_staff(self: &mut Self, user_id: String, name: String, password: SecretString, role: StaffRole, created_by: Option<StaffID>) -> Result<(StaffID, String), StaffError> {
+        if user_id.is_empty() || user_id.contains(char::is_whitespace) {
+            return Err(StaffError::InvalidUserId);
+        }
+        if self.staff.values().any(|member| member.user_id == user_id) {
+            return Err(StaffError::UserIdTaken);
+        }
+        if !passwords::check_password(password.expose(), &self.password_policy, &self.common_passwords, &[]).is_empty() {
+            return Err(StaffError::WeakPassword);
+        }
+        let id = self.next_staff_id;
+        self.next_staff_id += 1;
+        let detail = format!("Added {} \"{}\" as {}", id, user_id, role.description());
+        let password = self.hash_password(&password);
+        self.staff.insert(id, StaffMember {
+            id: id,
+            user_id: user_id,
+            name: name,
+            password: password,
+            role: role,
+            active: true,
+            created_by: created_by,
+        });
+        return Ok((id, detail));
+    }
+
+    pub fn authenticate_staff(self: &mut Self, user_id: &str, password: &SecretString) -> Result<StaffID, StaffError> {
+        let member = self.staff.values().find(|member| member.user_id == user_id).map(|member| (member.id, member.active && member.password.verify(password.expose())));
+        match member {
+            Some((id, true)) => {
+                self.audit_staff(id, None, AuditAction::StaffLogin, AuditOutcome::Success, format!("User ID \"{}\"", user_id));
+                return Ok(id);
+            },
+            Some((_, false)) => {
+                self.audit(None, AuditAction::StaffLogin, AuditOutcome::Failure, format!("User ID \"{}\"", user_id));
+                return Err(StaffError::InvalidCredentials);
+            },
+            None => {
+                self.audit(None, AuditAction::StaffLogin, AuditOutcome::Failure, format!("Unknown user ID \"{}\"", audit::redact_user_id(user_id)));
+                return Err(StaffError::InvalidCredentials);
+            },
+        }
+    }
+
+    pub fn get_staff(self: &mut Self, acting: StaffID) -> Result<Vec<&StaffMember>, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ManageStaff, AuditAction::StaffChange)?;
+        let mut members: Vec<&StaffMember> = self.staff.values().collect();
+        members.sort_by_key(|member| member.id);
+        return Ok(members);
+    }
+
+    pub fn deactivate_staff(self: &mut Self, acting: StaffID, id: StaffID) -> Result<(), StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ManageStaff, AuditAction::StaffChange)?;
+        if acting == id {
+            return Err(StaffError::CannotDeactivateSelf);
+        }
+        let member = self.staff.get_mut(&id).ok_or(StaffError::NotFound)?;
+        member.active = false;
+        let detail = format!("Deactivated {} \"{}\"", id, member.user_id);
+        self.audit_staff(acting, None, AuditAction::StaffChange, AuditOutcome::Success, detail);
+        return Ok(());
+    }
+
+    // Matches customer numbers exactly and names, user IDs and email addresses by substring.
+    pub fn search_customers(self: &mut Self, acting: StaffID, query: &str) -> Result<Vec<Rc<RefCell<BankAccount>>>, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::SearchCustomers, AuditAction::CustomerSearch)?;
+        let query = query.trim().to_lowercase();
+        let mut matches: Vec<Rc<RefCell<BankAccount>>> = self.bank_accounts.values()
+            .filter(|acc| {
+                let acc = acc.borrow();
+                return acc.id.to_string() == query || acc.name.to_lowercase().contains(&query)
+                    || acc.userid.to_lowercase().contains(&query) || acc.contact.email.to_lowercase().contains(&query);
+            })
+            .cloned()
+            .collect();
+        matches.sort_by_key(|acc| acc.borrow().id);
+        self.audit_staff(acting, None, AuditAction::CustomerSearch, AuditOutcome::Success, format!("\"{}\", {} found", query, matches.len()));
+        return Ok(matches);
+    }
+
+    pub fn staff_view_customer(self: &mut Self, acting: StaffID, owner: BankAccountID) -> Result<Rc<RefCell<BankAccount>>, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ViewAccounts, AuditAction::CustomerViewed)?;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(StaffError::CustomerNotFound)?;
+        self.audit_staff(acting, Some(owner), AuditAction::CustomerViewed, AuditOutcome::Success, String::from("Profile and accounts"));
+        return Ok(ptr_bank_account);
+    }
+
+    pub fn staff_view_history(self: &mut Self, acting: StaffID, id: MoneyAccountID) -> Result<Rc<RefCell<MoneyAccount>>, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ViewAccounts, AuditAction::CustomerViewed)?;
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(StaffError::AccountNotFound)?;
+        let owner = self.money_accounts.get(&id).copied();
+        self.audit_staff(acting, owner, AuditAction::CustomerViewed, AuditOutcome::Success, format!("History of {}", id));
+        return Ok(ptr_money_account);
+    }
+
+    // Corrects a balance by hand. Positive amounts credit the account. Locks and limits do not apply.
+    // Adjustments above the approval threshold have to go through request_adjustment instead.
+    pub fn post_adjustment(self: &mut Self, acting: StaffID, id: MoneyAccountID, amount: i32, reason: &str) -> Result<i32, StaffError> {
+        self.check_adjustment(acting, id, amount, reason)?;
+        if self.adjustment_needs_approval(id, amount) {
+            return Err(StaffError::ApprovalRequired);
+        }
+        return Ok(self.apply_adjustment(acting, id, amount, reason.trim()));
+    }
+
+    fn check_adjustment(self: &mut Self, acting: StaffID, id: MoneyAccountID, amount: i32, reason: &str) -> Result<(), StaffError> {
+        self.require_staff_permission(acting, StaffPermission::PostAdjustments, AuditAction::ManualAdjustment)?;
+        if reason.trim().is_empty() {
+            return Err(StaffError::MissingReason);
+        }
+        if amount == 0 {
+            return Err(StaffError::InvalidAmount);
+        }
+        if !self.check_if_money_account_id_exists(id) {
+            return Err(StaffError::AccountNotFound);
+        }
+        return Ok(());
+    }
+
+    fn apply_adjustment(self: &mut Self, acting: StaffID, id: MoneyAccountID, amount: i32, reason: &str) -> i32 {
+        let ptr_money_account = self.get_money_account_with_id_u32(id).unwrap();
+        let new_balance = ptr_money_account.borrow().balance + amount;
+        let _ = ptr_money_account.borrow_mut().set_balance(new_balance);
+        self.record_entry(id, TransactionKind::Adjustment, amount, new_balance, self.clock.now(), format!("Manual adjustment: {}", reason));
+        let owner = self.money_accounts.get(&id).copied();
+        self.audit_staff(acting, owner, AuditAction::ManualAdjustment, AuditOutcome::Success, format!("{} on {}: {}", amount, id, reason));
+        return new_balance;
+    }
+
+    // Freezes or releases a customer account. The customer is told either way.
+    pub fn set_account_locked(self: &mut Self, acting: StaffID, id: MoneyAccountID, locked: bool, reason: &str) -> Result<(), StaffError> {
+        let permission = if locked { StaffPermission::LockAccounts } else { StaffPermission::UnlockAccounts };
+        self.require_staff_permission(acting, permission, AuditAction::AccountLockChange)?;
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(StaffError::MissingReason);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(StaffError::AccountNotFound)?;
+        if matches!(ptr_money_account.borrow().account_type, MoneyAccountType::TermDeposit | MoneyAccountType::Loan) {
+            return Err(StaffError::ProductLocked);
+        }
+        ptr_money_account.borrow_mut().locked = locked;
+        let owner = *self.money_accounts.get(&id).unwrap();
+        let (verb, message) = if locked {
+            ("Locked", format!("Your account {} has been locked. Please contact us.", id))
+        } else {
+            ("Unlocked", format!("Your account {} has been unlocked.", id))
+        };
+        self.notifications.push(owner, message, self.clock.now());
+        self.audit_staff(acting, Some(owner), AuditAction::AccountLockChange, AuditOutcome::Success, format!("{} {}: {}", verb, id, reason));
+        return Ok(());
+    }
+
+    // Sets a random temporary password the customer has to change at their next login.
+    pub fn reset_customer_password(self: &mut Self, acting: StaffID, owner: BankAccountID) -> Result<SecretString, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ResetPasswords, AuditAction::PasswordReset)?;
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(StaffError::CustomerNotFound)?;
+        let temporary_password = passwords::generate_temporary_password(self.rng.as_mut());
+        let password = self.hash_password(&temporary_password);
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        self.store_password(&mut bank_account, password);
+        bank_account.must_change_password = true;
+        drop(bank_account);
+        self.audit_staff(acting, Some(owner), AuditAction::PasswordReset, AuditOutcome::Success, String::from("Temporary password issued"));
+        return Ok(temporary_password);
+    }
+
+    pub fn staff_set_onboarding_status(self: &mut Self, acting: StaffID, owner: BankAccountID, status: OnboardingStatus, note: Option<String>) -> Result<(), StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ReviewIdentity, AuditAction::OnboardingStatusChange)?;
+        let staff_context = AuditContext { actor: AuditActor::Staff(acting), channel: self.audit_context.channel };
+        let context = std::mem::replace(&mut self.audit_context, staff_context);
+        let result = self.set_onboarding_status(owner, status, note).map_err(|_| StaffError::CustomerNotFound);
+        self.audit_context = context;
+        return result;
+    }
+
+    pub fn staff_verify_integrity(self: &mut Self, acting: StaffID) -> Result<IntegrityReport, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::VerifyIntegrity, AuditAction::IntegrityCheck)?;
+        let report = self.verify_integrity();
+        let (outcome, detail) = match &report.first_broken_link {
+            None => (AuditOutcome::Success, String::from("All records intact")),
+            Some(link) => (AuditOutcome::Failure, format!("{} entry {}: {}", link.chain.description(), link.position, link.reason.description())),
+        };
+        self.audit_staff(acting, None, AuditAction::IntegrityCheck, outcome, detail);
+        return Ok(report);
+    }
+
+    pub fn staff_export_audit_log(self: &mut Self, acting: StaffID, path: &Path, customer: Option<BankAccountID>, from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Result<usize, StaffError> {
+        self.require_staff_permission(acting, StaffPermission::ExportAudit, AuditAction::AuditExport)?;
+        match self.export_audit_log(path, customer, from, to) {
+            Ok(count) => {
+                self.audit_staff(acting, customer, AuditAction::AuditExport, AuditOutcome::Success, format!("{} events to {}", count, path.display()));
+                return Ok(count);
+            },
+            Err(err) => {
+                self.audit_staff(acting, customer, AuditAction::AuditExport, AuditOutcome::Failure, err.to_string());
+                return Err(StaffError::ExportFailed);
+            },
+        }
+    }
+
+    // Denied attempts are audited too.
+    fn require_staff_permission(self: &mut Self, acting: StaffID, permission: StaffPermission, action: AuditAction) -> Result<(), StaffError> {
+        let allowed = match self.staff.get(&acting) {
+            Some(member) if member.active => member.role.has_permission(permission),
+            _ => return Err(StaffError::NotFound),
+        };
+        if !allowed {
+            self.audit_staff(acting, None, action, AuditOutcome::Failure, String::from(StaffError::PermissionDenied.as_str()));
+            return Err(StaffError::PermissionDenied);
+        }
+        return Ok(());
+    }
+
+    fn audit_staff(self: &mut Self, acting: StaffID, customer: Option<BankAccountID>, action: AuditAction, outcome: AuditOutcome, detail: String) {
+        let context = AuditContext { actor: AuditActor::Staff(acting), channel: self.audit_context.channel };
+        self.audit_log.append(self.clock.now(), &context, customer, action, outcome, detail);
+    }
+
+    // Approvals
+    pub fn set_approval_policy(self: &mut Self, policy: ApprovalPolicy) {
+        self.approval_policy = policy;
+    }
+
+    pub fn get_approval_policy(self: &Self) -> ApprovalPolicy {
+        return self.approval_policy;
+    }
+
+    // Thresholds are in the account's currency.
+    pub fn outflow_needs_approval(self: &Self, id: MoneyAccountID, amount: i32) -> bool {
+        return self.get_money_account_with_id_u32(id).is_some_and(|acc| amount > self.approval_policy.outflow_threshold.get(acc.borrow().currency));
+    }
+
+    // Who has to approve money leaving an account before it goes, if anyone. A guardian's limit
+    // applies only to their child and comes first; the bank's threshold applies to everyone, and
+    // takes over once the guardian has agreed (see carry_out_request).
+    pub fn outflow_reviewer(self: &Self, id: MoneyAccountID, amount: i32) -> Option<Reviewer> {
+        let ptr_money_account = self.get_money_account_with_id_u32(id)?;
+        let currency = ptr_money_account.borrow().currency;
+        let guardian = self.money_accounts.get(&id).and_then(|owner| self.get_guardian(*owner));
+        if let Some(guardian) = guardian.filter(|_| amount > self.guardian_policy.approval_limit.get(currency)) {
+            return Some(Reviewer::Guardian(guardian));
+        }
+        if self.outflow_needs_approval(id, amount) {
+            return Some(Reviewer::Bank);
+        }
+        return None;
+    }
+
+    pub fn adjustment_needs_approval(self: &Self, id: MoneyAccountID, amount: i32) -> bool {
+        return self.get_money_account_with_id_u32(id).is_some_and(|acc| amount.abs() > self.approval_policy.adjustment_threshold.get(acc.borrow().currency));
+    }
+
+    // Queues a large withdrawal for the bank, or a minor's guardian, to approve. Nothing is
+    // debited until then, and whether there is enough money is only checked at that point.
+    pub fn request_withdrawal(self: &mut Self, owner: BankAccountID, id: MoneyAccountID, amount: i32) -> Result<ApprovalID, BalanceError> {
+        if amount <= 0 {
+            return Err(BalanceError::InvalidAmount);
+        }
+        if self.money_accounts.get(&id) != Some(&owner) {
+            return Err(BalanceError::NotFound);
+        }
+        if !self.owner_is_verified(id) {
+            return Err(BalanceError::NotVerified);
+        }
+        if self.get_money_account_with_id_u32(id).is_some_and(|acc| acc.borrow().locked) {
+            return Err(BalanceError::Locked);
+        }
+        let reviewer = self.outflow_reviewer(id, amount).unwrap_or(Reviewer::Bank);
+        return Ok(self.open_approval_request(ApprovalOperation::Withdrawal { id: id, amount: amount }, ApprovalParty::Customer(owner), reviewer));
+    }
+
+    // Queues a manual adjustment for a second staff member to approve.
+    pub fn request_adjustment(self: &mut Self, acting: StaffID, id: MoneyAccountID, amount: i32, reason: &str) -> Result<ApprovalID, StaffError> {
+        self.check_adjustment(acting, id, amount, reason)?;
+        let operation = ApprovalOperation::Adjustment { id: id, amount: amount, reason: String::from(reason.trim()) };
+        return Ok(self.open_approval_request(operation, ApprovalParty::Staff(acting), Reviewer::Bank));
+    }
+
+    pub fn get_approval_request(self: &Self, id: ApprovalID) -> Option<&ApprovalRequest> {
+        return self.approval_requests.get(&id);
+    }
+
+    // Requests waiting for the bank to decide, oldest first. Business customers and guardians
+    // decide their own.
+    pub fn get_pending_approvals(self: &Self) -> Vec<&ApprovalRequest> {
+        let mut pending: Vec<&ApprovalRequest> = self.approval_requests.values()
+            .filter(|request| request.is_pending() && request.reviewer == Reviewer::Bank)
+            .collect();
+        pending.sort_by_key(|request| request.id);
+        return pending;
+    }
+
+    pub fn approve_request(self: &mut Self, acting: StaffID, id: ApprovalID) -> Result<(), ApprovalError> {
+        self.check_staff_decision(acting, id)?;
+        return self.carry_out_request(id, ApprovalParty::Staff(acting));
+    }
+
+    pub fn reject_request(self: &mut Self, acting: StaffID, id: ApprovalID, reason: &str) -> Result<(), ApprovalError> {
+        self.check_staff_decision(acting, id)?;
+        return self.reject_checked_request(id, ApprovalParty::Staff(acting), reason);
+    }
+
+    // Lapses every request nobody decided in time.
+    pub fn expire_approval_requests(self: &mut Self) {
+        let now = self.clock.now();
+        let mut expired: Vec<ApprovalID> = self.approval_requests.values()
+            .filter(|request| request.is_pending() && now >= request.expires_at)
+            .map(|request| request.id)
+            .collect();
+        expired.sort();
+        for id in expired {
+            self.close_request(id, ApprovalStatus::Expired, None, None);
+            self.audit(self.approval_customer(id), AuditAction::ApprovalDecision, AuditOutcome::Success, format!("Request {} expired", id));
+        }
+    }
+
+    // Outcomes of a staff member's own requests they have not seen yet.
+    pub fn take_staff_notifications(self: &mut Self, acting: StaffID) -> Vec<String> {
+        let mut ids: Vec<ApprovalID> = self.approval_requests.values()
+            .filter(|request| request.requested_by == ApprovalParty::Staff(acting) && !request.is_pending() && !request.requester_notified)
+            .map(|request| request.id)
+            .collect();
+        ids.sort();
+        let mut messages: Vec<String> = vec![];
+        for id in ids {
+            messages.push(self.approval_outcome_message(id));
+            self.approval_requests.get_mut(&id).unwrap().requester_notified = true;
+        }
+        return messages;
+    }
+
+    fn open_approval_request(self: &mut Self, operation: ApprovalOperation, requester: ApprovalParty, reviewer: Reviewer) -> ApprovalID {
+        let id = self.next_approval_id;
+        self.next_approval_id += 1;
+        let now = self.clock.now();
+        let detail = format!("Request {}: {}, {}", id, operation.description(), self.format_for_account(operation.money_account(), operation.amount()));
+        let owner = self.money_accounts.get(&operation.money_account()).copied();
+        self.approval_requests.insert(id, ApprovalRequest {
+            id: id,
+            operation: operation,
+            requested_by: requester,
+            reviewer: reviewer,
+            requested_at: now,
+            expires_at: now + self.approval_policy.expires_after,
+            status: ApprovalStatus::Pending,
+            decided_by: None,
+            earlier_approvals: vec![],
+            decided_at: None,
+            note: None,
+            requester_notified: false,
+        });
+        if let (Reviewer::Guardian(guardian), Some(owner)) = (reviewer, owner) {
+            let operation = &self.approval_requests[&id].operation;
+            let name = self.bank_accounts[&owner].borrow().name.clone();
+            let message = format!("{} asked for your approval: {}, {}. Review it under children's accounts.", name, operation.description(), self.format_for_account(operation.money_account(), operation.amount().abs()));
+            self.notifications.push(guardian, message, now);
+        }
+        self.audit_as(requester, owner, AuditAction::ApprovalRequested, AuditOutcome::Success, detail);
+        return id;
+    }
+
+    fn check_staff_decision(self: &mut Self, acting: StaffID, id: ApprovalID) -> Result<(), ApprovalError> {
+        self.require_staff_permission(acting, StaffPermission::ApproveRequests, AuditAction::ApprovalDecision).map_err(|_| ApprovalError::PermissionDenied)?;
+        if self.approval_requests.get(&id).is_some_and(|request| request.reviewer != Reviewer::Bank) {
+            return Err(ApprovalError::NotFound); // the business's own approvers or the guardian decide these
+        }
+        return self.check_decision(ApprovalParty::Staff(acting), id);
+    }
+
+    // The request has to be open still and asked for by someone else.
+    fn check_decision(self: &mut Self, decider: ApprovalParty, id: ApprovalID) -> Result<(), ApprovalError> {
+        let request = self.approval_requests.get(&id).ok_or(ApprovalError::NotFound)?;
+        if !request.is_pending() {
+            return Err(ApprovalError::NotPending);
+        }
+        if self.clock.now() >= request.expires_at {
+            self.expire_approval_requests();
+            return Err(ApprovalError::Expired);
+        }
+        let refusal = if request.requested_by == decider || request.earlier_approvals.contains(&decider) {
+            Some(ApprovalError::SelfApproval)
+        } else if let (ApprovalParty::Staff(requester), ApprovalParty::Staff(acting)) = (request.requested_by, decider) {
+            // whoever adds a staff member knows their first password, so could be acting as them
+            if self.staff_added_by(requester, acting) || self.staff_added_by(acting, requester) { Some(ApprovalError::RelatedStaff) } else { None }
+        } else {
+            None
+        };
+        if let Some(err) = refusal {
+            let customer = self.approval_customer(id);
+            self.audit_as(decider, customer, AuditAction::ApprovalDecision, AuditOutcome::Failure, format!("Request {}: {}", id, err.as_str()));
+            return Err(err);
+        }
+        return Ok(());
+    }
+
+    // Whether `member` was added by `ancestor`, directly or through staff `ancestor` added.
+    fn staff_added_by(self: &Self, ancestor: StaffID, member: StaffID) -> bool {
+        let mut current = self.staff.get(&member).and_then(|member| member.created_by);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.staff.get(&id).and_then(|member| member.created_by);
+        }
+        return false;
+    }
+
+    // Moves the money on the decider's behalf. If that fails, e.g. the account no longer has
+    // enough, the request is closed as failed and the requester is told why. A business
+    // approver's or guardian's yes is not enough above the bank's threshold: the request then
+    // goes on to the bank.
+    fn carry_out_request(self: &mut Self, id: ApprovalID, decider: ApprovalParty) -> Result<(), ApprovalError> {
+        let operation = self.approval_requests[&id].operation.clone();
+        if self.approval_requests[&id].reviewer != Reviewer::Bank && self.outflow_needs_approval(operation.money_account(), -operation.amount()) {
+            self.pass_to_bank(id, decider);
+            return Ok(());
+        }
+        let decider_context = AuditContext { actor: decider.audit_actor(), channel: self.audit_context.channel };
+        let context = std::mem::replace(&mut self.audit_context, decider_context);
+        let result = match operation {
+            ApprovalOperation::Withdrawal { id, amount } => self.post_withdrawal(id, amount).map(|_| ()).map_err(|err| String::from(err.as_str())),
+            ApprovalOperation::Adjustment { id, amount, reason } => match decider {
+                ApprovalParty::Staff(acting) => {
+                    self.apply_adjustment(acting, id, amount, &reason);
+                    Ok(())
+                },
+                _ => Err(String::from(ApprovalError::PermissionDenied.as_str())),
+            },
+            // checked again, the entry or deposit may have changed while the request waited
+            ApprovalOperation::Reversal { transaction, amount, reason, .. } => match decider {
+                ApprovalParty::Staff(acting) => {
+                    self.check_reversal(transaction, Some(amount.abs()))
+                        .and_then(|_| self.apply_reversal(acting, transaction, Some(amount.abs()), reason))
+                        .map(|_| ()).map_err(|err| String::from(err.as_str()))
+                },
+                _ => Err(String::from(ApprovalError::PermissionDenied.as_str())),
+            },
+            ApprovalOperation::FeeWaiver { transaction, .. } => match decider {
+                ApprovalParty::Staff(acting) => self.apply_fee_waiver(acting, transaction).map(|_| ()).map_err(|err| String::from(err.as_str())),
+                _ => Err(String::from(ApprovalError::PermissionDenied.as_str())),
+            },
+            ApprovalOperation::ChequeReturn { deposit, .. } => match decider {
+                ApprovalParty::Staff(acting) => self.apply_bounce(acting, deposit).map(|_| ()).map_err(|err| String::from(err.as_str())),
+                _ => Err(String::from(ApprovalError::PermissionDenied.as_str())),
+            },
+            ApprovalOperation::BillPayment { owner, from, payee, amount, reference } => {
+                self.post_bill_payment(owner, from, payee, amount, reference).map(|_| ()).map_err(|err| String::from(err.as_str()))
+            },
+            ApprovalOperation::StandingOrder { owner, from, to, amount, frequency, start } => {
+                match self.check_standing_order(owner, from, to, amount, start.max(self.clock.today())) {
+                    Ok(()) => {
+                        self.insert_standing_order(owner, from, to, amount, frequency, start.max(self.clock.today()));
+                        Ok(())
+                    },
+                    Err(err) => Err(String::from(err.as_str())),
+                }
+            },
+        };
+        self.audit_context = context;
+        match result {
+            Ok(()) => {
+                self.decide_request(id, decider, ApprovalStatus::Approved, None);
+                return Ok(());
+            },
+            Err(reason) => {
+                self.decide_request(id, decider, ApprovalStatus::Failed, Some(reason));
+                return Err(ApprovalError::Failed);
+            },
+        }
+    }
+
+    fn pass_to_bank(self: &mut Self, id: ApprovalID, decider: ApprovalParty) {
+        let now = self.clock.now();
+        let request = self.approval_requests.get_mut(&id).unwrap();
+        request.earlier_approvals.push(decider);
+        request.reviewer = Reviewer::Bank;
+        request.expires_at = now + self.approval_policy.expires_after;
+        let requested_by = request.requested_by;
+        let customer = self.approval_customer(id);
+        if let ApprovalParty::Customer(owner) | ApprovalParty::BusinessUser(owner, _) = requested_by {
+            self.notifications.push(owner, format!("Request {} was approved by {} and now needs the bank's approval too.", id, decider.description()), now);
+        }
+        self.audit_as(decider, customer, AuditAction::ApprovalDecision, AuditOutcome::Success, format!("Request {} approved, passed on to the bank", id));
+    }
+
+    fn reject_checked_request(self: &mut Self, id: ApprovalID, decider: ApprovalParty, reason: &str) -> Result<(), ApprovalError> {
+        if reason.trim().is_empty() {
+            return Err(ApprovalError::MissingReason);
+        }
+        self.decide_request(id, decider, ApprovalStatus::Rejected, Some(String::from(reason.trim())));
+        return Ok(());
+    }
+
+    fn decide_request(self: &mut Self, id: ApprovalID, decider: ApprovalParty, status: ApprovalStatus, note: Option<String>) {
+        let detail = match &note {
+            Some(note) => format!("Request {} {}: {}", id, status.description().to_lowercase(), note),
+            None => format!("Request {} {}", id, status.description().to_lowercase()),
+        };
+        self.close_request(id, status, Some(decider), note);
+        let customer = self.approval_customer(id);
+        self.audit_as(decider, customer, AuditAction::ApprovalDecision, AuditOutcome::Success, detail);
+    }
+
+    // Records the outcome and tells a customer requester straight away. Business users share
+    // their customer's notifications.
+    fn close_request(self: &mut Self, id: ApprovalID, status: ApprovalStatus, decided_by: Option<ApprovalParty>, note: Option<String>) {
+        let now = self.clock.now();
+        let request = self.approval_requests.get_mut(&id).unwrap();
+        request.status = status;
+        request.decided_by = decided_by;
+        request.decided_at = Some(now);
+        request.note = note;
+        if let ApprovalParty::Customer(owner) | ApprovalParty::BusinessUser(owner, _) = request.requested_by {
+            request.requester_notified = true;
+            let message = self.approval_outcome_message(id);
+            self.notifications.push(owner, message, now);
+        }
+    }
+
+    fn approval_outcome_message(self: &Self, id: ApprovalID) -> String {
+        let request = &self.approval_requests[&id];
+        let amount = self.format_for_account(request.operation.money_account(), request.operation.amount().abs());
+        return request.outcome_message(&amount);
+    }
+
+    fn approval_customer(self: &Self, id: ApprovalID) -> Option<BankAccountID> {
+        return self.approval_requests.get(&id).and_then(|request| self.money_accounts.get(&request.operation.money_account()).copied());
+    }
+
+    fn audit_as(self: &mut Self, party: ApprovalParty, customer: Option<BankAccountID>, action: AuditAction, outcome: AuditOutcome, detail: String) {
+        let context = AuditContext { actor: party.audit_actor(), channel: self.audit_context.channel };
+        self.audit_log.append(self.clock.now(), &context, customer, action, outcome, detail);
+    }
+
+    // Business customers
+    // The customer's own login administers the business's users.
+    pub fn add_business_user(self: &mut Self, owner: BankAccountID, user_id: String, name: String, permission: BusinessPermission, spending_limit: Option<i32>) -> Result<(BusinessUserID, SecretString), BusinessError> {
+        let ptr_bank_account = self.get_business(owner)?;
+        if user_id.is_empty() || user_id.contains(char::is_whitespace) {
+            return Err(BusinessError::InvalidUserId);
+        }
+        if self.user_id_in_use(&user_id) {
+            return Err(BusinessError::UserIdTaken);
+        }
+        if name.trim().is_empty() {
+            return Err(BusinessError::MissingName);
+        }
+        if spending_limit.is_some_and(|limit| limit <= 0) {
+            return Err(BusinessError::InvalidLimit);
+        }
+        let temporary_password = passwords::generate_temporary_password(self.rng.as_mut());
+        let password = self.hash_password(&temporary_password);
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let id = bank_account.business_users.len() as BusinessUserID + 1;
+        let detail = format!("Added user {} \"{}\" with {}", id, user_id, describe_business_access(permission, spending_limit));
+        bank_account.business_users.push(BusinessUser {
+            id: id,
+            user_id: user_id,
+            name: name,
+            password: password.clone(),
+            password_history: vec![password],
+            password_changed_at: Some(self.clock.now()),
+            must_change_password: true,
+            permission: permission,
+            spending_limit: spending_limit,
+            active: true,
+        });
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::BusinessUserChange, AuditOutcome::Success, detail);
+        return Ok((id, temporary_password));
+    }
+
+    pub fn update_business_user(self: &mut Self, owner: BankAccountID, user: BusinessUserID, permission: BusinessPermission, spending_limit: Option<i32>) -> Result<(), BusinessError> {
+        if spending_limit.is_some_and(|limit| limit <= 0) {
+            return Err(BusinessError::InvalidLimit);
+        }
+        let ptr_bank_account = self.get_business(owner)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let business_user = bank_account.business_user_mut(user).ok_or(BusinessError::UserNotFound)?;
+        business_user.permission = permission;
+        business_user.spending_limit = spending_limit;
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::BusinessUserChange, AuditOutcome::Success, format!("User {} now has {}", user, describe_business_access(permission, spending_limit)));
+        return Ok(());
+    }
+
+    pub fn deactivate_business_user(self: &mut Self, owner: BankAccountID, user: BusinessUserID) -> Result<(), BusinessError> {
+        let ptr_bank_account = self.get_business(owner)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        bank_account.business_user_mut(user).ok_or(BusinessError::UserNotFound)?.active = false;
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::BusinessUserChange, AuditOutcome::Success, format!("Removed user {}", user));
+        return Ok(());
+    }
+
+    pub fn find_business_user(self: &Self, user_id: &str) -> Option<(Rc<RefCell<BankAccount>>, BusinessUserID)> {
+        for account in self.bank_accounts.values() {
+            if let Some(user) = account.borrow().business_users.iter().find(|user| user.user_id == user_id) {
+                return Some((account.clone(), user.id));
+            }
+        }
+        return None;
+    }
+
+    pub fn change_business_user_password(self: &mut Self, owner: BankAccountID, user: BusinessUserID, current_password: &SecretString, new_password: SecretString) -> Result<(), BusinessError> {
+        let ptr_bank_account = self.get_business(owner)?;
+        let mut bank_account = ptr_bank_account.borrow_mut();
+        let business_user = bank_account.business_user_mut(user).ok_or(BusinessError::UserNotFound)?;
+        if !business_user.password.verify(current_password.expose()) {
+            return Err(BusinessError::WrongPassword);
+        }
+        if !passwords::check_password(new_password.expose(), &self.password_policy, &self.common_passwords, &business_user.password_history).is_empty() {
+            return Err(BusinessError::WeakPassword);
+        }
+        let new_password = PasswordHash::new(&new_password, self.password_policy.hash_iterations, self.rng.as_mut());
+        business_user.password_history.push(new_password.clone());
+        let excess = business_user.password_history.len().saturating_sub(self.password_policy.history_size.max(1));
+        business_user.password_history.drain(..excess);
+        business_user.password = new_password;
+        business_user.password_changed_at = Some(self.clock.now());
+        business_user.must_change_password = false;
+        drop(bank_account);
+        self.audit(Some(owner), AuditAction::PasswordChange, AuditOutcome::Success, format!("User {}", user));
+        return Ok(());
+    }
+
+    // Same rules as for customers: a temporary password or one past the policy's age has to be changed.
+    pub fn business_password_change_required(self: &Self, owner: BankAccountID, user: BusinessUserID) -> bool {
+        let ptr_bank_account = match self.bank_accounts.get(&owner) {
+            Some(acc) => acc,
+            None => return false,
+        };
+        let bank_account = ptr_bank_account.borrow();
+        let business_user = match bank_account.business_user(user) {
+            Some(business_user) => business_user,
+            None => return false,
+        };
+        if business_user.must_change_password {
+            return true;
+        }
+        match (self.password_policy.max_age_days, business_user.password_changed_at) {
+            (Some(max_age_days), Some(changed_at)) => return self.clock.now() - changed_at > Duration::days(max_age_days),
+            _ => return false,
+        }
+    }
+
+    // Whether a business user may make the payment straight away. ApprovalRequired means it is
+    // above their spending limit, or at least the two-factor step-up amount, and has to go through
+    // request_business_approval instead. Business users have no second factor of their own.
+    pub fn authorize_business_payment(self: &Self, owner: BankAccountID, user: BusinessUserID, operation: &ApprovalOperation) -> Result<(), BusinessError> {
+        let ptr_bank_account = self.get_business(owner)?;
+        let bank_account = ptr_bank_account.borrow();
+        let business_user = bank_account.business_user(user).ok_or(BusinessError::UserNotFound)?;
+        if !business_user.can_initiate() {
+            return Err(BusinessError::PermissionDenied);
+        }
+        let amount = operation.amount().abs();
+        if !business_user.within_limit(amount) || self.needs_owner_login(operation) {
+            return Err(BusinessError::ApprovalRequired);
+        }
+        return Ok(());
+    }
+
+    // Amounts a step-up would cover for the customer are left to the customer's own login.
+    fn needs_owner_login(self: &Self, operation: &ApprovalOperation) -> bool {
+        return match self.get_money_account_with_id_u32(operation.money_account()) {
+            Some(acc) => operation.amount().abs() >= self.two_factor_policy.step_up_amount.get(acc.borrow().currency),
+            None => false,
+        };
+    }
+
+    // Queues a withdrawal, bill payment or standing order from one of the business's users. Above
+    // their own limit another user approves it first; if the bank's threshold applies as well, the
+    // request goes on to the bank afterwards, or straight there when the user could pay it alone.
+    pub fn request_business_approval(self: &mut Self, owner: BankAccountID, user: BusinessUserID, operation: ApprovalOperation) -> Result<ApprovalID, BusinessError> {
+        if operation.is_staff_operation() {
+            return Err(BusinessError::PermissionDenied);
+        }
+        if self.money_accounts.get(&operation.money_account()) != Some(&owner) {
+            return Err(BusinessError::NotFound);
+        }
+        let reviewer = match self.authorize_business_payment(owner, user, &operation) {
+            Ok(()) => self.outflow_reviewer(operation.money_account(), -operation.amount()).unwrap_or(Reviewer::Business(owner)),
+            Err(BusinessError::ApprovalRequired) => Reviewer::Business(owner),
+            Err(err) => return Err(err),
+        };
+        return Ok(self.open_approval_request(operation, ApprovalParty::BusinessUser(owner, user), reviewer));
+    }
+
+    // Requests from the business's users waiting for one of its approvers, oldest first.
+    pub fn get_business_pending_approvals(self: &Self, owner: BankAccountID) -> Vec<&ApprovalRequest> {
+        let mut pending: Vec<&ApprovalRequest> = self.approval_requests.values()
+            .filter(|request| request.is_pending() && request.reviewer == Reviewer::Business(owner))
+            .collect();
+        pending.sort_by_key(|request| request.id);
+        return pending;
+    }
+
+    // `approver` is None for the customer's own login, which is not limited.
+    pub fn approve_business_request(self: &mut Self, owner: BankAccountID, approver: Option<BusinessUserID>, id: ApprovalID) -> Result<(), ApprovalError> {
+        let decider = self.check_business_decision(owner, approver, id)?;
+        return self.carry_out_request(id, decider);
+    }
+
+    pub fn reject_business_request(self: &mut Self, owner: BankAccountID, approver: Option<BusinessUserID>, id: ApprovalID, reason: &str) -> Result<(), ApprovalError> {
+        let decider = self.check_business_decision(owner, approver, id)?;
+        return self.reject_checked_request(id, decider, reason);
+    }
+
+    fn check_business_decision(self: &mut Self, owner: BankAccountID, approver: Option<BusinessUserID>, id: ApprovalID) -> Result<ApprovalParty, ApprovalError> {
+        let request = self.approval_requests.get(&id).ok_or(ApprovalError::NotFound)?;
+        if request.reviewer != Reviewer::Business(owner) {
+            return Err(ApprovalError::NotFound);
+        }
+        let amount = request.operation.amount().abs();
+        let decider = match approver {
+            Some(user) => {
+                let ptr_bank_account = self.get_business(owner).map_err(|_| ApprovalError::NotFound)?;
+                let bank_account = ptr_bank_account.borrow();
+                let business_user = bank_account.business_user(user).ok_or(ApprovalError::PermissionDenied)?;
+                if !business_user.can_approve() {
+                    return Err(ApprovalError::PermissionDenied);
+                }
+                if !business_user.within_limit(amount) || self.needs_owner_login(&request.operation) {
+                    return Err(ApprovalError::OverLimit);
+                }
+                ApprovalParty::BusinessUser(owner, user)
+            },
+            None => ApprovalParty::Customer(owner),
+        };
+        self.check_decision(decider, id)?;
+        return Ok(decider);
+    }
+
+    fn get_business(self: &Self, owner: BankAccountID) -> Result<Rc<RefCell<BankAccount>>, BusinessError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(owner).ok_or(BusinessError::NotFound)?;
+        if ptr_bank_account.borrow().business.is_none() {
+            return Err(BusinessError::NotBusiness);
+        }
+        return Ok(ptr_bank_account);
+    }
+
+    // User IDs are unique across customers and the users of business customers.
+    pub fn user_id_in_use(self: &Self, user_id: &str) -> bool {
+        return self.find_bank_account_with_user_id(user_id).is_some() || self.find_business_user(user_id).is_some();
+    }
+
+    // Minors
+    pub fn set_guardian_policy(self: &mut Self, policy: GuardianPolicy) {
+        self.guardian_policy = policy;
+    }
+
+    pub fn get_guardian_policy(self: &Self) -> GuardianPolicy {
+        return self.guardian_policy;
+    }
+
+    // Opens an account for a child, who logs in with their own user ID and password but needs
+    // the guardian's approval for larger payments and withdrawals until they come of age.
+    pub fn register_minor(self: &mut Self, guardian: BankAccountID, application: Application) -> Result<Rc<RefCell<BankAccount>>, OnboardingError> {
+        let user_id = application.user_id.clone();
+        let result = self.check_guardian(guardian).and_then(|()| self.open_customer(application, Some(guardian)));
+        match &result {
+            Ok(acc) => {
+                let (id, status) = (acc.borrow().id, acc.borrow().onboarding_status);
+                self.audit(Some(id), AuditAction::Registration, AuditOutcome::Success, format!("User ID \"{}\", minor with guardian {}, identity {}", user_id, guardian, status.description().to_lowercase()));
+            },
+            Err(err) => self.audit(Some(guardian), AuditAction::Registration, AuditOutcome::Failure, format!("Minor user ID \"{}\": {}", user_id, err.as_str())),
+        }
+        return result;
+    }
+
+    pub fn get_guardian(self: &Self, owner: BankAccountID) -> Option<BankAccountID> {
+        return self.bank_accounts.get(&owner).and_then(|acc| acc.borrow().guardian);
+    }
+
+    pub fn is_minor(self: &Self, owner: BankAccountID) -> bool {
+        return self.get_guardian(owner).is_some();
+    }
+
+    // The guardian's children, by customer number.
+    pub fn get_wards(self: &Self, guardian: BankAccountID) -> Vec<Rc<RefCell<BankAccount>>> {
+        let mut wards: Vec<Rc<RefCell<BankAccount>>> = self.bank_accounts.values()
+            .filter(|acc| acc.borrow().guardian == Some(guardian))
+            .cloned()
+            .collect();
+        wards.sort_by_key(|acc| acc.borrow().id);
+        return wards;
+    }
+
+    // One of a child's accounts for the guardian to look through. Recorded in the child's audit trail.
+    pub fn guardian_view_account(self: &mut Self, guardian: BankAccountID, id: MoneyAccountID) -> Result<Rc<RefCell<MoneyAccount>>, GuardianError> {
+        let owner = *self.money_accounts.get(&id).ok_or(GuardianError::NotFound)?;
+        if self.get_guardian(owner) != Some(guardian) {
+            self.audit(Some(owner), AuditAction::CustomerViewed, AuditOutcome::Failure, format!("Account {}: {}", id, GuardianError::NotWard.as_str()));
+            return Err(GuardianError::NotWard);
+        }
+        let ptr_money_account = self.get_money_account_with_id_u32(id).ok_or(GuardianError::NotFound)?;
+        self.audit(Some(owner), AuditAction::CustomerViewed, AuditOutcome::Success, format!("Guardian {} viewed account {}", guardian, id));
+        return Ok(ptr_money_account);
+    }
+
+    // The children's requests waiting for the guardian, oldest first.
+    pub fn get_guardian_pending_approvals(self: &Self, guardian: BankAccountID) -> Vec<&ApprovalRequest> {
+        let mut pending: Vec<&ApprovalRequest> = self.approval_requests.values()
+            .filter(|request| request.is_pending() && request.reviewer == Reviewer::Guardian(guardian))
+            .collect();
+        pending.sort_by_key(|request| request.id);
+        return pending;
+    }
+
+    pub fn approve_guardian_request(self: &mut Self, guardian: BankAccountID, id: ApprovalID) -> Result<(), ApprovalError> {
+        self.check_guardian_decision(guardian, id)?;
+        return self.carry_out_request(id, ApprovalParty::Customer(guardian));
+    }
+
+    pub fn reject_guardian_request(self: &mut Self, guardian: BankAccountID, id: ApprovalID, reason: &str) -> Result<(), ApprovalError> {
+        self.check_guardian_decision(guardian, id)?;
+        return self.reject_checked_request(id, ApprovalParty::Customer(guardian), reason);
+    }
+
+    fn check_guardian_decision(self: &mut Self, guardian: BankAccountID, id: ApprovalID) -> Result<(), ApprovalError> {
+        if !self.approval_requests.get(&id).is_some_and(|request| request.reviewer == Reviewer::Guardian(guardian)) {
+            return Err(ApprovalError::NotFound);
+        }
+        return self.check_decision(ApprovalParty::Customer(guardian), id);
+    }
+
+    // Guardians have to be verified adult personal customers without a guardian of their own.
+    fn check_guardian(self: &Self, guardian: BankAccountID) -> Result<(), OnboardingError> {
+        let ptr_bank_account = self.get_bank_account_with_id_u32(guardian).ok_or(OnboardingError::NotFound)?;
+        let bank_account = ptr_bank_account.borrow();
+        let adult = onboarding::age_on(bank_account.dob, self.clock.today()) >= self.onboarding_policy.min_age_years as i32;
+        if bank_account.onboarding_status != OnboardingStatus::Verified || bank_account.guardian.is_some() || bank_account.business.is_some() || !adult {
+            return Err(OnboardingError::GuardianNotEligible);
+        }
+        return Ok(());
+    }
+
+    // Children who have come of age keep their accounts as standard ones. Requests still
+    // waiting for the guardian lapse, since they can now make them themselves.
+    fn convert_adult_minors(self: &mut Self) {
+        let today = self.clock.today();
+        let min_age = self.onboarding_policy.min_age_years as i32;
+        let mut converted: Vec<(BankAccountID, BankAccountID)> = self.bank_accounts.values()
+            .filter_map(|acc| {
+                let acc = acc.borrow();
+                return acc.guardian.filter(|_| onboarding::age_on(acc.dob, today) >= min_age).map(|guardian| (acc.id, guardian));
+            })
+            .collect();
+        converted.sort();
+        let now = self.clock.now();
+        for (owner, guardian) in converted {
+            let name = {
+                let mut bank_account = self.bank_accounts[&owner].borrow_mut();
+                bank_account.guardian = None;
+                bank_account.name.clone()
+            };
+            let mut lapsed: Vec<ApprovalID> = self.approval_requests.values()
+                .filter(|request| request.is_pending() && request.reviewer == Reviewer::Guardian(guardian) && request.requested_by == ApprovalParty::Customer(owner))
+                .map(|request| request.id)
+                .collect();
+            lapsed.sort();
+            for id in lapsed {
+                self.close_request(id, ApprovalStatus::Expired, None, Some(String::from("your account is now a standard account")));
+                self.audit(Some(owner), AuditAction::ApprovalDecision, AuditOutcome::Success, format!("Request {} expired on conversion", id));
+            }
+            self.notifications.push(owner, String::from("Your account is now a standard account. Payments and withdrawals no longer need your guardian's approval."), now);
+            self.notifications.push(guardian, format!("{} ({}) has come of age and their account is now a standard account.", name, owner), now);
+            self.audit(Some(owner), AuditAction::MinorConversion, AuditOutcome::Success, format!("Converted to a standard account, guardian {} removed", guardian));
+        }
+    }
+
+    // Notifications
+    pub fn take_notifications(self: &mut Self, owner: BankAccountID) -> Vec<String> {
+        return self.notifications.take_unread(owner);
+    }
+}
+
+impl Default for DatabaseHandler {
+    fn default() -> Self {
+        return DatabaseHandler::new();
+    }
+}
+
+fn first_of_next_month(date: NaiveDate) -> NaiveDate {
+    if date.month() == 12 {
+        return NaiveDate::from_ymd_opt(date.year() + 1, 1, 1).unwrap();
+    }
+    return NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1).unwrap();
+}
+
+fn first_of_previous_month(date: NaiveDate) -> NaiveDate {
+    if date.month() == 1 {
+        return NaiveDate::from_ymd_opt(date.year() - 1, 12, 1).unwrap();
+    }
+    return NaiveDate::from_ymd_opt(date.year(), date.month() - 1, 1).unwrap();
+}
+
+// "approve payments, limit 50000 minor units" for audit details.
+fn describe_business_access(permission: BusinessPermission, spending_limit: Option<i32>) -> String {
+    match spending_limit {
+        Some(limit) => format!("{}, limit {} minor units", permission.description().to_lowercase(), limit),
+        None => format!("{}, no limit", permission.description().to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    // Monday 2 March 2026, 09:00
+    fn monday() -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap();
+    }
+
+    fn test_bank() -> (Rc<ManualClock>, DatabaseHandler) {
+        let clock = Rc::new(ManualClock::new(monday()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        // Full-strength hashing makes every test take seconds
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        return (clock, database);
+    }
+
+    fn application(user_id: &str, dob: NaiveDate) -> Application {
+        return Application {
+            user_id: String::from(user_id),
+            password: SecretString::from(PASSWORD),
+            name: format!("Test {}", user_id),
+            dob: dob,
+            contact: ContactDetails {
+                email: format!("{}@example.com", user_id),
+                phone: String::from("+46701234567"),
+                address: String::from("1 Test Street"),
+            },
+            id_document: format!("{}1234", user_id.to_uppercase()),
+            business: None,
+        };
+    }
+
+    // A verified adult customer with one checking account.
+    fn open_customer(database: &mut DatabaseHandler, user_id: &str, currency: Currency) -> (BankAccountID, MoneyAccountID) {
+        let dob = NaiveDate::from_ymd_opt(1990, 6, 15).unwrap();
+        let ptr_bank_account = database.register_customer(application(user_id, dob)).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, currency).borrow().id;
+        return (owner, id);
+    }
+
+    #[test]
+    fn credit_line_without_its_account_is_left_alone() {
+        let (clock, mut database) = test_bank();
+        let (owner, _) = open_customer(&mut database, "alice", Currency::Usd);
+        let card = database.open_credit_line(owner, String::from("Card"), Currency::Usd, CreditTerms { limit: 200000, purchase_apr: 0.2, cash_advance_apr: 0.25 }).unwrap();
+        database.money_accounts.remove(&card);
+        clock.advance(Duration::days(40));
+        database.process_credit_lines();
+        database.close_credit_statement(card);
+        assert!(database.credit_lines[&card].statements.is_empty());
+    }
+
+    #[test]
+    fn tampered_checkpoint_and_balance_are_reported() {
+        let (_, mut database) = test_bank();
+        let (_, id) = open_customer(&mut database, "alice", Currency::Usd);
+        database.deposit(id, 10000, DepositChannel::Cash, None).unwrap();
+        database.set_signing_key(SecretBytes::new(vec![7; 32]));
+        database.take_checkpoints();
+
+        database.checkpoints[0].entries += 1;
+        let link = database.verify_integrity().first_broken_link.unwrap();
+        assert!(link.chain == Chain::Ledger && link.reason == BrokenLinkReason::BadSignature);
+        database.checkpoints[0].entries -= 1;
+
+        database.set_signing_key(SecretBytes::new(vec![8; 32]));
+        assert!(database.verify_integrity().first_broken_link.is_some_and(|link| link.reason == BrokenLinkReason::BadSignature));
+        database.set_signing_key(SecretBytes::new(vec![7; 32]));
+
+        database.get_money_account_with_id_u32(id).unwrap().borrow_mut().balance = 99999;
+        let link = database.verify_integrity().first_broken_link.unwrap();
+        assert!(link.money_account == Some(id) && link.reason == BrokenLinkReason::BalanceMismatch);
+    }
+
+}
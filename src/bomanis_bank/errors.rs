@@ -1,31 +1,523 @@
 pub enum SessionError {
     FailedLogin,
     LockedAccount,
+    RegistrationFailed,
     Unknown,
 }
 
 impl SessionError {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Self::FailedLogin => "Login failed too many times!",
             Self::LockedAccount => "Bank account is locked!",
+            Self::RegistrationFailed => "Account could not be created!",
             Self::Unknown => "Unknown error",
         }
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum BalanceError {
     NotEnough,
     Locked,
+    InvalidAmount,
+    NotFound,
+    SameAccount,
+    NoExchangeRate,
+    WithdrawalLimitReached,
+    NotVerified,
+    KeyReused,
+    ApprovalRequired,
+    GuardianApprovalRequired,
+    PendingExceeded,
     Unknown,
 }
 
 impl BalanceError {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             BalanceError::NotEnough => "Not enough balance",
             BalanceError::Locked => "Bank account is locked",
+            BalanceError::InvalidAmount => "Amount must be greater than zero",
+            BalanceError::NotFound => "Account not found",
+            BalanceError::SameAccount => "Cannot transfer to the same account",
+            BalanceError::NoExchangeRate => "No exchange rate available between these currencies",
+            BalanceError::WithdrawalLimitReached => "Withdrawal limit for this statement cycle reached",
+            BalanceError::NotVerified => "Customer identity has not been verified",
+            BalanceError::KeyReused => "Idempotency key was already used for a different request",
+            BalanceError::ApprovalRequired => "This amount needs approval by the bank",
+            BalanceError::GuardianApprovalRequired => "This amount needs your guardian's approval",
+            BalanceError::PendingExceeded => "Amount is more than the pending balance",
             BalanceError::Unknown => "Unknown error"
         }
     }
+}
+
+#[derive(Debug)]
+pub enum ClearingError {
+    NotFound,
+    NotBounceable,
+    AlreadySettled,
+    PermissionDenied,
+    ApprovalRequired,
+}
+
+impl ClearingError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClearingError::NotFound => "Deposit not found",
+            ClearingError::NotBounceable => "Only cheque deposits can be bounced",
+            ClearingError::AlreadySettled => "Deposit has already cleared or bounced",
+            ClearingError::PermissionDenied => "Your role does not allow this",
+            ClearingError::ApprovalRequired => "This amount needs approval by a second staff member",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReversalError {
+    NotFound,
+    NotReversible,
+    AlreadyReversed,
+    ExceedsOriginal,
+    InvalidAmount,
+    NotAFee,
+    KeyReused,
+    AccountNotFound,
+    PermissionDenied,
+    PaymentPending,
+    Locked,
+    NotEnough,
+    ApprovalRequired,
+}
+
+impl ReversalError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReversalError::NotFound => "Transaction not found",
+            ReversalError::NotReversible => "This transaction cannot be reversed, post an adjustment instead",
+            ReversalError::AlreadyReversed => "Transaction has already been fully reversed",
+            ReversalError::ExceedsOriginal => "Amount is more than what remains of the original transaction",
+            ReversalError::InvalidAmount => "Amount must be greater than zero",
+            ReversalError::NotAFee => "Transaction is not a fee",
+            ReversalError::KeyReused => "Idempotency key was already used for a different request",
+            ReversalError::AccountNotFound => "Account not found",
+            ReversalError::PermissionDenied => "Your role does not allow this",
+            ReversalError::PaymentPending => "This debit belongs to a payment that has not settled yet",
+            ReversalError::Locked => "Account is locked",
+            ReversalError::NotEnough => "Not enough balance to take the amount back",
+            ReversalError::ApprovalRequired => "This amount needs approval by a second staff member",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ScheduleError {
+    NotFound,
+    AccountNotFound,
+    SameAccount,
+    InvalidAmount,
+    InvalidDate,
+    NotActive,
+    ApprovalRequired,
+    GuardianApprovalRequired,
+}
+
+impl ScheduleError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleError::NotFound => "Standing order not found",
+            ScheduleError::AccountNotFound => "Account not found",
+            ScheduleError::SameAccount => "Cannot transfer to the same account",
+            ScheduleError::InvalidAmount => "Amount must be greater than zero",
+            ScheduleError::InvalidDate => "Date must not be in the past",
+            ScheduleError::NotActive => "Standing order is no longer active",
+            ScheduleError::ApprovalRequired => "This amount needs approval by the bank",
+            ScheduleError::GuardianApprovalRequired => "This amount needs your guardian's approval",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PaymentError {
+    NotFound,
+    PayeeNotFound,
+    AccountNotFound,
+    InvalidPayee,
+    InvalidAmount,
+    NotEnough,
+    AccountLocked,
+    WithdrawalLimitReached,
+    NotVerified,
+    NotPending,
+    KeyReused,
+    DebitNotReversible,
+    ApprovalRequired,
+    GuardianApprovalRequired,
+}
+
+impl PaymentError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentError::NotFound => "Payment not found",
+            PaymentError::PayeeNotFound => "Payee not found",
+            PaymentError::AccountNotFound => "Account not found",
+            PaymentError::InvalidPayee => "Payee needs a name and a valid IBAN or account number",
+            PaymentError::InvalidAmount => "Amount must be greater than zero",
+            PaymentError::NotEnough => "Not enough balance",
+            PaymentError::AccountLocked => "Account is locked",
+            PaymentError::WithdrawalLimitReached => "Withdrawal limit for this statement cycle reached",
+            PaymentError::NotVerified => "Customer identity has not been verified",
+            PaymentError::NotPending => "Payment has already been settled",
+            PaymentError::KeyReused => "Idempotency key was already used for a different request",
+            PaymentError::DebitNotReversible => "The payment's debit can no longer be reversed",
+            PaymentError::ApprovalRequired => "This amount needs approval by the bank",
+            PaymentError::GuardianApprovalRequired => "This amount needs your guardian's approval",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CurrencyError {
+    UnknownCurrency,
+    NoRate,
+    RateFileUnreadable,
+    RateFileInvalid,
+}
+
+impl CurrencyError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CurrencyError::UnknownCurrency => "Unknown currency",
+            CurrencyError::NoRate => "No exchange rate available",
+            CurrencyError::RateFileUnreadable => "Rate file could not be read",
+            CurrencyError::RateFileInvalid => "Rate file is not in the expected format",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TermDepositError {
+    NotFound,
+    AccountNotFound,
+    NotChecking,
+    InvalidAmount,
+    InvalidTerm,
+    InvalidRate,
+    NotEnough,
+    NotActive,
+    NotVerified,
+}
+
+impl TermDepositError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TermDepositError::NotFound => "Term deposit not found",
+            TermDepositError::AccountNotFound => "Account not found",
+            TermDepositError::NotChecking => "Term deposits must be funded from a checkings account",
+            TermDepositError::InvalidAmount => "Amount is below the minimum for a term deposit",
+            TermDepositError::InvalidTerm => "Term is outside the range on offer",
+            TermDepositError::InvalidRate => "Interest rate is not valid",
+            TermDepositError::NotEnough => "Not enough balance",
+            TermDepositError::NotActive => "Term deposit is no longer active",
+            TermDepositError::NotVerified => "Customer identity has not been verified",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LoanError {
+    NotFound,
+    AccountNotFound,
+    NotChecking,
+    InvalidAmount,
+    InvalidTerm,
+    InvalidRate,
+    NotEnough,
+    NotActive,
+    NotVerified,
+    Minor,
+}
+
+impl LoanError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoanError::NotFound => "Loan not found",
+            LoanError::AccountNotFound => "Account not found",
+            LoanError::NotChecking => "Loans are paid into and repaid from a checkings account",
+            LoanError::InvalidAmount => "Amount is outside the range we lend",
+            LoanError::InvalidTerm => "Term is outside the range on offer",
+            LoanError::InvalidRate => "Interest rate is not valid",
+            LoanError::NotEnough => "Not enough balance",
+            LoanError::NotActive => "Loan has already been paid off",
+            LoanError::NotVerified => "Customer identity has not been verified",
+            LoanError::Minor => "We do not lend to customers under the age of majority",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CreditError {
+    NotFound,
+    AccountNotFound,
+    InvalidLimit,
+    InvalidRate,
+    NotVerified,
+    Minor,
+}
+
+impl CreditError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditError::NotFound => "Credit card not found",
+            CreditError::AccountNotFound => "Account not found",
+            CreditError::InvalidLimit => "Credit limit is outside the range on offer",
+            CreditError::InvalidRate => "Interest rate is not valid",
+            CreditError::NotVerified => "Customer identity has not been verified",
+            CreditError::Minor => "We do not offer credit to customers under the age of majority",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OnboardingError {
+    NotFound,
+    UserIdTaken,
+    WeakPassword,
+    MissingName,
+    FutureDateOfBirth,
+    ImplausibleDateOfBirth,
+    TooYoung,
+    InvalidEmail,
+    InvalidPhone,
+    MissingAddress,
+    InvalidIdDocument,
+    MissingCompanyName,
+    InvalidRegistrationNumber,
+    NotAMinor,
+    GuardianNotEligible,
+}
+
+impl OnboardingError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnboardingError::NotFound => "Customer not found",
+            OnboardingError::UserIdTaken => "That user ID is already in use",
+            OnboardingError::WeakPassword => "Password does not meet the password policy",
+            OnboardingError::MissingName => "Name is required",
+            OnboardingError::FutureDateOfBirth => "Date of birth is in the future",
+            OnboardingError::ImplausibleDateOfBirth => "Date of birth is too far in the past",
+            OnboardingError::TooYoung => "You are below the minimum age to open an account",
+            OnboardingError::InvalidEmail => "Email address is not valid",
+            OnboardingError::InvalidPhone => "Phone number is not valid",
+            OnboardingError::MissingAddress => "Address is required",
+            OnboardingError::InvalidIdDocument => "Identity document number should be 6 to 20 letters and digits",
+            OnboardingError::MissingCompanyName => "Company name is required",
+            OnboardingError::InvalidRegistrationNumber => "Registration number should be 6 to 12 letters and digits",
+            OnboardingError::NotAMinor => "Customers of age open their own account",
+            OnboardingError::GuardianNotEligible => "Guardians must be verified adult personal customers",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ProfileError {
+    NotFound,
+    WrongPassword,
+    WeakPassword,
+    MissingName,
+    InvalidEmail,
+    InvalidPhone,
+    MissingAddress,
+    InvalidUserId,
+    UserIdTaken,
+}
+
+impl ProfileError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileError::NotFound => "Customer not found",
+            ProfileError::WrongPassword => "Current password is incorrect",
+            ProfileError::WeakPassword => "New password does not meet the password policy",
+            ProfileError::MissingName => "Name is required",
+            ProfileError::InvalidEmail => "Email address is not valid",
+            ProfileError::InvalidPhone => "Phone number is not valid",
+            ProfileError::MissingAddress => "Address is required",
+            ProfileError::InvalidUserId => "User ID must not be empty or contain spaces",
+            ProfileError::UserIdTaken => "That user ID is already in use",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TwoFactorError {
+    NotFound,
+    AlreadyEnabled,
+    NotEnrolled,
+    NotEnabled,
+    InvalidCode,
+    Locked,
+    InvalidPolicy,
+}
+
+impl TwoFactorError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TwoFactorError::NotFound => "Customer not found",
+            TwoFactorError::AlreadyEnabled => "Two-factor authentication is already enabled",
+            TwoFactorError::NotEnrolled => "Two-factor authentication has not been set up",
+            TwoFactorError::NotEnabled => "Two-factor authentication is not enabled",
+            TwoFactorError::InvalidCode => "Authentication code is not valid",
+            TwoFactorError::Locked => "Too many wrong codes, please try again later",
+            TwoFactorError::InvalidPolicy => "Two-factor policy is not valid",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StaffError {
+    NotFound,
+    CustomerNotFound,
+    AccountNotFound,
+    InvalidCredentials,
+    PermissionDenied,
+    MissingReason,
+    InvalidAmount,
+    InvalidUserId,
+    UserIdTaken,
+    WeakPassword,
+    CannotDeactivateSelf,
+    ProductLocked,
+    ExportFailed,
+    ApprovalRequired,
+}
+
+impl StaffError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaffError::NotFound => "Staff member not found",
+            StaffError::CustomerNotFound => "Customer not found",
+            StaffError::AccountNotFound => "Account not found",
+            StaffError::InvalidCredentials => "Incorrect staff details",
+            StaffError::PermissionDenied => "Your role does not allow this",
+            StaffError::MissingReason => "A reason is required",
+            StaffError::InvalidAmount => "Amount must not be zero",
+            StaffError::InvalidUserId => "User ID must not be empty or contain spaces",
+            StaffError::UserIdTaken => "That user ID is already in use",
+            StaffError::WeakPassword => "Password does not meet the password policy",
+            StaffError::CannotDeactivateSelf => "You cannot deactivate yourself",
+            StaffError::ProductLocked => "Term deposit and loan accounts stay locked",
+            StaffError::ExportFailed => "The audit log could not be written",
+            StaffError::ApprovalRequired => "This adjustment needs approval by a second staff member",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ApprovalError {
+    NotFound,
+    NotPending,
+    Expired,
+    SelfApproval,
+    RelatedStaff,
+    PermissionDenied,
+    MissingReason,
+    OverLimit,
+    Failed,
+}
+
+impl ApprovalError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalError::NotFound => "Approval request not found",
+            ApprovalError::NotPending => "Approval request has already been decided",
+            ApprovalError::Expired => "Approval request has expired",
+            ApprovalError::SelfApproval => "Requests must be approved by someone else",
+            ApprovalError::RelatedStaff => "Requests cannot be approved by staff the requester added, or who added them",
+            ApprovalError::PermissionDenied => "Your role does not allow approving requests",
+            ApprovalError::MissingReason => "A reason is required",
+            ApprovalError::OverLimit => "Amount is above your spending limit",
+            ApprovalError::Failed => "Approved, but the money could not be moved",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BusinessError {
+    NotFound,
+    NotBusiness,
+    UserNotFound,
+    InvalidUserId,
+    UserIdTaken,
+    MissingName,
+    InvalidLimit,
+    PermissionDenied,
+    ApprovalRequired,
+    WrongPassword,
+    WeakPassword,
+}
+
+impl BusinessError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BusinessError::NotFound => "Customer not found",
+            BusinessError::NotBusiness => "This is not a business customer",
+            BusinessError::UserNotFound => "User not found",
+            BusinessError::InvalidUserId => "User ID must not be empty or contain spaces",
+            BusinessError::UserIdTaken => "That user ID is already in use",
+            BusinessError::MissingName => "Name is required",
+            BusinessError::InvalidLimit => "Spending limit must be greater than zero",
+            BusinessError::PermissionDenied => "Your permissions do not allow this",
+            BusinessError::ApprovalRequired => "This amount is above your spending limit and needs approval",
+            BusinessError::WrongPassword => "Current password is incorrect",
+            BusinessError::WeakPassword => "Password does not meet the password policy",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum GuardianError {
+    NotFound,
+    NotWard,
+}
+
+impl GuardianError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuardianError::NotFound => "Account not found",
+            GuardianError::NotWard => "That account does not belong to one of your children",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StatementError {
+    NotFound,
+    WriteFailed,
+}
+
+impl StatementError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementError::NotFound => "Statement not found",
+            StatementError::WriteFailed => "The statement could not be saved",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ExportError {
+    AccountNotFound,
+    InvalidRange,
+    WriteFailed,
+}
+
+impl ExportError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportError::AccountNotFound => "Account not found",
+            ExportError::InvalidRange => "The start date is after the end date",
+            ExportError::WriteFailed => "The export could not be saved",
+        }
+    }
 }
\ No newline at end of file
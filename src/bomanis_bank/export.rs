@@ -0,0 +1,273 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::bomanis_bank::account::{MoneyAccountID, MoneyAccountType};
+use crate::bomanis_bank::audit;
+use crate::bomanis_bank::currency::Currency;
+use crate::bomanis_bank::ledger::{Transaction, TransactionKind};
+
+const OFX_BANK_ID: &str = "BOMANIS";
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ExportFormat {
+    Ofx, // OFX 2.1.1, the XML flavour
+    Qif,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            ExportFormat::Ofx => "OFX",
+            ExportFormat::Qif => "QIF",
+            ExportFormat::Csv => "CSV",
+        }
+    }
+
+    pub fn extension(self: &Self) -> &'static str {
+        match self {
+            ExportFormat::Ofx => "ofx",
+            ExportFormat::Qif => "qif",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+pub const EXPORT_FORMATS: [ExportFormat; 3] = [
+    ExportFormat::Ofx,
+    ExportFormat::Qif,
+    ExportFormat::Csv,
+];
+
+// A money account's ledger entries over [from, to], both inclusive. Every entry carries its
+// ledger transaction id, which never changes, so tools that have already imported an entry
+// recognise it when the range is exported again.
+pub struct TransactionExport<'a> {
+    pub money_account: MoneyAccountID,
+    pub account_type: MoneyAccountType,
+    pub currency: Currency,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub closing_balance: i32, // after the last entry on or before `to`
+    pub generated_at: NaiveDateTime,
+    pub transactions: Vec<&'a Transaction>,
+}
+
+impl TransactionExport<'_> {
+    // e.g. "transactions-123456-2026-01-01-2026-01-31.ofx"
+    pub fn file_name(self: &Self, format: ExportFormat) -> String {
+        return format!("transactions-{}-{}-{}.{}", self.money_account, self.from, self.to, format.extension());
+    }
+
+    pub fn render(self: &Self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Ofx => return render_ofx(self),
+            ExportFormat::Qif => return render_qif(self),
+            ExportFormat::Csv => return render_csv(self),
+        }
+    }
+}
+
+// Credit cards go in the credit card message set, everything else is a bank account.
+fn render_ofx(export: &TransactionExport) -> String {
+    let currency = export.currency;
+    let now = ofx_date(export.generated_at);
+    let is_credit_card = export.account_type == MoneyAccountType::Credit;
+    let mut ofx: Vec<String> = vec![
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>"),
+        String::from("<?OFX OFXHEADER=\"200\" VERSION=\"211\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>"),
+        String::from("<OFX>"),
+        String::from("<SIGNONMSGSRSV1><SONRS>"),
+        String::from("<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>"),
+        format!("<DTSERVER>{}</DTSERVER>", now),
+        String::from("<LANGUAGE>ENG</LANGUAGE>"),
+        String::from("</SONRS></SIGNONMSGSRSV1>"),
+    ];
+    if is_credit_card {
+        ofx.push(String::from("<CREDITCARDMSGSRSV1><CCSTMTTRNRS>"));
+    } else {
+        ofx.push(String::from("<BANKMSGSRSV1><STMTTRNRS>"));
+    }
+    ofx.push(format!("<TRNUID>{}</TRNUID>", export.money_account));
+    ofx.push(String::from("<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>"));
+    if is_credit_card {
+        ofx.push(String::from("<CCSTMTRS>"));
+        ofx.push(format!("<CURDEF>{}</CURDEF>", currency.code()));
+        ofx.push(format!("<CCACCTFROM><ACCTID>{}</ACCTID></CCACCTFROM>", export.money_account));
+    } else {
+        ofx.push(String::from("<STMTRS>"));
+        ofx.push(format!("<CURDEF>{}</CURDEF>", currency.code()));
+        ofx.push(format!("<BANKACCTFROM><BANKID>{}</BANKID><ACCTID>{}</ACCTID><ACCTTYPE>{}</ACCTTYPE></BANKACCTFROM>", OFX_BANK_ID, export.money_account, ofx_account_type(export.account_type)));
+    }
+    ofx.push(String::from("<BANKTRANLIST>"));
+    ofx.push(format!("<DTSTART>{}</DTSTART>", ofx_date(export.from.and_hms_opt(0, 0, 0).unwrap())));
+    ofx.push(format!("<DTEND>{}</DTEND>", ofx_date(export.to.and_hms_opt(23, 59, 59).unwrap())));
+    for txn in &export.transactions {
+        ofx.push(String::from("<STMTTRN>"));
+        ofx.push(format!("<TRNTYPE>{}</TRNTYPE>", ofx_transaction_type(txn)));
+        ofx.push(format!("<DTPOSTED>{}</DTPOSTED>", ofx_date(txn.posted_at)));
+        ofx.push(format!("<TRNAMT>{}</TRNAMT>", currency.format_decimal(txn.amount)));
+        ofx.push(format!("<FITID>{}</FITID>", txn.id));
+        ofx.push(format!("<NAME>{}</NAME>", xml_escape(txn.kind.description())));
+        if !txn.memo.is_empty() {
+            ofx.push(format!("<MEMO>{}</MEMO>", xml_escape(&txn.memo)));
+        }
+        ofx.push(String::from("</STMTTRN>"));
+    }
+    ofx.push(String::from("</BANKTRANLIST>"));
+    ofx.push(format!("<LEDGERBAL><BALAMT>{}</BALAMT><DTASOF>{}</DTASOF></LEDGERBAL>", currency.format_decimal(export.closing_balance), ofx_date(export.to.and_hms_opt(23, 59, 59).unwrap())));
+    if is_credit_card {
+        ofx.push(String::from("</CCSTMTRS>"));
+        ofx.push(String::from("</CCSTMTTRNRS></CREDITCARDMSGSRSV1>"));
+    } else {
+        ofx.push(String::from("</STMTRS>"));
+        ofx.push(String::from("</STMTTRNRS></BANKMSGSRSV1>"));
+    }
+    ofx.push(String::from("</OFX>"));
+    return ofx.join("\n") + "\n";
+}
+
+fn ofx_date(at: NaiveDateTime) -> String {
+    return at.format("%Y%m%d%H%M%S").to_string();
+}
+
+fn ofx_account_type(account_type: MoneyAccountType) -> &'static str {
+    match account_type {
+        MoneyAccountType::Savings => "SAVINGS",
+        MoneyAccountType::TermDeposit => "CD",
+        MoneyAccountType::Loan => "CREDITLINE",
+        _ => "CHECKING",
+    }
+}
+
+fn ofx_transaction_type(txn: &Transaction) -> &'static str {
+    match txn.kind {
+        TransactionKind::Deposit => "DEP",
+        TransactionKind::Withdrawal => "CASH",
+        TransactionKind::TransferIn | TransactionKind::TransferOut => "XFER",
+        TransactionKind::BillPayment => "PAYMENT",
+        TransactionKind::Purchase => "POS",
+        TransactionKind::Fee => "FEE",
+        TransactionKind::Interest => "INT",
+        _ if txn.amount < 0 => "DEBIT",
+        _ => "CREDIT",
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    return value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+// QIF has no transaction id field, so the id goes in the number field, which importers
+// match on.
+fn render_qif(export: &TransactionExport) -> String {
+    let account_type = if export.account_type == MoneyAccountType::Credit { "CCard" } else { "Bank" };
+    let mut qif: Vec<String> = vec![format!("!Type:{}", account_type)];
+    for txn in &export.transactions {
+        qif.push(format!("D{}", txn.posted_at.format("%m/%d/%Y")));
+        qif.push(format!("T{}", export.currency.format_decimal(txn.amount)));
+        qif.push(format!("N{}", txn.id));
+        qif.push(format!("P{}", single_line(txn.kind.description())));
+        if !txn.memo.is_empty() {
+            qif.push(format!("M{}", single_line(&txn.memo)));
+        }
+        qif.push(String::from("^"));
+    }
+    return qif.join("\n") + "\n";
+}
+
+fn single_line(value: &str) -> String {
+    return value.replace(['\r', '\n'], " ");
+}
+
+fn render_csv(export: &TransactionExport) -> String {
+    let currency = export.currency;
+    let mut rows: Vec<String> = vec![String::from("transaction_id,posted_at,type,description,amount,balance,currency")];
+    for txn in &export.transactions {
+        rows.push(format!(
+            "{},{},{},{},{},{},{}",
+            txn.id, txn.posted_at.format("%Y-%m-%dT%H:%M:%S"), audit::csv_field(txn.kind.description()), audit::csv_field(&txn.memo),
+            currency.format_decimal(txn.amount), currency.format_decimal(txn.balance_after), currency.code(),
+        ));
+    }
+    return rows.join("\n") + "\n";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::Duration;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::ExportError;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // Monday 2 March 2026, with one verified customer who has a USD checking account.
+    fn bank_with_account() -> (Rc<ManualClock>, DatabaseHandler, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: date(1990, 6, 15),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (clock, database, id);
+    }
+
+    #[test]
+    fn export_renders_ofx_and_qif_with_stable_ids() {
+        let (clock, mut database, id) = bank_with_account();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        clock.advance(Duration::days(1));
+        database.withdraw(id, 1250, None).unwrap();
+        let today = date(2026, 3, 3);
+        let export = database.get_transaction_export(id, date(2026, 3, 1), today).unwrap();
+        assert_eq!(export.transactions.len(), 2);
+        let (deposit, withdrawal) = (export.transactions[0].id, export.transactions[1].id);
+
+        let ofx = export.render(ExportFormat::Ofx);
+        assert!(ofx.starts_with("<?xml version=\"1.0\""));
+        assert!(ofx.contains(&format!("<BANKACCTFROM><BANKID>BOMANIS</BANKID><ACCTID>{}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>", id)));
+        assert!(ofx.contains("<CURDEF>USD</CURDEF>"));
+        assert!(ofx.contains("<DTSTART>20260301000000</DTSTART>\n<DTEND>20260303235959</DTEND>"));
+        assert!(ofx.contains(&format!("<TRNTYPE>DEP</TRNTYPE>\n<DTPOSTED>20260302090000</DTPOSTED>\n<TRNAMT>500.00</TRNAMT>\n<FITID>{}</FITID>", deposit)));
+        assert!(ofx.contains(&format!("<TRNTYPE>CASH</TRNTYPE>\n<DTPOSTED>20260303090000</DTPOSTED>\n<TRNAMT>-12.50</TRNAMT>\n<FITID>{}</FITID>", withdrawal)));
+        assert!(ofx.contains("<LEDGERBAL><BALAMT>487.50</BALAMT><DTASOF>20260303235959</DTASOF></LEDGERBAL>"));
+        assert!(ofx.ends_with("</STMTTRNRS></BANKMSGSRSV1>\n</OFX>\n"));
+
+        let qif = export.render(ExportFormat::Qif);
+        let lines: Vec<&str> = qif.lines().collect();
+        assert_eq!(lines[0], "!Type:Bank");
+        assert_eq!(lines[1..4], ["D03/02/2026", "T500.00", &format!("N{}", deposit)[..]]);
+        assert_eq!(lines.iter().filter(|line| **line == "^").count(), 2);
+        assert!(lines.contains(&"T-12.50"));
+        assert!(lines.contains(&&format!("N{}", withdrawal)[..]));
+        assert_eq!(lines.last(), Some(&"^"));
+
+        // the same range exported again gives the same ids
+        let again = database.export_transactions(id, date(2026, 3, 1), today, ExportFormat::Qif).unwrap();
+        assert_eq!(again, qif);
+    }
+
+    #[test]
+    fn export_refuses_ranges_it_cannot_represent() {
+        let (_, database, id) = bank_with_account();
+        let today = date(2026, 3, 2);
+        assert!(matches!(database.get_transaction_export(id, today, today - Duration::days(1)), Err(ExportError::InvalidRange)));
+        assert!(matches!(database.get_transaction_export(id, today, NaiveDate::MAX), Err(ExportError::InvalidRange)));
+        assert!(database.get_transaction_export(id, today, today).is_ok());
+    }
+}
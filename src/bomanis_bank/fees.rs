@@ -0,0 +1,260 @@
+use crate::bomanis_bank::account::MoneyAccountType;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FeeKind {
+    Maintenance,
+    Withdrawal,
+    Overdraft,
+    ReturnedPayment,
+    ExcessWithdrawal,
+    EarlyClosure,
+    LatePayment,
+}
+
+impl FeeKind {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            FeeKind::Maintenance => "Monthly maintenance fee",
+            FeeKind::Withdrawal => "Withdrawal fee",
+            FeeKind::Overdraft => "Overdraft fee",
+            FeeKind::ReturnedPayment => "Returned payment fee",
+            FeeKind::ExcessWithdrawal => "Excess withdrawal fee",
+            FeeKind::EarlyClosure => "Early closure penalty",
+            FeeKind::LatePayment => "Late payment fee",
+        }
+    }
+}
+
+// A fee that applies (or would apply) to an account, in the account's minor units.
+pub struct FeeQuote {
+    pub kind: FeeKind,
+    pub amount: i32,
+    pub memo: String,
+}
+
+// All amounts are minor units of the account's currency.
+#[derive(Copy, Clone)]
+pub struct FeeSchedule {
+    pub monthly_maintenance_fee: i32,
+    pub maintenance_waiver_balance: i32, // waived when the month's average daily balance reaches this
+    pub free_withdrawals_per_month: u32,
+    pub withdrawal_fee: i32,
+    pub overdraft_fee: i32,
+    pub returned_payment_fee: i32,
+}
+
+impl FeeSchedule {
+    pub fn free() -> Self {
+        return FeeSchedule {
+            monthly_maintenance_fee: 0,
+            maintenance_waiver_balance: 0,
+            free_withdrawals_per_month: 0,
+            withdrawal_fee: 0,
+            overdraft_fee: 0,
+            returned_payment_fee: 0,
+        }
+    }
+
+    pub fn maintenance_fee(self: &Self, average_balance: i32, period: &str) -> Option<FeeQuote> {
+        if self.monthly_maintenance_fee <= 0 || average_balance >= self.maintenance_waiver_balance {
+            return None;
+        }
+        return Some(FeeQuote {
+            kind: FeeKind::Maintenance,
+            amount: self.monthly_maintenance_fee,
+            memo: format!("{} for {}", FeeKind::Maintenance.description(), period),
+        });
+    }
+
+    // `withdrawals_so_far` excludes the withdrawal being charged for.
+    pub fn withdrawal_fee(self: &Self, withdrawals_so_far: u32) -> Option<FeeQuote> {
+        if self.withdrawal_fee <= 0 || withdrawals_so_far < self.free_withdrawals_per_month {
+            return None;
+        }
+        return Some(FeeQuote {
+            kind: FeeKind::Withdrawal,
+            amount: self.withdrawal_fee,
+            memo: format!("{} (withdrawal {} this month, {} free)", FeeKind::Withdrawal.description(), withdrawals_so_far + 1, self.free_withdrawals_per_month),
+        });
+    }
+
+    pub fn overdraft_fee(self: &Self, balance_after: i32) -> Option<FeeQuote> {
+        if self.overdraft_fee <= 0 || balance_after >= 0 {
+            return None;
+        }
+        return Some(FeeQuote {
+            kind: FeeKind::Overdraft,
+            amount: self.overdraft_fee,
+            memo: String::from(FeeKind::Overdraft.description()),
+        });
+    }
+
+    pub fn returned_payment_fee(self: &Self, what: &str) -> Option<FeeQuote> {
+        if self.returned_payment_fee <= 0 {
+            return None;
+        }
+        return Some(FeeQuote {
+            kind: FeeKind::ReturnedPayment,
+            amount: self.returned_payment_fee,
+            memo: format!("{} ({})", FeeKind::ReturnedPayment.description(), what),
+        });
+    }
+}
+
+// Caps how many times money can leave a savings account per statement cycle (a calendar month).
+#[derive(Copy, Clone)]
+pub struct WithdrawalLimit {
+    pub max_per_cycle: u32, // withdrawals, transfers out and bill payments combined
+    pub excess_fee: Option<i32>, // charge this instead of rejecting once the limit is reached
+}
+
+impl WithdrawalLimit {
+    pub fn excess_withdrawal_fee(self: &Self) -> Option<FeeQuote> {
+        let amount = self.excess_fee?;
+        return Some(FeeQuote {
+            kind: FeeKind::ExcessWithdrawal,
+            amount: amount,
+            memo: format!("{} (over {} this cycle)", FeeKind::ExcessWithdrawal.description(), self.max_per_cycle),
+        });
+    }
+}
+
+impl Default for WithdrawalLimit {
+    fn default() -> Self {
+        return WithdrawalLimit {
+            max_per_cycle: 6,
+            excess_fee: None,
+        }
+    }
+}
+
+// One fee schedule per product.
+#[derive(Copy, Clone)]
+pub struct FeeSchedules {
+    pub checking: FeeSchedule,
+    pub savings: FeeSchedule,
+    pub term_deposit: FeeSchedule,
+    pub loan: FeeSchedule,
+    pub credit: FeeSchedule,
+}
+
+impl FeeSchedules {
+    pub fn for_type(self: &Self, acc_type: MoneyAccountType) -> FeeSchedule {
+        match acc_type {
+            MoneyAccountType::Savings => self.savings,
+            MoneyAccountType::TermDeposit => self.term_deposit,
+            MoneyAccountType::Loan => self.loan,
+            MoneyAccountType::Credit => self.credit,
+            _ => self.checking,
+        }
+    }
+}
+
+impl Default for FeeSchedules {
+    fn default() -> Self {
+        return FeeSchedules {
+            checking: FeeSchedule {
+                monthly_maintenance_fee: 500,
+                maintenance_waiver_balance: 150000,
+                free_withdrawals_per_month: 10,
+                withdrawal_fee: 250,
+                overdraft_fee: 3500,
+                returned_payment_fee: 2500,
+            },
+            savings: FeeSchedule {
+                monthly_maintenance_fee: 0,
+                maintenance_waiver_balance: 0,
+                free_withdrawals_per_month: 6,
+                withdrawal_fee: 500,
+                overdraft_fee: 3500,
+                returned_payment_fee: 2500,
+            },
+            // locked accounts, their penalties are set by the term deposit and loan policies
+            term_deposit: FeeSchedule::free(),
+            loan: FeeSchedule::free(),
+            // interest and late fees are set by the credit policy
+            credit: FeeSchedule {
+                returned_payment_fee: 2500,
+                ..FeeSchedule::free()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::{Duration, NaiveDate};
+    use std::cell::RefCell;
+    use crate::bomanis_bank::account::BankAccount;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::ReversalError;
+    use crate::bomanis_bank::ledger::{IncomeKind, TransactionKind};
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::StaffRole;
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn bank(clock: Rc<ManualClock>) -> (DatabaseHandler, Rc<RefCell<BankAccount>>) {
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        return (database, ptr_bank_account);
+    }
+
+    #[test]
+    fn product_accounts_are_not_charged_maintenance() {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let (mut database, ptr_bank_account) = bank(clock.clone());
+        let schedules = FeeSchedules::default();
+        database.set_fee_schedules(FeeSchedules { loan: schedules.checking, term_deposit: schedules.checking, credit: schedules.checking, ..schedules });
+        let ids: Vec<(MoneyAccountType, u32)> = [MoneyAccountType::Checking, MoneyAccountType::Loan, MoneyAccountType::TermDeposit, MoneyAccountType::Credit].iter()
+            .map(|acc_type| (*acc_type, database.make_new_money_account(ptr_bank_account.clone(), String::from("Product"), *acc_type, Currency::Usd).borrow().id))
+            .collect();
+
+        clock.advance(Duration::days(92));
+        database.assess_maintenance_fees();
+        for (acc_type, id) in ids {
+            let fees = database.get_transaction_history(id).iter().filter(|txn| txn.kind == TransactionKind::Fee).count();
+            assert_eq!(fees, if acc_type == MoneyAccountType::Checking { 2 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn waived_fee_is_refunded_and_taken_out_of_income() {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let (mut database, ptr_bank_account) = bank(clock.clone());
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let supervisor = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        let teller = database.create_staff(admin, String::from("tess"), String::from("Tess"), SecretString::from(PASSWORD), StaffRole::Teller).unwrap();
+        database.deposit(id, 10000, DepositChannel::Cash, None).unwrap();
+        let deposit = database.get_transaction_history(id).last().unwrap().id;
+        clock.advance(Duration::days(60)); // first charged cycle is April
+        database.assess_maintenance_fees();
+        let fee = database.get_transaction_history(id).last().unwrap().id;
+        assert_eq!(database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance(), 9500);
+
+        assert_eq!(database.waive_fee(teller, fee), Err(ReversalError::PermissionDenied));
+        assert_eq!(database.waive_fee(supervisor, deposit), Err(ReversalError::NotAFee));
+        database.waive_fee(supervisor, fee).unwrap();
+        assert_eq!(database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance(), 10000);
+        let fee_income: i32 = database.get_income_entries().iter().filter(|entry| entry.kind == IncomeKind::Fee).map(|entry| entry.amount).sum();
+        assert_eq!(fee_income, 0);
+        assert_eq!(database.waive_fee(supervisor, fee), Err(ReversalError::AlreadyReversed));
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDateTime};
+
+use crate::bomanis_bank::errors::{BalanceError, PaymentError, ReversalError};
+use crate::bomanis_bank::ledger::TransactionID;
+use crate::bomanis_bank::payments::PaymentID;
+
+// Result of a money-moving call, kept so a retry with the same key gets the same answer.
+#[derive(Copy, Clone)]
+pub enum StoredOutcome {
+    Balance(Result<i32, BalanceError>),
+    Reversal(Result<TransactionID, ReversalError>),
+    Payment(Result<PaymentID, PaymentError>),
+}
+
+impl StoredOutcome {
+    // Successes and errors a retry would get again. Errors that depend on the account's state at
+    // the time, like not enough balance, are not final: the same request may go through later.
+    pub fn is_final(self: &Self) -> bool {
+        match self {
+            StoredOutcome::Balance(Err(err)) => !matches!(err,
+                BalanceError::NotEnough | BalanceError::Locked | BalanceError::NoExchangeRate | BalanceError::WithdrawalLimitReached |
+                BalanceError::NotVerified | BalanceError::ApprovalRequired | BalanceError::GuardianApprovalRequired |
+                BalanceError::PendingExceeded | BalanceError::KeyReused | BalanceError::Unknown),
+            StoredOutcome::Payment(Err(err)) => !matches!(err,
+                PaymentError::NotEnough | PaymentError::AccountLocked | PaymentError::WithdrawalLimitReached |
+                PaymentError::NotVerified | PaymentError::ApprovalRequired | PaymentError::GuardianApprovalRequired | PaymentError::KeyReused),
+            StoredOutcome::Reversal(Err(err)) => !matches!(err,
+                ReversalError::KeyReused | ReversalError::PermissionDenied | ReversalError::Locked | ReversalError::NotEnough |
+                ReversalError::ApprovalRequired),
+            _ => true,
+        }
+    }
+}
+
+pub enum Replay {
+    Fresh, // key not seen (or expired), run the operation
+    Outcome(StoredOutcome),
+    Mismatch, // key seen with different parameters
+}
+
+struct IdempotencyRecord {
+    fingerprint: String,
+    outcome: StoredOutcome,
+    stored_at: NaiveDateTime,
+}
+
+pub struct IdempotencyStore {
+    records: HashMap<String, IdempotencyRecord>,
+    retention: Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new(retention: Duration) -> Self {
+        return IdempotencyStore {
+            records: HashMap::new(),
+            retention: retention,
+        }
+    }
+
+    pub fn get_retention(self: &Self) -> Duration {
+        return self.retention;
+    }
+
+    pub fn set_retention(self: &mut Self, retention: Duration) {
+        self.retention = retention;
+    }
+
+    // `fingerprint` describes the request parameters, so a key reused for a different
+    // request can be told apart from a genuine retry.
+    pub fn check(self: &mut Self, key: &str, fingerprint: &str, now: NaiveDateTime) -> Replay {
+        self.purge_expired(now);
+        match self.records.get(key) {
+            Some(record) if record.fingerprint == fingerprint => Replay::Outcome(record.outcome),
+            Some(_) => Replay::Mismatch,
+            None => Replay::Fresh,
+        }
+    }
+
+    pub fn store(self: &mut Self, key: &str, fingerprint: String, outcome: StoredOutcome, now: NaiveDateTime) {
+        self.records.insert(String::from(key), IdempotencyRecord {
+            fingerprint: fingerprint,
+            outcome: outcome,
+            stored_at: now,
+        });
+    }
+
+    pub fn purge_expired(self: &mut Self, now: NaiveDateTime) {
+        let retention = self.retention;
+        self.records.retain(|_, record| now - record.stored_at < retention);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::NaiveDate;
+    use crate::bomanis_bank::account::{MoneyAccountID, MoneyAccountType};
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+    }
+
+    fn bank_with_account() -> (DatabaseHandler, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(at(9)));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (database, id);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn same_key_and_request_replays_the_outcome() {
+        let mut store = IdempotencyStore::new(Duration::hours(24));
+        assert!(matches!(store.check("key-1", "deposit:1:100", at(9)), Replay::Fresh));
+        store.store("key-1", String::from("deposit:1:100"), StoredOutcome::Balance(Ok(100)), at(9));
+        assert!(matches!(store.check("key-1", "deposit:1:100", at(10)), Replay::Outcome(StoredOutcome::Balance(Ok(100)))));
+    }
+
+    #[test]
+    fn same_key_for_another_request_is_a_mismatch() {
+        let mut store = IdempotencyStore::new(Duration::hours(24));
+        store.store("key-1", String::from("deposit:1:100"), StoredOutcome::Balance(Ok(100)), at(9));
+        assert!(matches!(store.check("key-1", "deposit:1:200", at(10)), Replay::Mismatch));
+    }
+
+    #[test]
+    fn keys_are_forgotten_after_the_retention_period() {
+        let mut store = IdempotencyStore::new(Duration::hours(2));
+        store.store("key-1", String::from("deposit:1:100"), StoredOutcome::Balance(Ok(100)), at(9));
+        assert!(matches!(store.check("key-1", "deposit:1:200", at(10)), Replay::Mismatch));
+        assert!(matches!(store.check("key-1", "deposit:1:200", at(11)), Replay::Fresh));
+    }
+
+    #[test]
+    fn only_outcomes_a_retry_would_repeat_are_final() {
+        assert!(StoredOutcome::Balance(Ok(0)).is_final());
+        assert!(StoredOutcome::Balance(Err(BalanceError::InvalidAmount)).is_final());
+        assert!(!StoredOutcome::Balance(Err(BalanceError::NotEnough)).is_final());
+        assert!(!StoredOutcome::Balance(Err(BalanceError::Locked)).is_final());
+        assert!(StoredOutcome::Payment(Err(PaymentError::PayeeNotFound)).is_final());
+        assert!(!StoredOutcome::Payment(Err(PaymentError::WithdrawalLimitReached)).is_final());
+        assert!(StoredOutcome::Reversal(Err(ReversalError::AlreadyReversed)).is_final());
+        assert!(!StoredOutcome::Reversal(Err(ReversalError::NotEnough)).is_final());
+    }
+
+    #[test]
+    fn retried_deposit_is_posted_once() {
+        let (mut database, id) = bank_with_account();
+        assert_eq!(database.deposit(id, 10000, DepositChannel::Cash, Some("dep-1")), Ok(10000));
+        assert_eq!(database.deposit(id, 10000, DepositChannel::Cash, Some("dep-1")), Ok(10000));
+        assert_eq!(balance(&database, id), 10000);
+        assert_eq!(database.get_transaction_history(id).len(), 1);
+        assert_eq!(database.deposit(id, 20000, DepositChannel::Cash, Some("dep-1")), Err(BalanceError::KeyReused));
+        assert_eq!(database.withdraw(id, 10000, Some("dep-1")), Err(BalanceError::KeyReused));
+    }
+
+    #[test]
+    fn retry_after_not_enough_balance_runs_again() {
+        let (mut database, id) = bank_with_account();
+        assert_eq!(database.withdraw(id, 5000, Some("wd-1")), Err(BalanceError::NotEnough));
+        database.deposit(id, 10000, DepositChannel::Cash, None).unwrap();
+        assert_eq!(database.withdraw(id, 5000, Some("wd-1")), Ok(5000));
+        assert_eq!(database.withdraw(id, 5000, Some("wd-1")), Ok(5000));
+        assert_eq!(balance(&database, id), 5000);
+    }
+
+    #[test]
+    fn invalid_amount_is_replayed() {
+        let (mut database, id) = bank_with_account();
+        assert_eq!(database.deposit(id, 0, DepositChannel::Cash, Some("dep-1")), Err(BalanceError::InvalidAmount));
+        assert_eq!(database.deposit(id, 0, DepositChannel::Cash, Some("dep-1")), Err(BalanceError::InvalidAmount));
+        assert_eq!(database.deposit(id, 100, DepositChannel::Cash, Some("dep-1")), Err(BalanceError::KeyReused));
+    }
+}
@@ -0,0 +1,236 @@
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::bomanis_bank::account::MoneyAccountID;
+use crate::bomanis_bank::secret::SecretBytes;
+
+// Type Aliases
+pub type EntryHash = [u8; 32];
+
+// What the first entry of a chain links back to.
+pub const GENESIS_HASH: EntryHash = [0; 32];
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Chain {
+    Ledger,
+    Audit,
+}
+
+impl Chain {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            Chain::Ledger => "Ledger",
+            Chain::Audit => "Audit log",
+        }
+    }
+}
+
+// SHA-256 over the previous entry's hash and this entry's content, so changing, removing or
+// reordering any entry changes every hash after it.
+pub fn chain_hash(prev_hash: &EntryHash, content: &str) -> EntryHash {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash);
+    hasher.update(content.as_bytes());
+    return hasher.finalize().into();
+}
+
+pub fn to_hex(hash: &EntryHash) -> String {
+    return hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+// The checkpoint signing key as configured, in hex. Shorter than the hash it signs is refused.
+pub fn parse_signing_key(hex: &str) -> Option<SecretBytes> {
+    let hex = hex.trim();
+    if hex.len() < 64 || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let key: Option<Vec<u8>> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect();
+    return key.map(SecretBytes::new);
+}
+
+// Free text goes in length-prefixed so no choice of memo can be mistaken for other fields.
+pub fn text_field(value: &str) -> String {
+    return format!("{}:{}", value.len(), value);
+}
+
+// The head of a chain at a point in time, signed with the bank's key. Once a checkpoint has been
+// handed out (printed, mailed to the auditors), rewriting the chain up to it and rehashing
+// everything is detectable too.
+#[derive(Clone)]
+pub struct Checkpoint {
+    pub chain: Chain,
+    pub entries: usize, // chain length when it was taken
+    pub head_hash: EntryHash,
+    pub created_at: NaiveDateTime,
+    pub signature: EntryHash, // HMAC-SHA256 under the signing key
+}
+
+impl Checkpoint {
+    pub fn new(chain: Chain, entries: usize, head_hash: EntryHash, created_at: NaiveDateTime, key: &SecretBytes) -> Self {
+        let mut checkpoint = Checkpoint {
+            chain: chain,
+            entries: entries,
+            head_hash: head_hash,
+            created_at: created_at,
+            signature: [0; 32],
+        };
+        checkpoint.signature = checkpoint.expected_signature(key);
+        return checkpoint;
+    }
+
+    pub fn is_signature_valid(self: &Self, key: &SecretBytes) -> bool {
+        return self.mac(key).verify_slice(&self.signature).is_ok(); // constant time
+    }
+
+    fn expected_signature(self: &Self, key: &SecretBytes) -> EntryHash {
+        return self.mac(key).finalize().into_bytes().into();
+    }
+
+    fn mac(self: &Self, key: &SecretBytes) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.expose()).unwrap();
+        mac.update(self.signed_content().as_bytes());
+        return mac;
+    }
+
+    fn signed_content(self: &Self) -> String {
+        return format!("{}|{}|{}|{}", self.chain.description(), self.entries, to_hex(&self.head_hash), self.created_at.format("%Y-%m-%dT%H:%M:%S%.f"));
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum BrokenLinkReason {
+    ContentChanged, // the entry no longer hashes to the hash stored with it
+    ChainBroken, // the entry does not point at the hash of the one before it
+    CheckpointMismatch, // the chain no longer matches a signed checkpoint
+    BadSignature, // the checkpoint itself was altered
+    BalanceMismatch, // the account balance differs from the last ledger entry
+}
+
+impl BrokenLinkReason {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            BrokenLinkReason::ContentChanged => "Entry content does not match its hash",
+            BrokenLinkReason::ChainBroken => "Entry does not link to the previous entry",
+            BrokenLinkReason::CheckpointMismatch => "Chain does not match a signed checkpoint",
+            BrokenLinkReason::BadSignature => "Checkpoint signature is not valid",
+            BrokenLinkReason::BalanceMismatch => "Account balance does not match the ledger",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BrokenLink {
+    pub chain: Chain,
+    pub position: usize, // 1-based entry number, or the checkpoint's entry count
+    pub money_account: Option<MoneyAccountID>, // for balance mismatches
+    pub reason: BrokenLinkReason,
+}
+
+pub struct IntegrityReport {
+    pub ledger_entries: usize,
+    pub audit_events: usize,
+    pub checkpoints: usize,
+    pub signing_key_configured: bool, // without one no checkpoints are taken
+    pub first_broken_link: Option<BrokenLink>,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(self: &Self) -> bool {
+        return self.first_broken_link.is_none();
+    }
+}
+
+// Walks a chain given each entry's stored previous hash, stored hash and freshly recomputed hash.
+// Returns the 1-based position of the first entry that does not check out.
+pub fn first_broken_entry(links: &[(EntryHash, EntryHash, EntryHash)]) -> Option<(usize, BrokenLinkReason)> {
+    let mut expected_prev = GENESIS_HASH;
+    for (index, (prev_hash, hash, recomputed)) in links.iter().enumerate() {
+        if *prev_hash != expected_prev {
+            return Some((index + 1, BrokenLinkReason::ChainBroken));
+        }
+        if recomputed != hash {
+            return Some((index + 1, BrokenLinkReason::ContentChanged));
+        }
+        expected_prev = *hash;
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::NaiveDate;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    const KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn checkpoint(key: &SecretBytes) -> Checkpoint {
+        let at = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(23, 0, 0).unwrap();
+        return Checkpoint::new(Chain::Ledger, 3, chain_hash(&GENESIS_HASH, "entry"), at, key);
+    }
+
+    #[test]
+    fn signing_key_has_to_be_32_bytes_of_hex() {
+        assert_eq!(parse_signing_key(KEY_HEX).unwrap().expose().len(), 32);
+        assert!(parse_signing_key(&KEY_HEX[..62]).is_none());
+        assert!(parse_signing_key(&KEY_HEX.replace('0', "g")).is_none());
+        assert!(parse_signing_key("").is_none());
+    }
+
+    #[test]
+    fn altered_checkpoint_fails_its_signature() {
+        let key = parse_signing_key(KEY_HEX).unwrap();
+        let mut signed = checkpoint(&key);
+        assert!(signed.is_signature_valid(&key));
+        signed.entries = 2;
+        assert!(!signed.is_signature_valid(&key));
+    }
+
+    #[test]
+    fn checkpoint_does_not_verify_under_another_key() {
+        let key = parse_signing_key(KEY_HEX).unwrap();
+        let other = parse_signing_key(&KEY_HEX.replace("1f", "ff")).unwrap();
+        assert!(!checkpoint(&key).is_signature_valid(&other));
+    }
+
+    #[test]
+    fn chain_reports_the_first_bad_link() {
+        let first = chain_hash(&GENESIS_HASH, "a");
+        let second = chain_hash(&first, "b");
+        let links = [(GENESIS_HASH, first, first), (first, second, second)];
+        assert!(first_broken_entry(&links).is_none());
+        assert!(matches!(first_broken_entry(&[(GENESIS_HASH, first, second)]), Some((1, BrokenLinkReason::ContentChanged))));
+        assert!(matches!(first_broken_entry(&[links[1]]), Some((1, BrokenLinkReason::ChainBroken))));
+    }
+
+    #[test]
+    fn checkpoints_need_a_configured_key() {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd);
+        assert!(database.take_checkpoints().is_empty());
+        assert!(!database.verify_integrity().signing_key_configured);
+
+        database.set_signing_key(SecretBytes::new(vec![7; 32]));
+        assert_eq!(database.take_checkpoints().len(), 2); // ledger and audit log
+        assert!(database.verify_integrity().first_broken_link.is_none());
+    }
+}
@@ -0,0 +1,516 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::bomanis_bank::account::MoneyAccountID;
+use crate::bomanis_bank::currency::Currency;
+use crate::bomanis_bank::integrity::{self, BrokenLinkReason, EntryHash, GENESIS_HASH};
+
+// Type Aliases
+pub type TransactionID = u32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TransactionKind {
+    Deposit,
+    Withdrawal,
+    TransferIn,
+    TransferOut,
+    BillPayment,
+    Fee,
+    DepositCleared,
+    DepositReturned,
+    Reversal,
+    Interest,
+    Purchase,
+    Adjustment,
+}
+
+impl TransactionKind {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            TransactionKind::Deposit => "Deposit",
+            TransactionKind::Withdrawal => "Withdrawal",
+            TransactionKind::TransferIn => "Transfer in",
+            TransactionKind::TransferOut => "Transfer out",
+            TransactionKind::BillPayment => "Bill payment",
+            TransactionKind::Fee => "Fee",
+            TransactionKind::DepositCleared => "Deposit cleared",
+            TransactionKind::DepositReturned => "Deposit returned",
+            TransactionKind::Reversal => "Reversal",
+            TransactionKind::Interest => "Interest",
+            TransactionKind::Purchase => "Card purchase",
+            TransactionKind::Adjustment => "Manual adjustment",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ReasonCode {
+    CustomerRequest,
+    DuplicateEntry,
+    ProcessingError,
+    Fraud,
+    Refund,
+    PaymentRejected,
+    FeeWaived,
+}
+
+impl ReasonCode {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            ReasonCode::CustomerRequest => "Customer request",
+            ReasonCode::DuplicateEntry => "Duplicate entry",
+            ReasonCode::ProcessingError => "Processing error",
+            ReasonCode::Fraud => "Fraud",
+            ReasonCode::Refund => "Refund",
+            ReasonCode::PaymentRejected => "Payment rejected",
+            ReasonCode::FeeWaived => "Fee waived",
+        }
+    }
+}
+
+pub struct Transaction {
+    pub id: TransactionID,
+    pub money_account: MoneyAccountID,
+    pub kind: TransactionKind,
+    pub amount: i32, // positive credits the account, negative debits it
+    pub balance_after: i32,
+    pub posted_at: NaiveDateTime,
+    pub memo: String,
+    pub reverses: Option<TransactionID>, // original entry, set on reversals only
+    pub reason: Option<ReasonCode>,
+    pub reversed_amount: i32, // how much of this entry has been reversed so far
+    pub prev_hash: EntryHash,
+    pub hash: EntryHash, // over everything above apart from reversed_amount, which later reversals update
+}
+
+impl Transaction {
+    pub fn is_reversal(self: &Self) -> bool {
+        return self.reverses.is_some();
+    }
+
+    // What the hash covers.
+    pub fn content(self: &Self) -> String {
+        return format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.id, self.money_account, self.kind.description(), self.amount, self.balance_after,
+            self.posted_at.format("%Y-%m-%dT%H:%M:%S%.f"), integrity::text_field(&self.memo),
+            self.reverses.map(|id| id.to_string()).unwrap_or_default(),
+            self.reason.map(|reason| reason.description()).unwrap_or_default(),
+        );
+    }
+
+    pub fn reversible_amount(self: &Self) -> i32 {
+        if self.is_reversal() {
+            return 0;
+        }
+        return self.amount.abs() - self.reversed_amount;
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum IncomeKind {
+    FxSpread,
+    Fee,
+    LoanInterest,
+    CreditInterest,
+}
+
+impl IncomeKind {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            IncomeKind::FxSpread => "FX spread",
+            IncomeKind::Fee => "Fee",
+            IncomeKind::LoanInterest => "Loan interest",
+            IncomeKind::CreditInterest => "Credit interest",
+        }
+    }
+}
+
+// Money the bank itself earns, kept apart from customer entries.
+pub struct IncomeEntry {
+    pub kind: IncomeKind,
+    pub currency: Currency,
+    pub amount: i32,
+    pub posted_at: NaiveDateTime,
+    pub memo: String,
+    pub related_transaction: Option<TransactionID>,
+}
+
+// Append-only record of every posted balance movement.
+pub struct Ledger {
+    entries: Vec<Transaction>,
+    next_transaction_id: TransactionID,
+    income: Vec<IncomeEntry>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        return Ledger {
+            entries: vec![],
+            next_transaction_id: 1,
+            income: vec![],
+        }
+    }
+
+    pub fn record(self: &mut Self, money_account: MoneyAccountID, kind: TransactionKind, amount: i32, balance_after: i32, posted_at: NaiveDateTime, memo: String) -> TransactionID {
+        return self.append(Transaction {
+            id: 0,
+            money_account: money_account,
+            kind: kind,
+            amount: amount,
+            balance_after: balance_after,
+            posted_at: posted_at,
+            memo: memo,
+            reverses: None,
+            reason: None,
+            reversed_amount: 0,
+            prev_hash: GENESIS_HASH,
+            hash: GENESIS_HASH,
+        });
+    }
+
+    pub fn record_reversal(self: &mut Self, original_id: TransactionID, amount: i32, balance_after: i32, posted_at: NaiveDateTime, reason: ReasonCode) -> Option<TransactionID> {
+        let original = self.get_mut(original_id)?;
+        original.reversed_amount += amount.abs();
+        let money_account = original.money_account;
+        return Some(self.append(Transaction {
+            id: 0,
+            money_account: money_account,
+            kind: TransactionKind::Reversal,
+            amount: amount,
+            balance_after: balance_after,
+            posted_at: posted_at,
+            memo: format!("Reversal of #{} ({})", original_id, reason.description()),
+            reverses: Some(original_id),
+            reason: Some(reason),
+            reversed_amount: 0,
+            prev_hash: GENESIS_HASH,
+            hash: GENESIS_HASH,
+        }));
+    }
+
+    // Numbers the entry and links it to the end of the chain.
+    fn append(self: &mut Self, mut transaction: Transaction) -> TransactionID {
+        transaction.id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+        transaction.prev_hash = self.head_hash();
+        transaction.hash = integrity::chain_hash(&transaction.prev_hash, &transaction.content());
+        let id = transaction.id;
+        self.entries.push(transaction);
+        return id;
+    }
+
+    pub fn len(self: &Self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn head_hash(self: &Self) -> EntryHash {
+        return self.hash_after(self.entries.len()).unwrap_or(GENESIS_HASH);
+    }
+
+    // Hash of the chain after its first `entries` entries.
+    pub fn hash_after(self: &Self, entries: usize) -> Option<EntryHash> {
+        if entries == 0 {
+            return Some(GENESIS_HASH);
+        }
+        return self.entries.get(entries - 1).map(|txn| txn.hash);
+    }
+
+    // 1-based position of the first entry that has been tampered with.
+    pub fn first_broken_entry(self: &Self) -> Option<(usize, BrokenLinkReason)> {
+        let links: Vec<(EntryHash, EntryHash, EntryHash)> = self.entries.iter()
+            .map(|txn| (txn.prev_hash, txn.hash, integrity::chain_hash(&txn.prev_hash, &txn.content())))
+            .collect();
+        return integrity::first_broken_entry(&links);
+    }
+
+    // Balance after the latest entry for the account, if it has any.
+    pub fn last_balance(self: &Self, money_account: MoneyAccountID) -> Option<i32> {
+        return self.entries.iter().rev().find(|txn| txn.money_account == money_account).map(|txn| txn.balance_after);
+    }
+
+    pub fn get(self: &Self, id: TransactionID) -> Option<&Transaction> {
+        // ids are handed out in order, so the entry sits at id - 1
+        return self.entries.get((id as usize).checked_sub(1)?);
+    }
+
+    fn get_mut(self: &mut Self, id: TransactionID) -> Option<&mut Transaction> {
+        return self.entries.get_mut((id as usize).checked_sub(1)?);
+    }
+
+    pub fn history_for(self: &Self, money_account: MoneyAccountID) -> Vec<&Transaction> {
+        return self.entries.iter().filter(|txn| txn.money_account == money_account).collect();
+    }
+
+    pub fn reversals_of(self: &Self, id: TransactionID) -> Vec<&Transaction> {
+        return self.entries.iter().filter(|txn| txn.reverses == Some(id)).collect();
+    }
+
+    // Balance after the last entry posted at or before `at`.
+    pub fn balance_at(self: &Self, money_account: MoneyAccountID, at: NaiveDateTime) -> i32 {
+        return self.entries.iter()
+            .rev()
+            .find(|txn| txn.money_account == money_account && txn.posted_at <= at)
+            .map(|txn| txn.balance_after)
+            .unwrap_or(0);
+    }
+
+    // Average of end-of-day balances over [from, to).
+    pub fn average_daily_balance(self: &Self, money_account: MoneyAccountID, from: NaiveDate, to: NaiveDate) -> i32 {
+        let days = (to - from).num_days();
+        if days <= 0 {
+            return 0;
+        }
+        let mut total: i64 = 0;
+        let mut day = from;
+        while day < to {
+            let end_of_day = (day + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap() - Duration::nanoseconds(1);
+            total += self.balance_at(money_account, end_of_day) as i64;
+            day += Duration::days(1);
+        }
+        return (total / days) as i32;
+    }
+
+    pub fn record_income(self: &mut Self, kind: IncomeKind, currency: Currency, amount: i32, posted_at: NaiveDateTime, memo: String, related_transaction: Option<TransactionID>) {
+        self.income.push(IncomeEntry {
+            kind: kind,
+            currency: currency,
+            amount: amount,
+            posted_at: posted_at,
+            memo: memo,
+            related_transaction: related_transaction,
+        });
+    }
+
+    pub fn income_entries(self: &Self) -> &Vec<IncomeEntry> {
+        return &self.income;
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        return Ledger::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::{BankAccountID, MoneyAccountType};
+    use crate::bomanis_bank::audit::AuditAction;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::{FxPolicy, RateTable};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::ReversalError;
+    use crate::bomanis_bank::loans::{AmortizationMethod, LoanTerms};
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::{StaffID, StaffRole};
+    use crate::bomanis_bank::term_deposits::{InterestPayment, MaturityInstruction, TermDepositTerms};
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+    }
+
+    // A customer with a USD checking account, and a supervisor and a teller to correct entries on it.
+    fn bank_with_staff() -> (DatabaseHandler, BankAccountID, MoneyAccountID, StaffID, StaffID) {
+        let clock = Rc::new(ManualClock::new(at(2, 9)));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let supervisor = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        let teller = database.create_staff(admin, String::from("tess"), String::from("Tess"), SecretString::from(PASSWORD), StaffRole::Teller).unwrap();
+        return (database, owner, id, supervisor, teller);
+    }
+
+    fn open_account(database: &mut DatabaseHandler, owner: BankAccountID, currency: Currency) -> MoneyAccountID {
+        let ptr_bank_account = database.get_bank_account_with_id_u32(owner).unwrap();
+        return database.make_new_money_account(ptr_bank_account, format!("{} account", currency.code()), MoneyAccountType::Checking, currency).borrow().id;
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    fn last_entry(database: &DatabaseHandler, id: MoneyAccountID) -> TransactionID {
+        return database.get_transaction_history(id).last().unwrap().id;
+    }
+
+    #[test]
+    fn partial_reversals_add_up_to_the_original() {
+        let mut ledger = Ledger::new();
+        let debit = ledger.record(123456, TransactionKind::Withdrawal, -10000, 40000, at(2, 9), String::new());
+        let first = ledger.record_reversal(debit, 3000, 43000, at(2, 10), ReasonCode::ProcessingError).unwrap();
+        assert_eq!(ledger.get(debit).unwrap().reversible_amount(), 7000);
+        ledger.record_reversal(debit, 7000, 50000, at(2, 11), ReasonCode::Refund).unwrap();
+        assert_eq!(ledger.get(debit).unwrap().reversible_amount(), 0);
+
+        let reversal = ledger.get(first).unwrap();
+        assert!(reversal.is_reversal());
+        assert!(reversal.kind == TransactionKind::Reversal);
+        assert_eq!(reversal.reverses, Some(debit));
+        assert_eq!(reversal.reversible_amount(), 0);
+        assert_eq!(ledger.reversals_of(debit).iter().map(|txn| txn.amount).sum::<i32>(), 10000);
+    }
+
+    #[test]
+    fn reversing_a_missing_entry_records_nothing() {
+        let mut ledger = Ledger::new();
+        assert!(ledger.record_reversal(42, 100, 100, at(2, 9), ReasonCode::Fraud).is_none());
+        assert!(ledger.is_empty());
+    }
+
+    #[test]
+    fn balance_at_uses_the_last_entry_posted_by_then() {
+        let mut ledger = Ledger::new();
+        ledger.record(123456, TransactionKind::Deposit, 10000, 10000, at(2, 9), String::new());
+        ledger.record(654321, TransactionKind::Deposit, 99999, 99999, at(2, 12), String::new());
+        ledger.record(123456, TransactionKind::Withdrawal, -2500, 7500, at(3, 9), String::new());
+        assert_eq!(ledger.balance_at(123456, at(1, 9)), 0);
+        assert_eq!(ledger.balance_at(123456, at(2, 23)), 10000);
+        assert_eq!(ledger.balance_at(123456, at(3, 9)), 7500);
+        assert_eq!(ledger.last_balance(123456), Some(7500));
+        // a day at 10000, then two at 7500
+        assert_eq!(ledger.average_daily_balance(123456, at(2, 0).date(), at(5, 0).date()), 8333);
+    }
+
+    #[test]
+    fn tampering_is_found_at_the_changed_entry() {
+        let mut ledger = Ledger::new();
+        ledger.record(123456, TransactionKind::Deposit, 10000, 10000, at(2, 9), String::new());
+        ledger.record(123456, TransactionKind::Withdrawal, -2500, 7500, at(2, 10), String::new());
+        ledger.record(123456, TransactionKind::Deposit, 500, 8000, at(2, 11), String::new());
+        assert!(ledger.first_broken_entry().is_none());
+
+        ledger.entries[1].amount = -25;
+        assert!(matches!(ledger.first_broken_entry(), Some((2, BrokenLinkReason::ContentChanged))));
+        ledger.entries[1].amount = -2500;
+        assert!(ledger.first_broken_entry().is_none());
+
+        ledger.entries.remove(1);
+        assert!(matches!(ledger.first_broken_entry(), Some((2, BrokenLinkReason::ChainBroken))));
+    }
+
+    #[test]
+    fn reversals_restore_the_balance_and_stop_at_the_original_amount() {
+        let (mut database, _, id, supervisor, _) = bank_with_staff();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        database.withdraw(id, 10000, None).unwrap();
+        let withdrawal = last_entry(&database, id);
+
+        let partial = database.reverse_transaction(supervisor, withdrawal, Some(4000), ReasonCode::ProcessingError, None).unwrap();
+        assert_eq!(balance(&database, id), 44000);
+        assert_eq!(database.get_transaction(partial).unwrap().amount, 4000);
+        assert_eq!(database.reverse_transaction(supervisor, withdrawal, Some(6001), ReasonCode::Refund, None), Err(ReversalError::ExceedsOriginal));
+        assert_eq!(database.reverse_transaction(supervisor, withdrawal, Some(0), ReasonCode::Refund, None), Err(ReversalError::InvalidAmount));
+        database.reverse_transaction(supervisor, withdrawal, None, ReasonCode::Refund, None).unwrap();
+        assert_eq!(balance(&database, id), 50000);
+        assert_eq!(database.reverse_transaction(supervisor, withdrawal, None, ReasonCode::Refund, None), Err(ReversalError::AlreadyReversed));
+        assert_eq!(database.reverse_transaction(supervisor, partial, None, ReasonCode::Refund, None), Err(ReversalError::NotReversible));
+    }
+
+    #[test]
+    fn reversing_a_credit_debits_the_account() {
+        let (mut database, _, id, supervisor, _) = bank_with_staff();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        let deposit = last_entry(&database, id);
+        let reversal = database.reverse_transaction(supervisor, deposit, None, ReasonCode::DuplicateEntry, None).unwrap();
+        assert_eq!(database.get_transaction(reversal).unwrap().amount, -50000);
+        assert_eq!(balance(&database, id), 0);
+    }
+
+    #[test]
+    fn reversed_credit_respects_the_lock_and_the_balance() {
+        let (mut database, _, id, supervisor, _) = bank_with_staff();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        let deposit = last_entry(&database, id);
+        database.withdraw(id, 40000, None).unwrap();
+        assert_eq!(database.reverse_transaction(supervisor, deposit, None, ReasonCode::DuplicateEntry, None), Err(ReversalError::NotEnough));
+        assert_eq!(balance(&database, id), 10000);
+
+        database.set_account_locked(supervisor, id, true, "Investigation").unwrap();
+        assert_eq!(database.reverse_transaction(supervisor, deposit, Some(5000), ReasonCode::DuplicateEntry, None), Err(ReversalError::Locked));
+        assert_eq!(balance(&database, id), 10000);
+        assert!(database.get_reversals_of(deposit).is_empty());
+    }
+
+    #[test]
+    fn reversals_are_audited_and_need_the_permission() {
+        let (mut database, _, id, supervisor, teller) = bank_with_staff();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        let deposit = last_entry(&database, id);
+        assert_eq!(database.reverse_transaction(teller, deposit, None, ReasonCode::Fraud, None), Err(ReversalError::PermissionDenied));
+        assert_eq!(database.reverse_transaction(supervisor, 999, None, ReasonCode::Fraud, None), Err(ReversalError::NotFound));
+
+        let balance_changes = |database: &DatabaseHandler| database.get_audit_events(None, None, None).iter().filter(|event| event.action == AuditAction::BalanceChange).count();
+        let before = balance_changes(&database);
+        database.reverse_transaction(supervisor, deposit, None, ReasonCode::Fraud, None).unwrap();
+        assert_eq!(balance_changes(&database), before + 1);
+    }
+
+    #[test]
+    fn transfer_legs_are_not_reversed_one_at_a_time() {
+        let (mut database, owner, id, supervisor, _) = bank_with_staff();
+        let savings = open_account(&mut database, owner, Currency::Usd);
+        let euros = open_account(&mut database, owner, Currency::Eur);
+        database.set_rate_table(RateTable::parse("2026-03-01,USD,EUR,0.90").unwrap());
+        database.set_fx_policy(FxPolicy { spread: 0.01 });
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        database.transfer(id, savings, 10000, None).unwrap();
+        database.transfer(id, euros, 10000, None).unwrap();
+        let income = database.get_income_entries().len();
+
+        for account in [id, savings, euros] {
+            for entry in database.get_transaction_history(account).iter().map(|txn| txn.id).collect::<Vec<TransactionID>>() {
+                let kind = database.get_transaction(entry).unwrap().kind;
+                if kind == TransactionKind::TransferIn || kind == TransactionKind::TransferOut {
+                    assert_eq!(database.reverse_transaction(supervisor, entry, None, ReasonCode::ProcessingError, None), Err(ReversalError::NotReversible));
+                }
+            }
+        }
+        assert_eq!((balance(&database, id), balance(&database, savings), balance(&database, euros)), (30000, 10000, 8910));
+        assert_eq!(database.get_income_entries().len(), income);
+    }
+
+    #[test]
+    fn loan_and_term_deposit_entries_are_left_to_their_products() {
+        let (mut database, owner, id, supervisor, _) = bank_with_staff();
+        database.deposit(id, 500000, DepositChannel::Cash, None).unwrap();
+        let deposit = database.open_term_deposit(owner, id, String::from("Fixed"), TermDepositTerms {
+            principal: 200000,
+            term_months: 12,
+            annual_rate: 0.035,
+            interest_payment: InterestPayment::AtMaturity,
+            at_maturity: MaturityInstruction::PayOut,
+        }).unwrap();
+        let funding = last_entry(&database, id);
+        let loan = database.originate_loan(owner, id, String::from("Car"), LoanTerms { principal: 100000, annual_rate: 0.079, term_months: 12, method: AmortizationMethod::Annuity }).unwrap();
+        let disbursement = last_entry(&database, id);
+
+        for entry in [funding, disbursement, last_entry(&database, deposit), last_entry(&database, loan)] {
+            assert_eq!(database.reverse_transaction(supervisor, entry, None, ReasonCode::ProcessingError, None), Err(ReversalError::NotReversible));
+        }
+        assert_eq!(balance(&database, id), 400000);
+        assert_eq!(balance(&database, deposit), 200000);
+        assert_eq!(balance(&database, loan), -database.get_loan(loan).unwrap().outstanding_principal());
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::NaiveDate;
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::term_deposits::add_months;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum AmortizationMethod {
+    Annuity, // equal payments, the interest share shrinks over time
+    StraightLine, // equal principal, payments shrink over time
+}
+
+impl AmortizationMethod {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            AmortizationMethod::Annuity => "Annuity (equal payments)",
+            AmortizationMethod::StraightLine => "Straight-line (equal principal)",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum InstallmentStatus {
+    Scheduled,
+    Overdue,
+    Paid,
+    Cancelled, // the loan was paid off before this fell due
+}
+
+impl InstallmentStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            InstallmentStatus::Scheduled => "Scheduled",
+            InstallmentStatus::Overdue => "Overdue",
+            InstallmentStatus::Paid => "Paid",
+            InstallmentStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum LoanStatus {
+    Active,
+    PaidOff,
+}
+
+impl LoanStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            LoanStatus::Active => "Active",
+            LoanStatus::PaidOff => "Paid off",
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct LoanTerms {
+    pub principal: i32, // minor units of the disbursement account's currency
+    pub annual_rate: f64, // e.g. 0.07 for 7%
+    pub term_months: u32,
+    pub method: AmortizationMethod,
+}
+
+#[derive(Copy, Clone)]
+pub struct LoanPolicy {
+    pub min_principal: i32,
+    pub max_principal: i32,
+    pub min_term_months: u32,
+    pub max_term_months: u32,
+    pub offered_rate: f64, // annual rate offered to customers applying in the session
+    pub grace_days: u32, // days after the due date before an unpaid installment counts as late
+    pub penalty_rate: f64, // annual penalty interest on late installments, on top of the loan rate
+}
+
+impl Default for LoanPolicy {
+    fn default() -> Self {
+        return LoanPolicy {
+            min_principal: 50000,
+            max_principal: 5000000,
+            min_term_months: 3,
+            max_term_months: 84,
+            offered_rate: 0.079,
+            grace_days: 3,
+            penalty_rate: 0.05,
+        }
+    }
+}
+
+pub struct Installment {
+    pub number: u32,
+    pub due_date: NaiveDate,
+    pub principal: i32,
+    pub interest: i32,
+    pub balance_after: i32, // principal still owed once this is paid
+    pub status: InstallmentStatus,
+    pub paid_on: Option<NaiveDate>,
+    pub penalty_paid: i32,
+}
+
+impl Installment {
+    pub fn total(self: &Self) -> i32 {
+        return self.principal + self.interest;
+    }
+
+    pub fn is_open(self: &Self) -> bool {
+        return matches!(self.status, InstallmentStatus::Scheduled | InstallmentStatus::Overdue);
+    }
+
+    pub fn days_late(self: &Self, today: NaiveDate, policy: &LoanPolicy) -> i64 {
+        if !self.is_open() {
+            return 0;
+        }
+        return ((today - self.due_date).num_days() - policy.grace_days as i64).max(0);
+    }
+
+    // Penalty interest on the whole installment for every day past the grace period.
+    pub fn penalty_interest(self: &Self, today: NaiveDate, policy: &LoanPolicy) -> i32 {
+        let days_late = self.days_late(today, policy);
+        return (self.total() as f64 * policy.penalty_rate * days_late as f64 / 365.0).round() as i32;
+    }
+}
+
+// What paying a loan off in full today would cost.
+pub struct PayoffQuote {
+    pub principal: i32,
+    pub interest: i32, // overdue interest plus interest accrued since the last due date
+    pub penalty: i32,
+    pub total: i32,
+}
+
+pub struct Loan {
+    pub money_account: MoneyAccountID,
+    pub owner: BankAccountID,
+    pub repayment_account: MoneyAccountID, // checking account the loan was paid into and installments are collected from
+    pub terms: LoanTerms,
+    pub originated_on: NaiveDate,
+    pub installments: Vec<Installment>,
+    pub status: LoanStatus,
+}
+
+impl Loan {
+    pub fn outstanding_principal(self: &Self) -> i32 {
+        return self.installments.iter().filter(|installment| installment.is_open()).map(|installment| installment.principal).sum();
+    }
+
+    pub fn next_installment(self: &Self) -> Option<&Installment> {
+        return self.installments.iter().find(|installment| installment.is_open());
+    }
+
+    pub fn payoff_quote(self: &Self, today: NaiveDate, policy: &LoanPolicy) -> PayoffQuote {
+        let open: Vec<&Installment> = self.installments.iter().filter(|installment| installment.is_open()).collect();
+        let overdue_interest: i32 = open.iter().filter(|installment| installment.due_date <= today).map(|installment| installment.interest).sum();
+        let penalty: i32 = open.iter().map(|installment| installment.penalty_interest(today, policy)).sum();
+        // interest on what is still owed from the last due date that has passed
+        let period_start = self.installments.iter().rev()
+            .map(|installment| installment.due_date)
+            .find(|due_date| *due_date <= today)
+            .unwrap_or(self.originated_on);
+        let not_yet_due: i32 = open.iter().filter(|installment| installment.due_date > today).map(|installment| installment.principal).sum();
+        let days = (today - period_start).num_days().max(0);
+        let accrued_interest = (not_yet_due as f64 * self.terms.annual_rate * days as f64 / 365.0).round() as i32;
+        let principal = self.outstanding_principal();
+        return PayoffQuote {
+            principal: principal,
+            interest: overdue_interest + accrued_interest,
+            penalty: penalty,
+            total: principal + overdue_interest + accrued_interest + penalty,
+        };
+    }
+}
+
+// Monthly installments starting a month after `start`. Amounts are rounded to minor units and the
+// last installment absorbs the rounding so the principal is repaid exactly.
+pub fn amortization_schedule(terms: &LoanTerms, start: NaiveDate) -> Vec<Installment> {
+    let mut installments: Vec<Installment> = vec![];
+    let monthly_rate = terms.annual_rate / 12.0;
+    let months = terms.term_months;
+    let annuity_payment = if monthly_rate == 0.0 {
+        terms.principal as f64 / months as f64
+    } else {
+        terms.principal as f64 * monthly_rate / (1.0 - (1.0 + monthly_rate).powi(-(months as i32)))
+    };
+    let mut balance = terms.principal;
+    for number in 1..=months {
+        let interest = (balance as f64 * monthly_rate).round() as i32;
+        let principal = if number == months {
+            balance
+        } else {
+            match terms.method {
+                AmortizationMethod::Annuity => annuity_payment.round() as i32 - interest,
+                AmortizationMethod::StraightLine => (terms.principal as f64 / months as f64).round() as i32,
+            }
+        }.min(balance);
+        balance -= principal;
+        installments.push(Installment {
+            number: number,
+            due_date: add_months(start, number).unwrap_or(NaiveDate::MAX), // refused by originate_loan
+            principal: principal,
+            interest: interest,
+            balance_after: balance,
+            status: InstallmentStatus::Scheduled,
+            paid_on: None,
+            penalty_paid: 0,
+        });
+    }
+    return installments;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    fn terms(method: AmortizationMethod, annual_rate: f64) -> LoanTerms {
+        return LoanTerms { principal: 1000000, annual_rate: annual_rate, term_months: 12, method: method };
+    }
+
+    #[test]
+    fn annuity_payments_are_equal_and_repay_the_principal() {
+        let schedule = amortization_schedule(&terms(AmortizationMethod::Annuity, 0.12), date(2026, 1, 15));
+        assert_eq!(schedule.len(), 12);
+        // 10000.00 at 1% a month over 12 months is 888.49 a month
+        assert_eq!(schedule[0].interest, 10000);
+        assert_eq!(schedule[0].total(), 88849);
+        assert!(schedule[..11].iter().all(|installment| installment.total() == 88849));
+        assert!((schedule[11].total() - 88849).abs() <= 12);
+        assert!(schedule.windows(2).all(|pair| pair[1].interest < pair[0].interest));
+        assert_eq!(schedule.iter().map(|installment| installment.principal).sum::<i32>(), 1000000);
+        assert_eq!(schedule[11].balance_after, 0);
+    }
+
+    #[test]
+    fn straight_line_repays_equal_principal() {
+        let schedule = amortization_schedule(&terms(AmortizationMethod::StraightLine, 0.12), date(2026, 1, 15));
+        assert!(schedule[..11].iter().all(|installment| installment.principal == 83333));
+        assert_eq!(schedule[11].principal, 1000000 - 11 * 83333);
+        assert_eq!(schedule[0].interest, 10000);
+        assert_eq!(schedule[1].interest, 9167);
+        assert_eq!(schedule.iter().map(|installment| installment.principal).sum::<i32>(), 1000000);
+    }
+
+    #[test]
+    fn interest_free_loan_splits_the_principal() {
+        let schedule = amortization_schedule(&terms(AmortizationMethod::Annuity, 0.0), date(2026, 1, 15));
+        assert!(schedule.iter().all(|installment| installment.interest == 0));
+        assert_eq!(schedule.iter().map(|installment| installment.principal).sum::<i32>(), 1000000);
+    }
+
+    #[test]
+    fn installments_fall_due_monthly_from_the_start() {
+        let schedule = amortization_schedule(&terms(AmortizationMethod::Annuity, 0.12), date(2026, 1, 31));
+        assert_eq!(schedule[0].due_date, date(2026, 2, 28));
+        assert_eq!(schedule[1].due_date, date(2026, 3, 31));
+        assert_eq!(schedule[11].due_date, date(2027, 1, 31));
+    }
+
+    #[test]
+    fn penalty_starts_after_the_grace_period() {
+        let policy = LoanPolicy::default();
+        let installment = &amortization_schedule(&terms(AmortizationMethod::Annuity, 0.12), date(2026, 1, 15))[0];
+        assert_eq!(installment.days_late(date(2026, 2, 18), &policy), 0);
+        assert_eq!(installment.days_late(date(2026, 2, 28), &policy), 10);
+        // 888.49 at 5% a year for 10 days
+        assert_eq!(installment.penalty_interest(date(2026, 2, 28), &policy), 122);
+    }
+}
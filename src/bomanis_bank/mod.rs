@@ -7,4 +7,29 @@ pub use session::Session;
 pub mod database;
 pub use database::DatabaseHandler;
 
-pub mod errors;
\ No newline at end of file
+pub mod errors;
+
+pub mod clearing;
+pub mod ledger;
+pub mod idempotency;
+pub mod clock;
+pub mod scheduler;
+pub mod notifications;
+pub mod payments;
+pub mod currency;
+pub mod fees;
+pub mod term_deposits;
+pub mod loans;
+pub mod credit;
+pub mod onboarding;
+pub mod profile;
+pub mod passwords;
+pub mod totp;
+pub mod secret;
+pub mod audit;
+pub mod integrity;
+pub mod staff;
+pub mod approvals;
+pub mod business;
+pub mod statements;
+pub mod export;
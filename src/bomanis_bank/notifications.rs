@@ -0,0 +1,48 @@
+use chrono::NaiveDateTime;
+
+use crate::bomanis_bank::account::BankAccountID;
+
+pub struct Notification {
+    pub bank_account: BankAccountID,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+    pub read: bool,
+}
+
+// Messages for customers, shown the next time they are in a session.
+pub struct Notifications {
+    messages: Vec<Notification>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        return Notifications { messages: vec![] }
+    }
+
+    pub fn push(self: &mut Self, bank_account: BankAccountID, message: String, created_at: NaiveDateTime) {
+        self.messages.push(Notification {
+            bank_account: bank_account,
+            message: message,
+            created_at: created_at,
+            read: false,
+        });
+    }
+
+    // Returns unread messages for the account and marks them as read.
+    pub fn take_unread(self: &mut Self, bank_account: BankAccountID) -> Vec<String> {
+        let mut unread: Vec<String> = vec![];
+        for notification in self.messages.iter_mut() {
+            if notification.bank_account == bank_account && !notification.read {
+                notification.read = true;
+                unread.push(format!("[{}] {}", notification.created_at.format("%Y-%m-%d"), notification.message));
+            }
+        }
+        return unread;
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        return Notifications::new();
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+use chrono::{Datelike, NaiveDate};
+
+use crate::bomanis_bank::business::BusinessDetails;
+use crate::bomanis_bank::currency::{Currency, CurrencyAmounts};
+use crate::bomanis_bank::errors::OnboardingError;
+use crate::bomanis_bank::secret::SecretString;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum OnboardingStatus {
+    Pending, // identity not checked yet
+    PendingReview, // the verifier could not decide, staff have to look at it
+    Verified,
+    Rejected,
+}
+
+impl OnboardingStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            OnboardingStatus::Pending => "Pending verification",
+            OnboardingStatus::PendingReview => "Pending manual review",
+            OnboardingStatus::Verified => "Verified",
+            OnboardingStatus::Rejected => "Rejected",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ContactDetails {
+    pub email: String,
+    pub phone: String,
+    pub address: String,
+}
+
+// Everything a new customer gives us at registration.
+pub struct Application {
+    pub user_id: String,
+    pub password: SecretString,
+    pub name: String,
+    pub dob: NaiveDate,
+    pub contact: ContactDetails,
+    pub id_document: String, // passport or national ID number
+    pub business: Option<BusinessDetails>, // business customers only. `name` and `dob` are then the person opening it
+}
+
+#[derive(Copy, Clone)]
+pub struct OnboardingPolicy {
+    pub min_age_years: u32,
+    pub max_age_years: u32, // anything older is taken to be a typo
+}
+
+impl Default for OnboardingPolicy {
+    fn default() -> Self {
+        return OnboardingPolicy {
+            min_age_years: 18,
+            max_age_years: 120,
+        }
+    }
+}
+
+// Minors bank with a guardian watching over them until they reach the onboarding minimum age.
+#[derive(Copy, Clone)]
+pub struct GuardianPolicy {
+    pub approval_limit: CurrencyAmounts, // money leaving a child's account above this needs the guardian's approval
+}
+
+impl Default for GuardianPolicy {
+    fn default() -> Self {
+        return GuardianPolicy {
+            approval_limit: CurrencyAmounts::new([
+                (Currency::Usd, 5000),
+                (Currency::Eur, 5000),
+                (Currency::Gbp, 5000),
+                (Currency::Sek, 50000),
+                (Currency::Chf, 5000),
+                (Currency::Jpy, 7500),
+            ]),
+        }
+    }
+}
+
+pub enum VerificationOutcome {
+    Verified,
+    NeedsReview(String),
+    Rejected(String),
+}
+
+// Whoever checks that the person is who they say they are.
+pub trait IdentityVerifier {
+    fn verify(self: &mut Self, application: &Application) -> VerificationOutcome;
+}
+
+// Stand-in for a real identity provider: accepts every document apart from the ones it has
+// been told to refer or refuse.
+pub struct LocalIdentityVerifier {
+    pub review_documents: HashSet<String>,
+    pub rejected_documents: HashSet<String>,
+}
+
+impl LocalIdentityVerifier {
+    pub fn new() -> Self {
+        return LocalIdentityVerifier {
+            review_documents: HashSet::new(),
+            rejected_documents: HashSet::new(),
+        }
+    }
+}
+
+impl Default for LocalIdentityVerifier {
+    fn default() -> Self {
+        return LocalIdentityVerifier::new();
+    }
+}
+
+impl IdentityVerifier for LocalIdentityVerifier {
+    fn verify(self: &mut Self, application: &Application) -> VerificationOutcome {
+        if self.rejected_documents.contains(&application.id_document) {
+            return VerificationOutcome::Rejected(String::from("Identity document could not be verified"));
+        }
+        if self.review_documents.contains(&application.id_document) {
+            return VerificationOutcome::NeedsReview(String::from("Identity document needs a manual check"));
+        }
+        return VerificationOutcome::Verified;
+    }
+}
+
+// Whole years between `dob` and `today`.
+pub fn age_on(dob: NaiveDate, today: NaiveDate) -> i32 {
+    let mut age = today.year() - dob.year();
+    if (today.month(), today.day()) < (dob.month(), dob.day()) {
+        age -= 1;
+    }
+    return age;
+}
+
+pub fn check_date_of_birth(dob: NaiveDate, today: NaiveDate, policy: &OnboardingPolicy) -> Result<(), OnboardingError> {
+    if dob > today {
+        return Err(OnboardingError::FutureDateOfBirth);
+    }
+    let age = age_on(dob, today);
+    if age > policy.max_age_years as i32 {
+        return Err(OnboardingError::ImplausibleDateOfBirth);
+    }
+    if age < policy.min_age_years as i32 {
+        return Err(OnboardingError::TooYoung);
+    }
+    return Ok(());
+}
+
+// For accounts a guardian opens: the child has to be below the minimum age to open their own.
+pub fn check_minor_date_of_birth(dob: NaiveDate, today: NaiveDate, policy: &OnboardingPolicy) -> Result<(), OnboardingError> {
+    if dob > today {
+        return Err(OnboardingError::FutureDateOfBirth);
+    }
+    if age_on(dob, today) >= policy.min_age_years as i32 {
+        return Err(OnboardingError::NotAMinor);
+    }
+    return Ok(());
+}
+
+// Something@domain.tld, nothing stricter.
+pub fn is_valid_email(email: &str) -> bool {
+    let (local, domain) = match email.trim().split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    return !local.is_empty() && !email.contains(char::is_whitespace)
+        && domain.split('.').count() >= 2 && domain.split('.').all(|part| !part.is_empty());
+}
+
+// 7 to 15 digits, optionally with a leading + and spaces or dashes between groups.
+pub fn is_valid_phone(phone: &str) -> bool {
+    let phone = phone.trim();
+    let digits = phone.strip_prefix('+').unwrap_or(phone);
+    if !digits.chars().all(|c| c.is_ascii_digit() || c == ' ' || c == '-') {
+        return false;
+    }
+    let count = digits.chars().filter(|c| c.is_ascii_digit()).count();
+    return (7..=15).contains(&count);
+}
+
+pub fn check_contact_details(contact: &ContactDetails) -> Result<(), OnboardingError> {
+    if !is_valid_email(&contact.email) {
+        return Err(OnboardingError::InvalidEmail);
+    }
+    if !is_valid_phone(&contact.phone) {
+        return Err(OnboardingError::InvalidPhone);
+    }
+    if contact.address.trim().is_empty() {
+        return Err(OnboardingError::MissingAddress);
+    }
+    return Ok(());
+}
+
+// 6 to 20 letters and digits.
+pub fn is_valid_id_document(document: &str) -> bool {
+    return (6..=20).contains(&document.len()) && document.chars().all(|c| c.is_ascii_alphanumeric());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::Duration;
+    use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID, MoneyAccountType};
+    use crate::bomanis_bank::approvals::{ApprovalStatus, Reviewer};
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::{ApprovalError, BalanceError, PaymentError, ScheduleError};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::scheduler::Frequency;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::StaffRole;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    fn application(user_id: &str, dob: NaiveDate) -> Application {
+        return Application {
+            user_id: String::from(user_id),
+            password: SecretString::from("Correct-Horse-42"),
+            name: format!("Test {}", user_id),
+            dob: dob,
+            contact: ContactDetails { email: format!("{}@example.com", user_id), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: format!("{}1234", user_id.to_uppercase()),
+            business: None,
+        };
+    }
+
+    // Monday 2 March 2026, with an adult customer to act as guardian.
+    fn bank_with_guardian() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let guardian = database.register_customer(application("alice", date(1990, 6, 15))).unwrap().borrow().id;
+        return (clock, database, guardian);
+    }
+
+    // A child of `guardian` with one checking account in each of `currencies`.
+    fn open_minor(database: &mut DatabaseHandler, guardian: BankAccountID, user_id: &str, dob: NaiveDate, currencies: &[Currency]) -> (BankAccountID, Vec<MoneyAccountID>) {
+        let ptr_bank_account = database.register_minor(guardian, application(user_id, dob)).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let ids = currencies.iter()
+            .map(|currency| database.make_new_money_account(ptr_bank_account.clone(), format!("Pocket money {}", currency.code()), MoneyAccountType::Checking, *currency).borrow().id)
+            .collect();
+        return (owner, ids);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn email_needs_a_local_part_and_a_dotted_domain() {
+        assert!(is_valid_email("alice@example.com"));
+        assert!(is_valid_email("alice.smith@mail.example.co.uk"));
+        assert!(!is_valid_email("alice.example.com"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("alice@example"));
+        assert!(!is_valid_email("alice@example..com"));
+        assert!(!is_valid_email("alice smith@example.com"));
+    }
+
+    #[test]
+    fn phone_has_seven_to_fifteen_digits() {
+        assert!(is_valid_phone("+46701234567"));
+        assert!(is_valid_phone("070-123 45 67"));
+        assert!(is_valid_phone("1234567"));
+        assert!(is_valid_phone("+123456789012345"));
+        assert!(!is_valid_phone("123456"));
+        assert!(!is_valid_phone("1234567890123456"));
+        assert!(!is_valid_phone("+46 (70) 1234567"));
+        assert!(!is_valid_phone("46+701234567"));
+    }
+
+    #[test]
+    fn id_document_is_six_to_twenty_letters_and_digits() {
+        assert!(is_valid_id_document("ALICE1"));
+        assert!(is_valid_id_document("A1B2C3D4E5F6G7H8I9J0"));
+        assert!(!is_valid_id_document("ALIC1"));
+        assert!(!is_valid_id_document("A1B2C3D4E5F6G7H8I9J0K"));
+        assert!(!is_valid_id_document("ALICE-1234"));
+        assert!(!is_valid_id_document("ÅSA12345"));
+    }
+
+    #[test]
+    fn customers_can_open_an_account_from_their_eighteenth_birthday() {
+        let policy = OnboardingPolicy::default();
+        let today = date(2026, 3, 2);
+        assert!(check_date_of_birth(date(2008, 3, 2), today, &policy).is_ok());
+        assert_eq!(check_date_of_birth(date(2008, 3, 3), today, &policy), Err(OnboardingError::TooYoung));
+        assert_eq!(check_date_of_birth(today, today, &policy), Err(OnboardingError::TooYoung));
+        assert_eq!(check_date_of_birth(date(2026, 3, 3), today, &policy), Err(OnboardingError::FutureDateOfBirth));
+    }
+
+    #[test]
+    fn implausible_ages_are_refused() {
+        let policy = OnboardingPolicy::default();
+        let today = date(2026, 3, 2);
+        assert!(check_date_of_birth(date(1906, 3, 3), today, &policy).is_ok()); // 119
+        assert!(check_date_of_birth(date(1906, 3, 2), today, &policy).is_ok()); // 120 today
+        assert_eq!(check_date_of_birth(date(1905, 3, 2), today, &policy), Err(OnboardingError::ImplausibleDateOfBirth));
+    }
+
+    #[test]
+    fn leap_day_birthdays_come_of_age_on_the_first_of_march() {
+        let dob = date(2008, 2, 29);
+        assert_eq!(age_on(dob, date(2026, 2, 28)), 17);
+        assert_eq!(age_on(dob, date(2026, 3, 1)), 18);
+        assert_eq!(check_date_of_birth(dob, date(2026, 2, 28), &OnboardingPolicy::default()), Err(OnboardingError::TooYoung));
+    }
+
+    #[test]
+    fn minor_outflows_go_to_the_guardian_and_then_the_bank() {
+        let (_, mut database, guardian) = bank_with_guardian();
+        let (child, ids) = open_minor(&mut database, guardian, "tim", date(2014, 5, 1), &[Currency::Usd, Currency::Sek]);
+        let (usd, sek) = (ids[0], ids[1]);
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from("Correct-Horse-42")).unwrap();
+        let sam = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from("Correct-Horse-42"), StaffRole::Supervisor).unwrap();
+        database.deposit(usd, 2000000, DepositChannel::Cash, None).unwrap();
+        database.deposit(sek, 2000000, DepositChannel::Cash, None).unwrap();
+        let payee = database.add_payee(child, String::from("Shop"), "GB82 WEST 1234 5698 7654 32", String::new()).unwrap();
+        let today = date(2026, 3, 2);
+        database.take_notifications(guardian);
+
+        assert!(matches!(database.withdraw(usd, 6000, None), Err(BalanceError::GuardianApprovalRequired)));
+        assert!(matches!(database.pay_bill(child, usd, payee, 6000, None, None), Err(PaymentError::GuardianApprovalRequired)));
+        assert!(matches!(database.create_standing_order(child, usd, sek, 6000, Frequency::MonthlyOnDay(2), today), Err(ScheduleError::GuardianApprovalRequired)));
+        // the limit is set per currency
+        assert!(database.withdraw(sek, 6000, None).is_ok());
+
+        let payment = database.request_bill_payment(child, usd, payee, 6000, None).unwrap();
+        assert!(database.get_approval_request(payment).unwrap().reviewer == Reviewer::Guardian(guardian));
+        assert_eq!(database.take_notifications(guardian).len(), 1);
+        assert!(database.approve_guardian_request(guardian, payment).is_ok());
+        assert_eq!(database.get_outbound_payments(child).len(), 1);
+
+        // above the bank's threshold too: the guardian decides first, then the bank
+        let request = database.request_withdrawal(child, usd, 600000).unwrap();
+        assert!(database.get_approval_request(request).unwrap().reviewer == Reviewer::Guardian(guardian));
+        assert!(matches!(database.approve_request(sam, request), Err(ApprovalError::NotFound)));
+        assert!(database.approve_guardian_request(guardian, request).is_ok());
+        assert!(database.get_approval_request(request).unwrap().reviewer == Reviewer::Bank);
+        let before = balance(&database, usd);
+        assert!(database.approve_request(sam, request).is_ok());
+        assert_eq!(balance(&database, usd), before - 600000);
+    }
+
+    #[test]
+    fn minor_becomes_a_standard_customer_on_coming_of_age() {
+        let (clock, mut database, guardian) = bank_with_guardian();
+        // turns 18 the day after the clock starts
+        let (child, ids) = open_minor(&mut database, guardian, "tim", date(2008, 3, 3), &[Currency::Usd]);
+        let id = ids[0];
+        database.deposit(id, 100000, DepositChannel::Cash, None).unwrap();
+        let request = database.request_withdrawal(child, id, 6000).unwrap();
+
+        clock.advance(Duration::hours(14));
+        database.tick();
+        assert!(database.is_minor(child));
+        assert!(database.get_approval_request(request).unwrap().is_pending());
+
+        clock.advance(Duration::hours(2));
+        database.tick();
+        assert!(!database.is_minor(child));
+        assert!(database.get_wards(guardian).is_empty());
+        assert!(database.get_approval_request(request).unwrap().status == ApprovalStatus::Expired);
+        assert!(database.take_notifications(child).iter().any(|message| message.contains("standard account")));
+        assert!(database.take_notifications(guardian).iter().any(|message| message.contains("has come of age")));
+        assert!(matches!(database.approve_guardian_request(guardian, request), Err(ApprovalError::NotPending)));
+        assert!(database.withdraw(id, 6000, None).is_ok());
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::bomanis_bank::clock::RandomSource;
+use crate::bomanis_bank::secret::SecretString;
+
+#[derive(Copy, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub history_size: usize, // how many previous passwords, the current one included, cannot be reused
+    pub max_age_days: Option<i64>, // passwords older than this have to be changed at the next login
+    pub hash_iterations: u32, // PBKDF2 rounds for newly set passwords
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        return PasswordPolicy {
+            min_length: 10,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: false,
+            history_size: 5,
+            max_age_days: Some(90),
+            hash_iterations: 600_000,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PasswordRule {
+    MinLength,
+    Uppercase,
+    Lowercase,
+    Digit,
+    Symbol,
+    NotCommon,
+    NotReused,
+}
+
+impl PasswordRule {
+    pub fn description(self: &Self, policy: &PasswordPolicy) -> String {
+        match self {
+            PasswordRule::MinLength => format!("must be at least {} characters long", policy.min_length),
+            PasswordRule::Uppercase => String::from("must contain an uppercase letter"),
+            PasswordRule::Lowercase => String::from("must contain a lowercase letter"),
+            PasswordRule::Digit => String::from("must contain a digit"),
+            PasswordRule::Symbol => String::from("must contain a symbol"),
+            PasswordRule::NotCommon => String::from("must not be a common or breached password"),
+            PasswordRule::NotReused => format!("must not be one of your last {} passwords", policy.history_size),
+        }
+    }
+}
+
+// A password as it is stored: PBKDF2-HMAC-SHA256 over a random salt. Each hash keeps its own
+// iteration count, so raising the policy's count only affects passwords set from then on.
+#[derive(Clone)]
+pub struct PasswordHash {
+    salt: [u8; 16],
+    iterations: u32,
+    hash: [u8; 32],
+}
+
+impl PasswordHash {
+    pub fn new(password: &SecretString, iterations: u32, rng: &mut dyn RandomSource) -> Self {
+        let mut salt = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        let iterations = iterations.max(1);
+        return PasswordHash { salt: salt, iterations: iterations, hash: derive(password.expose(), &salt, iterations) };
+    }
+
+    // Compares in constant time, so how long it takes says nothing about how close the guess was.
+    pub fn verify(self: &Self, password: &str) -> bool {
+        let candidate = derive(password, &self.salt, self.iterations);
+        return bool::from(candidate.ct_eq(&self.hash));
+    }
+}
+
+fn derive(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut hash);
+    return hash;
+}
+
+// Passwords nobody may use, e.g. from a breach corpus. One per line, # starts a comment.
+pub struct CommonPasswordList {
+    passwords: HashSet<String>,
+}
+
+impl CommonPasswordList {
+    pub fn new() -> Self {
+        return CommonPasswordList { passwords: HashSet::new() }
+    }
+
+    pub fn load_from_file(path: &Path) -> std::io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        return Ok(CommonPasswordList::parse(&contents));
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let passwords = contents.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.to_lowercase())
+            .collect();
+        return CommonPasswordList { passwords: passwords };
+    }
+
+    pub fn contains(self: &Self, password: &str) -> bool {
+        return self.passwords.contains(&password.to_lowercase());
+    }
+
+    pub fn len(self: &Self) -> usize {
+        return self.passwords.len();
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        return self.passwords.is_empty();
+    }
+}
+
+impl Default for CommonPasswordList {
+    fn default() -> Self {
+        return CommonPasswordList::new();
+    }
+}
+
+// Every rule the password breaks, in the order they are listed to the customer. Empty means it passes.
+pub fn check_password(password: &str, policy: &PasswordPolicy, common_passwords: &CommonPasswordList, history: &[PasswordHash]) -> Vec<PasswordRule> {
+    let mut failed: Vec<PasswordRule> = vec![];
+    if password.chars().count() < policy.min_length {
+        failed.push(PasswordRule::MinLength);
+    }
+    if policy.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+        failed.push(PasswordRule::Uppercase);
+    }
+    if policy.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+        failed.push(PasswordRule::Lowercase);
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        failed.push(PasswordRule::Digit);
+    }
+    if policy.require_symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        failed.push(PasswordRule::Symbol);
+    }
+    if common_passwords.contains(password) {
+        failed.push(PasswordRule::NotCommon);
+    }
+    if history.iter().rev().take(policy.history_size).any(|old| old.verify(password)) {
+        failed.push(PasswordRule::NotReused);
+    }
+    return failed;
+}
+
+// Issued by staff on a reset: 12 characters with at least one of each class, nothing ambiguous.
+pub fn generate_temporary_password(rng: &mut dyn RandomSource) -> SecretString {
+    const CLASSES: [&[u8]; 3] = [b"ABCDEFGHJKLMNPQRSTUVWXYZ", b"abcdefghjkmnpqrstuvwxyz", b"23456789"];
+    let mut password = String::new();
+    for i in 0..12 {
+        let class = CLASSES[if i < CLASSES.len() { i } else { rng.gen_range(0, CLASSES.len() as u32) as usize }];
+        password.push(class[rng.gen_range(0, class.len() as u32) as usize] as char);
+    }
+    return SecretString::new(password);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bomanis_bank::clock::SeededRandom;
+
+    #[test]
+    fn hash_verifies_only_the_original_password() {
+        let mut rng = SeededRandom::new(7);
+        let hash = PasswordHash::new(&SecretString::from("Correct-Horse-42"), 1_000, &mut rng);
+        assert!(hash.verify("Correct-Horse-42"));
+        assert!(!hash.verify("correct-horse-42"));
+        assert!(!hash.verify(""));
+    }
+
+    #[test]
+    fn same_password_gets_a_different_salt() {
+        let mut rng = SeededRandom::new(7);
+        let first = PasswordHash::new(&SecretString::from("Correct-Horse-42"), 1_000, &mut rng);
+        let second = PasswordHash::new(&SecretString::from("Correct-Horse-42"), 1_000, &mut rng);
+        assert!(first.salt != second.salt);
+        assert!(first.hash != second.hash);
+        assert!(second.verify("Correct-Horse-42"));
+    }
+
+    #[test]
+    fn reused_password_is_caught_through_its_hash() {
+        let mut rng = SeededRandom::new(7);
+        let policy = PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() };
+        let history = vec![PasswordHash::new(&SecretString::from("Correct-Horse-42"), policy.hash_iterations, &mut rng)];
+        let failed = check_password("Correct-Horse-42", &policy, &CommonPasswordList::new(), &history);
+        assert!(failed == vec![PasswordRule::NotReused]);
+        assert!(check_password("Battery-Staple-7", &policy, &CommonPasswordList::new(), &history).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use chrono::NaiveDateTime;
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::currency::Currency;
+use crate::bomanis_bank::ledger::TransactionID;
+
+// Type Aliases
+pub type PayeeID = u32;
+pub type PaymentID = u32;
+
+#[derive(Clone)]
+pub struct Payee {
+    pub id: PayeeID,
+    pub name: String,
+    pub external_account: String, // IBAN or domestic account number
+    pub reference: String, // default reference shown on the payee's statement
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PaymentStatus {
+    Pending,
+    Sent,
+    Rejected,
+}
+
+impl PaymentStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "Pending",
+            PaymentStatus::Sent => "Sent",
+            PaymentStatus::Rejected => "Rejected",
+        }
+    }
+}
+
+pub struct OutboundPayment {
+    pub id: PaymentID,
+    pub owner: BankAccountID,
+    pub from: MoneyAccountID,
+    pub payee: Payee, // copied, so later edits to the payee book do not change history
+    pub amount: i32,
+    pub currency: Currency,
+    pub reference: String,
+    pub status: PaymentStatus,
+    pub created_at: NaiveDateTime,
+    pub debit_transaction: TransactionID,
+    pub rejection_reason: Option<String>,
+}
+
+pub enum NetworkResponse {
+    Acknowledged,
+    Rejected(String),
+}
+
+// Whoever settles outbound payments with other banks.
+pub trait PaymentNetwork {
+    fn submit(self: &mut Self, payment: &OutboundPayment) -> NetworkResponse;
+}
+
+// Stand-in for a real clearing system: accepts everything apart from accounts it has been
+// told to refuse.
+pub struct LocalPaymentNetwork {
+    pub rejected_accounts: HashSet<String>,
+}
+
+impl LocalPaymentNetwork {
+    pub fn new() -> Self {
+        return LocalPaymentNetwork { rejected_accounts: HashSet::new() }
+    }
+}
+
+impl Default for LocalPaymentNetwork {
+    fn default() -> Self {
+        return LocalPaymentNetwork::new();
+    }
+}
+
+impl PaymentNetwork for LocalPaymentNetwork {
+    fn submit(self: &mut Self, payment: &OutboundPayment) -> NetworkResponse {
+        if self.rejected_accounts.contains(&payment.payee.external_account) {
+            return NetworkResponse::Rejected(String::from("Beneficiary account closed"));
+        }
+        return NetworkResponse::Acknowledged;
+    }
+}
+
+// Strips spaces and upper-cases, the form external accounts are stored in.
+pub fn normalize_external_account(account: &str) -> String {
+    return account.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+}
+
+// Accepts an IBAN with a valid check digit, or a plain 6 to 17 digit account number.
+pub fn is_valid_external_account(account: &str) -> bool {
+    let account = normalize_external_account(account);
+    if !account.is_empty() && account.chars().all(|c| c.is_ascii_digit()) {
+        return (6..=17).contains(&account.len());
+    }
+    return is_valid_iban(&account);
+}
+
+pub fn is_valid_iban(iban: &str) -> bool {
+    if iban.len() < 15 || iban.len() > 34 || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let (country, rest) = iban.split_at(2);
+    if !country.chars().all(|c| c.is_ascii_uppercase()) || !rest[..2].chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    // move the first four characters to the end, turn letters into numbers and check mod 97
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    let mut remainder: u32 = 0;
+    for c in rearranged.chars() {
+        let value = c.to_digit(36).unwrap();
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    return remainder == 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::NaiveDate;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::{PaymentError, ReversalError};
+    use crate::bomanis_bank::ledger::ReasonCode;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+    use crate::bomanis_bank::staff::StaffRole;
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn bank_with_account() -> (DatabaseHandler, BankAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (database, owner, id);
+    }
+
+    #[test]
+    fn iban_check_digits_are_verified() {
+        assert!(is_valid_iban("GB82WEST12345698765432"));
+        assert!(is_valid_iban("DE89370400440532013000"));
+        assert!(is_valid_iban("SE4550000000058398257466"));
+        assert!(!is_valid_iban("GB82WEST12345698765433")); // last digit changed
+        assert!(!is_valid_iban("GB28WEST12345698765432")); // check digits swapped
+    }
+
+    #[test]
+    fn malformed_ibans_are_refused() {
+        assert!(!is_valid_iban("GB82WEST1234")); // too short
+        assert!(!is_valid_iban("gb82WEST12345698765432"));
+        assert!(!is_valid_iban("GBX2WEST12345698765432"));
+        assert!(!is_valid_iban("GB82-WEST-1234-5698-7654-32"));
+    }
+
+    #[test]
+    fn external_accounts_are_normalized_first() {
+        assert_eq!(normalize_external_account(" gb82 west 1234 5698 7654 32 "), "GB82WEST12345698765432");
+        assert!(is_valid_external_account("gb82 west 1234 5698 7654 32"));
+        assert!(is_valid_external_account("12345678"));
+        assert!(!is_valid_external_account("12345"));
+        assert!(!is_valid_external_account("123456789012345678"));
+    }
+
+    #[test]
+    fn pending_payment_debit_cannot_be_reversed_by_hand() {
+        let (mut database, owner, id) = bank_with_account();
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        let supervisor = database.create_staff(admin, String::from("sam"), String::from("Sam"), SecretString::from(PASSWORD), StaffRole::Supervisor).unwrap();
+        let mut network = LocalPaymentNetwork::new();
+        network.rejected_accounts.insert(String::from("GB82WEST12345698765432"));
+        database.set_payment_network(Box::new(network));
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        let payee = database.add_payee(owner, String::from("Closed Ltd"), "GB82 WEST 1234 5698 7654 32", String::new()).unwrap();
+        let payment = database.pay_bill(owner, id, payee, 10000, None, None).unwrap();
+        let debit = database.get_outbound_payments(owner)[0].debit_transaction;
+
+        assert_eq!(database.reverse_transaction(supervisor, debit, None, ReasonCode::Refund, None), Err(ReversalError::PaymentPending));
+        let returned_payment_fee = database.fee_schedule_for(id).unwrap().returned_payment_fee("").map_or(0, |fee| fee.amount);
+        database.settle_outbound_payments();
+        assert!(database.get_outbound_payments(owner)[0].status == PaymentStatus::Rejected);
+        assert_eq!(database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance(), 50000 - returned_payment_fee);
+        assert_eq!(database.reject_outbound_payment(payment, String::from("again")), Err(PaymentError::NotPending));
+        assert_eq!(database.reverse_transaction(supervisor, debit, None, ReasonCode::Refund, None), Err(ReversalError::AlreadyReversed));
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::NaiveDateTime;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ProfileField {
+    Name,
+    Email,
+    Phone,
+    Address,
+    UserId,
+    Password,
+    TwoFactor,
+}
+
+impl ProfileField {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            ProfileField::Name => "Name",
+            ProfileField::Email => "Email",
+            ProfileField::Phone => "Phone",
+            ProfileField::Address => "Address",
+            ProfileField::UserId => "User ID",
+            ProfileField::Password => "Password",
+            ProfileField::TwoFactor => "Two-factor authentication",
+        }
+    }
+}
+
+// One entry in a customer's profile audit trail. Password changes keep no values.
+#[derive(Clone)]
+pub struct ProfileChange {
+    pub field: ProfileField,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::NaiveDate;
+    use crate::bomanis_bank::account::BankAccountID;
+    use crate::bomanis_bank::audit::AuditAction;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::ProfileError;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn contact(email: &str) -> ContactDetails {
+        return ContactDetails { email: String::from(email), phone: String::from("+46701234567"), address: String::from("1 Test Street") };
+    }
+
+    fn register(database: &mut DatabaseHandler, user_id: &str) -> BankAccountID {
+        return database.register_customer(Application {
+            user_id: String::from(user_id),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: contact(&format!("{}@example.com", user_id)),
+            id_document: format!("{}1234", user_id.to_uppercase()),
+            business: None,
+        }).unwrap().borrow().id;
+    }
+
+    fn bank() -> DatabaseHandler {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock, Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        return database;
+    }
+
+    fn profile_audit_details(database: &DatabaseHandler, owner: BankAccountID) -> Vec<String> {
+        return database.get_audit_events(Some(owner), None, None).into_iter()
+            .filter(|event| event.action == AuditAction::ProfileChange)
+            .map(|event| event.detail)
+            .collect();
+    }
+
+    #[test]
+    fn name_change_is_trimmed_and_kept_in_the_history() {
+        let mut database = bank();
+        let owner = register(&mut database, "alice");
+        assert_eq!(database.update_name(owner, String::from("   ")), Err(ProfileError::MissingName));
+        database.update_name(owner, String::from(" Alice Smith ")).unwrap();
+        database.update_name(owner, String::from("Alice Smith")).unwrap(); // unchanged, not logged
+
+        let history = database.get_profile_history(owner);
+        assert_eq!(history.len(), 1);
+        assert!(history[0].field == ProfileField::Name);
+        assert_eq!((history[0].old_value.as_deref(), history[0].new_value.as_deref()), (Some("Alice Example"), Some("Alice Smith")));
+        assert_eq!(database.get_bank_account_with_id_u32(owner).unwrap().borrow().name, "Alice Smith");
+        assert_eq!(profile_audit_details(&database, owner), vec![String::from("Name changed")]);
+    }
+
+    #[test]
+    fn only_changed_contact_details_are_logged_and_values_stay_out_of_the_audit_log() {
+        let mut database = bank();
+        let owner = register(&mut database, "alice");
+        assert_eq!(database.update_contact_details(owner, contact("not an email")), Err(ProfileError::InvalidEmail));
+        assert_eq!(database.update_contact_details(owner, ContactDetails { phone: String::from("12"), ..contact("alice@example.com") }), Err(ProfileError::InvalidPhone));
+        assert_eq!(database.update_contact_details(owner, ContactDetails { address: String::new(), ..contact("alice@example.com") }), Err(ProfileError::MissingAddress));
+        assert!(database.get_profile_history(owner).is_empty());
+
+        database.update_contact_details(owner, contact("alice@example.org")).unwrap();
+        let history = database.get_profile_history(owner);
+        assert_eq!(history.len(), 1);
+        assert!(history[0].field == ProfileField::Email);
+        assert_eq!(history[0].new_value.as_deref(), Some("alice@example.org"));
+        let details = profile_audit_details(&database, owner);
+        assert_eq!(details, vec![String::from("Email changed")]);
+        assert!(details.iter().all(|detail| !detail.contains("example.org")));
+    }
+
+    #[test]
+    fn user_id_must_be_free_and_without_spaces() {
+        let mut database = bank();
+        let alice = register(&mut database, "alice");
+        let bob = register(&mut database, "bob");
+        assert_eq!(database.change_user_id(bob, String::from("alice")), Err(ProfileError::UserIdTaken));
+        assert_eq!(database.change_user_id(alice, String::from("alice smith")), Err(ProfileError::InvalidUserId));
+        assert_eq!(database.change_user_id(alice, String::new()), Err(ProfileError::InvalidUserId));
+
+        database.change_user_id(alice, String::from("alice.smith")).unwrap();
+        assert!(database.user_id_in_use("alice.smith") && !database.user_id_in_use("alice"));
+        let history = database.get_profile_history(alice);
+        assert!(history.len() == 1 && history[0].field == ProfileField::UserId);
+        assert_eq!((history[0].old_value.as_deref(), history[0].new_value.as_deref()), (Some("alice"), Some("alice.smith")));
+        assert_eq!(profile_audit_details(&database, alice), vec![String::from("User ID changed")]);
+        database.change_user_id(bob, String::from("alice")).unwrap(); // free again
+        assert!(database.get_profile_history(bob).len() == 1);
+    }
+}
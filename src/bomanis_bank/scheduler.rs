@@ -0,0 +1,291 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::clearing;
+
+// Type Aliases
+pub type StandingOrderID = u32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Frequency {
+    Once,
+    Weekly,
+    MonthlyOnDay(u32),
+    LastBusinessDay,
+}
+
+impl Frequency {
+    pub fn description(self: &Self) -> String {
+        match self {
+            Frequency::Once => String::from("One-off"),
+            Frequency::Weekly => String::from("Weekly"),
+            Frequency::MonthlyOnDay(day) => format!("Monthly on day {}", day),
+            Frequency::LastBusinessDay => String::from("Monthly on the last business day"),
+        }
+    }
+
+    // First run on or after `start`.
+    pub fn first_run_from(self: &Self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Frequency::Once | Frequency::Weekly => start,
+            Frequency::MonthlyOnDay(day) => {
+                let this_month = day_in_month(start.year(), start.month(), *day);
+                if this_month >= start { this_month } else { self.next_run_after(this_month) }
+            },
+            Frequency::LastBusinessDay => {
+                let this_month = last_business_day(start.year(), start.month());
+                if this_month >= start { this_month } else { self.next_run_after(this_month) }
+            },
+        }
+    }
+
+    // Next run following one on `previous`. One-off orders never repeat, so they stay put.
+    pub fn next_run_after(self: &Self, previous: NaiveDate) -> NaiveDate {
+        let (next_year, next_month) = if previous.month() == 12 {
+            (previous.year() + 1, 1)
+        } else {
+            (previous.year(), previous.month() + 1)
+        };
+        match self {
+            Frequency::Once => previous,
+            Frequency::Weekly => previous + Duration::weeks(1),
+            Frequency::MonthlyOnDay(day) => day_in_month(next_year, next_month, *day),
+            Frequency::LastBusinessDay => last_business_day(next_year, next_month),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum OrderStatus {
+    Active,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            OrderStatus::Active => "Active",
+            OrderStatus::Completed => "Completed",
+            OrderStatus::Failed => "Failed",
+            OrderStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct SchedulerPolicy {
+    pub max_retries: u32, // extra attempts after insufficient funds, one per day
+}
+
+impl Default for SchedulerPolicy {
+    fn default() -> Self {
+        return SchedulerPolicy { max_retries: 2 }
+    }
+}
+
+pub struct StandingOrder {
+    pub id: StandingOrderID,
+    pub owner: BankAccountID,
+    pub from: MoneyAccountID,
+    pub to: MoneyAccountID,
+    pub amount: i32,
+    pub frequency: Frequency,
+    pub next_run: NaiveDate, // scheduled date of the upcoming occurrence
+    pub retry_on: Option<NaiveDate>, // set while an occurrence is waiting on funds
+    pub failed_attempts: u32,
+    pub status: OrderStatus,
+}
+
+impl StandingOrder {
+    pub fn due_date(self: &Self) -> NaiveDate {
+        return self.retry_on.unwrap_or(self.next_run);
+    }
+
+    pub fn is_due(self: &Self, today: NaiveDate) -> bool {
+        return self.status == OrderStatus::Active && self.due_date() <= today;
+    }
+
+    // Moves past the current occurrence, whether it ran or was given up on.
+    pub fn advance(self: &mut Self) {
+        self.retry_on = None;
+        self.failed_attempts = 0;
+        if self.frequency == Frequency::Once {
+            self.status = OrderStatus::Completed;
+        } else {
+            self.next_run = self.frequency.next_run_after(self.next_run);
+        }
+    }
+}
+
+// Clamps to the end of shorter months, so day 31 runs on the 30th in April.
+fn day_in_month(year: i32, month: u32, day: u32) -> NaiveDate {
+    let last = last_day_of_month(year, month);
+    return NaiveDate::from_ymd_opt(year, month, day.clamp(1, last.day())).unwrap();
+}
+
+pub fn last_day_of_month(year: i32, month: u32) -> NaiveDate {
+    let first_of_next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    return first_of_next.unwrap().pred_opt().unwrap();
+}
+
+pub fn last_business_day(year: i32, month: u32) -> NaiveDate {
+    let mut date = last_day_of_month(year, month);
+    while !clearing::is_business_day(date) {
+        date = date.pred_opt().unwrap();
+    }
+    return date;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::fees::WithdrawalLimit;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // A customer with two USD accounts, on Monday 2 March 2026.
+    fn bank_with_accounts() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID, MoneyAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: date(1990, 6, 15),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let from = database.make_new_money_account(ptr_bank_account.clone(), String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        let to = database.make_new_money_account(ptr_bank_account, String::from("Rent"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (clock, database, owner, from, to);
+    }
+
+    // A savings account that has used up its one withdrawal this cycle, and a checking account
+    // to pay into.
+    fn savings_at_limit() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID, MoneyAccountID, MoneyAccountID) {
+        let (clock, mut database, owner, checking, _) = bank_with_accounts();
+        let ptr_bank_account = database.get_bank_account_with_id_u32(owner).unwrap();
+        let savings = database.make_new_money_account(ptr_bank_account, String::from("Rainy day"), MoneyAccountType::Savings, Currency::Usd).borrow().id;
+        database.set_savings_withdrawal_limit(WithdrawalLimit { max_per_cycle: 1, excess_fee: None });
+        database.deposit(savings, 50000, DepositChannel::Cash, None).unwrap();
+        database.transfer(savings, checking, 1000, None).unwrap();
+        return (clock, database, owner, savings, checking);
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn weekly_runs_every_seven_days() {
+        let start = date(2026, 3, 2);
+        assert_eq!(Frequency::Weekly.first_run_from(start), start);
+        assert_eq!(Frequency::Weekly.next_run_after(start), date(2026, 3, 9));
+        assert_eq!(Frequency::Weekly.next_run_after(date(2026, 12, 28)), date(2027, 1, 4));
+    }
+
+    #[test]
+    fn monthly_day_is_clamped_to_short_months_and_restored_after() {
+        let frequency = Frequency::MonthlyOnDay(31);
+        let first = frequency.first_run_from(date(2026, 1, 15));
+        assert_eq!(first, date(2026, 1, 31));
+        let february = frequency.next_run_after(first);
+        assert_eq!(february, date(2026, 2, 28));
+        let march = frequency.next_run_after(february);
+        assert_eq!(march, date(2026, 3, 31));
+        assert_eq!(frequency.next_run_after(march), date(2026, 4, 30));
+        assert_eq!(Frequency::MonthlyOnDay(10).first_run_from(date(2026, 3, 11)), date(2026, 4, 10));
+    }
+
+    #[test]
+    fn last_business_day_skips_the_weekend() {
+        assert_eq!(last_business_day(2026, 3), date(2026, 3, 31)); // Tuesday
+        assert_eq!(last_business_day(2026, 5), date(2026, 5, 29)); // the 31st is a Sunday
+        assert_eq!(Frequency::LastBusinessDay.first_run_from(date(2026, 5, 30)), date(2026, 6, 30));
+        assert_eq!(Frequency::LastBusinessDay.next_run_after(date(2026, 12, 31)), date(2027, 1, 29));
+    }
+
+    #[test]
+    fn order_short_of_funds_is_retried_daily_then_skipped() {
+        let (clock, mut database, owner, from, to) = bank_with_accounts();
+        let id = database.create_standing_order(owner, from, to, 5000, Frequency::MonthlyOnDay(2), date(2026, 3, 2)).unwrap();
+        for attempt in 1..=2 {
+            database.run_standing_orders();
+            let order = database.get_standing_orders(owner)[0];
+            assert_eq!((order.id, order.failed_attempts, order.retry_on), (id, attempt, Some(date(2026, 3, 2 + attempt))));
+            assert!(database.take_notifications(owner).iter().any(|message| message.contains("try again")));
+            clock.advance(Duration::days(1));
+        }
+
+        database.run_standing_orders();
+        let order = database.get_standing_orders(owner)[0];
+        assert!(order.status == OrderStatus::Active);
+        assert_eq!((order.failed_attempts, order.retry_on, order.next_run), (0, None, date(2026, 4, 2)));
+        assert!(database.take_notifications(owner).iter().any(|message| message.contains("failed")));
+    }
+
+    #[test]
+    fn retry_goes_through_once_funds_arrive() {
+        let (clock, mut database, owner, from, to) = bank_with_accounts();
+        database.create_standing_order(owner, from, to, 5000, Frequency::Weekly, date(2026, 3, 2)).unwrap();
+        database.run_standing_orders();
+        assert_eq!(database.get_standing_orders(owner)[0].failed_attempts, 1);
+
+        database.deposit(from, 8000, DepositChannel::Cash, None).unwrap();
+        clock.advance(Duration::days(1));
+        database.run_standing_orders();
+        let order = database.get_standing_orders(owner)[0];
+        assert_eq!((order.failed_attempts, order.retry_on, order.next_run), (0, None, date(2026, 3, 9)));
+        assert_eq!((balance(&database, from), balance(&database, to)), (3000, 5000));
+    }
+
+    #[test]
+    fn one_off_order_over_the_withdrawal_limit_waits_for_the_next_cycle() {
+        let (clock, mut database, owner, savings, checking) = savings_at_limit();
+        database.create_standing_order(owner, savings, checking, 5000, Frequency::Once, date(2026, 3, 2)).unwrap();
+
+        database.run_standing_orders();
+        let first_of_april = date(2026, 4, 1);
+        let order = database.get_standing_orders(owner)[0];
+        assert!(order.status == OrderStatus::Active);
+        assert_eq!(order.retry_on, Some(first_of_april));
+        assert_eq!(balance(&database, savings), 49000);
+
+        clock.set(first_of_april.and_hms_opt(9, 0, 0).unwrap());
+        database.run_standing_orders();
+        assert!(database.get_standing_orders(owner)[0].status == OrderStatus::Completed);
+        assert_eq!(balance(&database, savings), 44000);
+    }
+
+    #[test]
+    fn recurring_order_over_the_withdrawal_limit_skips_one_payment() {
+        let (_, mut database, owner, savings, checking) = savings_at_limit();
+        database.create_standing_order(owner, savings, checking, 5000, Frequency::MonthlyOnDay(2), date(2026, 3, 2)).unwrap();
+
+        database.run_standing_orders();
+        let order = database.get_standing_orders(owner)[0];
+        assert!(order.status == OrderStatus::Active);
+        assert_eq!(order.next_run, date(2026, 4, 2));
+        assert!(database.take_notifications(owner).iter().any(|message| message.contains("was skipped")));
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+use zeroize::Zeroize;
+
+// Passwords, recovery codes and anything else that must not end up in logs. The text is wiped
+// from memory when the value is dropped and Debug never shows it.
+#[derive(Clone, PartialEq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: String) -> Self {
+        return SecretString(secret);
+    }
+
+    pub fn expose(self: &Self) -> &str {
+        return &self.0;
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        return self.0.is_empty();
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        return SecretString(String::from(secret));
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "SecretString(<redacted>)");
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(self: &mut Self) {
+        self.0.zeroize();
+    }
+}
+
+// Key material, e.g. TOTP secrets.
+#[derive(Clone)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn new(secret: Vec<u8>) -> Self {
+        return SecretBytes(secret);
+    }
+
+    pub fn expose(self: &Self) -> &[u8] {
+        return &self.0;
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "SecretBytes(<redacted>)");
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(self: &mut Self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_never_shows_the_secret() {
+        let password = SecretString::from("Correct-Horse-42");
+        assert_eq!(format!("{:?}", password), "SecretString(<redacted>)");
+        assert_eq!(format!("{:?}", Some(vec![password.clone()])), "Some([SecretString(<redacted>)])");
+        assert_eq!(password.expose(), "Correct-Horse-42");
+
+        let key = SecretBytes::new(b"12345678901234567890".to_vec());
+        assert_eq!(format!("{:#?}", key), "SecretBytes(<redacted>)");
+        assert!(!format!("{:?}", key).contains("49"));
+    }
+}
@@ -1,54 +1,145 @@
-use std::io::Write;
-use std::ptr;
+use std::io::{IsTerminal, Write};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::path::Path;
 use regex::Regex;
+use zeroize::Zeroize;
 use chrono::NaiveDate;
 
+use crate::bomanis_bank::BankAccount;
 use crate::bomanis_bank::account::{
-    BankAccount,
+    BankAccountID,
     MoneyAccount,
+    MoneyAccountID,
     MoneyAccountType,
     UserActions
 };
 use crate::bomanis_bank::DatabaseHandler;
+use crate::bomanis_bank::audit::{self, AuditAction, AuditActor, AuditChannel, AuditContext, AuditOutcome};
+use crate::bomanis_bank::clearing::{DepositChannel, DepositID};
+use crate::bomanis_bank::credit::CreditTerms;
+use crate::bomanis_bank::currency::{Currency, SUPPORTED_CURRENCIES};
+use crate::bomanis_bank::fees::FeeQuote;
+use crate::bomanis_bank::ledger::{ReasonCode, TransactionID, TransactionKind};
+use crate::bomanis_bank::onboarding::{self, Application, ContactDetails, OnboardingStatus};
+use crate::bomanis_bank::loans::{AmortizationMethod, Installment, LoanStatus, LoanTerms};
+use crate::bomanis_bank::payments::PayeeID;
+use crate::bomanis_bank::scheduler::{Frequency, OrderStatus, StandingOrderID};
+use crate::bomanis_bank::term_deposits::{InterestPayment, MaturityInstruction, TermDepositStatus, TermDepositTerms};
+use crate::bomanis_bank::secret::SecretString;
+use crate::bomanis_bank::approvals::{ApprovalID, ApprovalOperation, Reviewer};
+use crate::bomanis_bank::business::{self, BusinessDetails, BusinessPermission, BusinessUserID, BUSINESS_PERMISSIONS};
+use crate::bomanis_bank::export::{ExportFormat, EXPORT_FORMATS};
+use crate::bomanis_bank::statements::{StatementFormat, StatementID, STATEMENT_FORMATS};
+use crate::bomanis_bank::staff::{StaffActions, StaffID, StaffRole, STAFF_ACTIONS};
+use crate::bomanis_bank::integrity::{self, IntegrityReport};
+use crate::bomanis_bank::clock::{Clock, RandomSource, SystemClock, ThreadRandom};
 use crate::bomanis_bank::errors;
 
+const RATE_TABLE_PATH: &str = "fx_rates.csv";
+const COMMON_PASSWORDS_PATH: &str = "common_passwords.txt";
+const SIGNING_KEY_VAR: &str = "BOMANIS_SIGNING_KEY"; // hex, at least 32 bytes
+const ADMIN_USER_ID_VAR: &str = "BOMANIS_ADMIN_USER_ID"; // the first admin, while there is none
+const ADMIN_PASSWORD_VAR: &str = "BOMANIS_ADMIN_PASSWORD";
+const ADMIN_NAME_VAR: &str = "BOMANIS_ADMIN_NAME";
+
 pub struct Session {
     bank_account: Option<Rc<RefCell<BankAccount>>>, // ownership of bank account
     database: DatabaseHandler,
+    staff: Option<StaffID>, // staff member logged in to the staff console
+    business_user: Option<BusinessUserID>, // None when the customer logged in with their own credentials
 }
 
 impl Session {
     pub fn new() -> Self {
+        let mut session = Session::with_providers(Rc::new(SystemClock), Box::new(ThreadRandom));
+        if let Err(err) = session.database.load_rate_table(Path::new(RATE_TABLE_PATH)) {
+            println!("Warning: {} ({}), transfers between currencies are unavailable.", err.as_str(), RATE_TABLE_PATH);
+        }
+        if let Err(err) = session.database.load_common_passwords(Path::new(COMMON_PASSWORDS_PATH)) {
+            println!("Warning: could not read {} ({}), common passwords will not be rejected.", COMMON_PASSWORDS_PATH, err);
+        }
+        match std::env::var(SIGNING_KEY_VAR).ok().and_then(|hex| integrity::parse_signing_key(&hex)) {
+            Some(key) => session.database.set_signing_key(key),
+            None => println!("Warning: {} is not set to a key of at least 32 bytes in hex, integrity checkpoints will not be taken.", SIGNING_KEY_VAR),
+        }
+        if let (Ok(user_id), Ok(password)) = (std::env::var(ADMIN_USER_ID_VAR), std::env::var(ADMIN_PASSWORD_VAR)) {
+            let name = std::env::var(ADMIN_NAME_VAR).unwrap_or_else(|_| String::from("Administrator"));
+            if let Err(err) = session.database.provision_admin(user_id, name, SecretString::new(password)) {
+                println!("Warning: the admin from {} could not be created. {}.", ADMIN_USER_ID_VAR, err.as_str());
+            }
+        }
+        return session;
+    }
+
+    // Lets simulations pin time and randomness for the whole session, database included.
+    pub fn with_providers(clock: Rc<dyn Clock>, rng: Box<dyn RandomSource>) -> Self {
+        let mut database = DatabaseHandler::with_providers(clock, rng);
+        database.set_audit_context(AuditContext { actor: AuditActor::Anonymous, channel: AuditChannel::Cli });
         return Session{
             bank_account: None,
-            database: DatabaseHandler::new(),
+            database: database,
+            staff: None,
+            business_user: None,
         }
     }
 
+    // Everything the database records from here on is attributed to `actor`.
+    fn set_audit_actor(self: &mut Self, actor: AuditActor) {
+        self.database.set_audit_context(AuditContext { actor: actor, channel: AuditChannel::Cli });
+    }
+
     // login process will return true if successful.
     // Takes ownership of the user's bank account from the hashmap.
     fn login_process(self: &mut Self) -> bool {
-        let mut user_id: String = String::new();
-        let mut password: String  = String::new();
-
         let mut max_retry_loop_i = 3; // maximum times we can retry login before exiting.
         loop {
             max_retry_loop_i -= 1;
             print!("User ID: ");
             let _ = std::io::stdout().flush();
-            user_id = get_user_input_as_str();
-            print!("Password: ");
-            let _ = std::io::stdout().flush();
-            password = get_user_input_as_str();
+            let user_id = get_user_input_as_str();
+            let password = get_user_input_as_secret("Password: ");
             
             match self.database.find_bank_account_with_user_id(user_id.trim()) {
-                Some(account) => {
+                Some(account) if account.borrow().password.verify(password.expose()) => {
                     self.bank_account = Some(account.clone()); // take ownership of bank account
+                    let owner = account.borrow().id;
+                    self.set_audit_actor(AuditActor::Customer(owner));
+                    if self.database.two_factor_enabled(owner) && !self.second_factor_process(owner) {
+                        self.database.audit(Some(owner), AuditAction::Login, AuditOutcome::Failure, String::from("Second factor not provided"));
+                        self.set_audit_actor(AuditActor::Anonymous);
+                        self.bank_account = None;
+                        println!("Too many incorrect codes, please try again later...");
+                        return false;
+                    }
+                    self.database.audit(Some(owner), AuditAction::Login, AuditOutcome::Success, format!("User ID \"{}\"", user_id.trim()));
+                    if self.database.password_change_required(owner) {
+                        if account.borrow().must_change_password {
+                            println!("Your password was reset and must be changed before you continue.");
+                        } else {
+                            println!("Your password has expired and must be changed before you continue.");
+                        }
+                        self.force_password_change(owner, &password);
+                    }
                     return true;
                 }
-                None => println!("Incorrect details, please try again.")
+                Some(account) => {
+                    let owner = account.borrow().id;
+                    self.database.audit(Some(owner), AuditAction::Login, AuditOutcome::Failure, format!("Wrong password for user ID \"{}\"", user_id.trim()));
+                    println!("Incorrect details, please try again.");
+                },
+                None => match self.database.find_business_user(user_id.trim()) {
+                    Some((account, user)) => {
+                        if self.business_user_login(account, user, &password) {
+                            return true;
+                        }
+                        println!("Incorrect details, please try again.");
+                    },
+                    None => {
+                        self.database.audit(None, AuditAction::Login, AuditOutcome::Failure, format!("Unknown user ID \"{}\"", audit::redact_user_id(user_id.trim())));
+                        println!("Incorrect details, please try again.");
+                    },
+                },
             }
 
             if max_retry_loop_i <= 0 {
@@ -58,28 +149,144 @@ impl Session {
         }
     }
 
+    // Someone acting for a business customer, with their own credentials and permissions.
+    fn business_user_login(self: &mut Self, account: Rc<RefCell<BankAccount>>, user: BusinessUserID, password: &SecretString) -> bool {
+        let owner = account.borrow().id;
+        let matches = match account.borrow().business_user(user) {
+            Some(business_user) => business_user.active && business_user.password.verify(password.expose()),
+            None => false,
+        };
+        if !matches {
+            self.database.audit(Some(owner), AuditAction::Login, AuditOutcome::Failure, format!("Wrong password for user {}", user));
+            return false;
+        }
+        self.bank_account = Some(account);
+        self.business_user = Some(user);
+        self.set_audit_actor(AuditActor::BusinessUser(owner, user));
+        self.database.audit(Some(owner), AuditAction::Login, AuditOutcome::Success, format!("User {}", user));
+        if self.database.business_password_change_required(owner, user) {
+            println!("Please choose a new password before you continue.");
+            self.force_business_password_change(owner, user, password);
+        }
+        return true;
+    }
+
+    fn force_business_password_change(self: &mut Self, owner: BankAccountID, user: BusinessUserID, current_password: &SecretString) {
+        loop {
+            let new_password = get_user_input_as_secret("New password: ");
+            if !self.password_meets_policy(None, new_password.expose()) {
+                continue;
+            }
+            if get_user_input_as_secret("Confirm new password: ") != new_password {
+                println!("Sorry, the new passwords do not match.");
+                continue;
+            }
+            match self.database.change_business_user_password(owner, user, current_password, new_password) {
+                Ok(()) => {
+                    println!("Your password has been changed.");
+                    return;
+                },
+                Err(err) => println!("Sorry, your password could not be changed. {}.", err.as_str()),
+            }
+        }
+    }
+
+    // Second step of logging in for customers with two-factor authentication.
+    fn second_factor_process(self: &mut Self, owner: BankAccountID) -> bool {
+        for _ in 0..3 {
+            let code = get_user_input_as_secret("Authentication code (or a recovery code): ");
+            match self.database.verify_second_factor(owner, code.expose()) {
+                Ok(()) => {
+                    self.warn_if_few_recovery_codes(owner);
+                    return true;
+                },
+                Err(errors::TwoFactorError::Locked) => {
+                    println!("Sorry, {}.", errors::TwoFactorError::Locked.as_str().to_lowercase());
+                    return false;
+                },
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+        return false;
+    }
+
+    // Step-up for an amount leaving one of the customer's accounts, if the policy asks for it.
+    // Business users have no second factor, so those amounts are left to the customer's own login.
+    fn step_up_for_amount(self: &mut Self, amount: i32, currency: Currency) -> bool {
+        let owner = self.bank_account_for_session().borrow().id;
+        if self.business_user.is_some() || !self.database.requires_step_up(owner, amount, currency) {
+            return true;
+        }
+        return self.step_up_verification();
+    }
+
+    // Asks for a fresh code before a high-risk action. Customers without two-factor go straight through.
+    fn step_up_verification(self: &mut Self) -> bool {
+        let owner = self.bank_account_for_session().borrow().id;
+        if !self.database.two_factor_enabled(owner) {
+            return true;
+        }
+        println!("Please confirm it is you.");
+        return self.second_factor_process(owner);
+    }
+
+    fn warn_if_few_recovery_codes(self: &Self, owner: BankAccountID) {
+        let remaining = self.database.remaining_recovery_codes(owner);
+        if remaining <= 3 {
+            println!("You have {} recovery codes left. You can generate new ones under Manage profile.", remaining);
+        }
+    }
+
     fn make_account_process(self: &mut Self) -> bool {
         println!("Thank you for making an account at Bomanis Bank!");
-        println!("Please enter the following details to get started...");
+        println!("Who is the account for?");
+        println!("1. Myself");
+        println!("2. A business");
+        let business = loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => break None,
+                2 => break Some(BusinessDetails {
+                    company_name: get_user_input_validated("Company name: ", |name| !name.is_empty(), errors::OnboardingError::MissingCompanyName),
+                    registration_number: get_user_input_validated("Company registration number: ", business::is_valid_registration_number, errors::OnboardingError::InvalidRegistrationNumber),
+                }),
+                _ => println!("Invalid input. Please try again."),
+            }
+        };
+        if business.is_some() {
+            println!("You will administer the business's users. Please enter your own details...");
+        } else {
+            println!("Please enter the following details to get started...");
+        }
         let mut user_id: String;
         loop {
             print!("User ID: ");
             let _ = std::io::stdout().flush();
             user_id = get_user_input_as_str();
-            if self.database.find_bank_account_with_user_id(&user_id).is_some() {
+            if self.database.user_id_in_use(&user_id) {
                 println!("That user ID is already in use, please enter another.")
             } else {
                 break;
             }
         }
 
-        print!("Password: ");
-        let _ = std::io::stdout().flush();
-        let password = get_user_input_as_str();
+        let password = loop {
+            let password = get_user_input_as_secret("Password: ");
+            if self.password_meets_policy(None, password.expose()) {
+                break password;
+            }
+        };
 
-        print!("Name: ");
-        let _ = std::io::stdout().flush();
-        let name = get_user_input_as_str();
+        let name = loop {
+            print!("Name: ");
+            let _ = std::io::stdout().flush();
+            let name = get_user_input_as_str();
+            if !name.is_empty() {
+                break name;
+            }
+            println!("{}. Please try again.", errors::OnboardingError::MissingName.as_str());
+        };
 
         let dob_re = Regex::new("^([0-9]{4}-[0-9]{2}-[0-9]{2})$").unwrap();
         let mut dob_str: String;
@@ -91,8 +298,18 @@ impl Session {
             if let Some(captures) = dob_re.captures(&dob_str) {
                 let captured_dob = captures.get(1).unwrap().as_str();
                 if let Ok(date) = NaiveDate::parse_from_str(captured_dob, "%Y-%m-%d") {
-                    dob = date;
-                    break;
+                    match self.database.check_date_of_birth(date) {
+                        Ok(()) => {
+                            dob = date;
+                            break;
+                        },
+                        Err(errors::OnboardingError::TooYoung) => {
+                            println!("Sorry, {}.", errors::OnboardingError::TooYoung.as_str().to_lowercase());
+                            println!("A parent or guardian who banks with us can open one for you.");
+                            return false;
+                        },
+                        Err(err) => println!("\nInvalid DOB! {}.", err.as_str()),
+                    }
                 } else {
                     println!("\nInvalid DOB! Please enter a correct date.");
                 }
@@ -100,8 +317,29 @@ impl Session {
                 println!("\nInvalid DOB! Please enter in the format specified.");
             }
         }
-        let new_bank_account = self.database.make_new_bank_account(name, dob, user_id, password);
+        let email = get_user_input_validated("Email: ", onboarding::is_valid_email, errors::OnboardingError::InvalidEmail);
+        let phone = get_user_input_validated("Phone: ", onboarding::is_valid_phone, errors::OnboardingError::InvalidPhone);
+        let address = get_user_input_validated("Address: ", |address| !address.is_empty(), errors::OnboardingError::MissingAddress);
+        let id_document = get_user_input_validated("Passport or national ID number: ", onboarding::is_valid_id_document, errors::OnboardingError::InvalidIdDocument);
+
+        let application = Application {
+            user_id: user_id,
+            password: password,
+            name: name,
+            dob: dob,
+            contact: ContactDetails { email: email, phone: phone, address: address },
+            id_document: id_document,
+            business: business,
+        };
+        let new_bank_account = match self.database.register_customer(application) {
+            Ok(acc) => acc,
+            Err(err) => {
+                println!("Sorry, your account could not be created. {}.", err.as_str());
+                return false;
+            },
+        };
         println!("Account successfully created! Your new account number is {}.", new_bank_account.borrow().id);
+        self.set_audit_actor(AuditActor::Customer(new_bank_account.borrow().id));
         self.bank_account = Some(new_bank_account);
         return true;
     }
@@ -114,6 +352,7 @@ impl Session {
                 println!("\r\nChoose the following options:");
                 println!("1. Login");
                 println!("2. Make a new account");
+                println!("3. Staff console");
                 println!("0. Exit");
                 
                 print!("Enter a number: ");
@@ -130,18 +369,22 @@ impl Session {
                         if self.make_account_process() {
                             break;
                         } else {
-                            return Err(errors::SessionError::Unknown);
+                            return Err(errors::SessionError::RegistrationFailed);
                         }
                     },
+                    3 => self.staff_console(),
                     0 => return Ok(()),
                     _ => println!("Invalid input. Please try again."),
                 }
             }
 
-            println!("\r\nWelcome {}!", self.bank_account_for_session().borrow().name);
+            println!("\r\nWelcome {}!", self.session_display_name());
+            self.show_onboarding_status();
 
             // Looping user input dialogue
             loop {
+                self.database.tick();
+                self.show_notifications();
                 println!("Please choose one of the following options:");
                 let supported_actions: Vec<UserActions> = self.get_valid_actions(self.bank_account_for_session());
                 for (i, actions) in supported_actions.iter().enumerate() {
@@ -157,8 +400,27 @@ impl Session {
                         UserActions::CreateCheckings => self.action_create_money_account(MoneyAccountType::Checking),
                         UserActions::Deposit => self.action_deposit(),
                         UserActions::Withdraw => self.action_withdraw(),
+                        UserActions::ShowBalance => self.action_show_balance(),
+                        UserActions::ShowHistory => self.action_show_history(),
+                        UserActions::Statements => self.action_statements(),
+                        UserActions::ExportTransactions => self.action_export_transactions(),
+                        UserActions::StandingOrders => self.action_standing_orders(),
+                        UserActions::PayBill => self.action_pay_bill(),
+                        UserActions::ManagePayees => self.action_manage_payees(),
+                        UserActions::TermDeposits => self.action_term_deposits(),
+                        UserActions::Loans => self.action_loans(),
+                        UserActions::CreditCards => self.action_credit_cards(),
+                        UserActions::Profile => self.action_profile(),
+                        UserActions::ManageUsers => self.action_manage_users(),
+                        UserActions::PaymentApprovals => self.action_payment_approvals(),
+                        UserActions::Children => self.action_children(),
+                        UserActions::ChangePassword => self.action_change_business_user_password(),
                         UserActions::Logout => {
+                            let owner = self.bank_account_for_session().borrow().id;
+                            self.database.audit(Some(owner), AuditAction::Logout, AuditOutcome::Success, String::new());
+                            self.set_audit_actor(AuditActor::Anonymous);
                             self.bank_account = None;
+                            self.business_user = None;
                             break;
                         },
                         UserActions::Exit => break,
@@ -171,6 +433,526 @@ impl Session {
         }
     }
 
+    // Staff console. Separate from customer logins: staff act on other people's accounts, so every
+    // action is checked against their role and audited under their staff ID.
+    fn staff_console(self: &mut Self) {
+        if !self.database.has_staff() {
+            println!("No staff have been set up. Set {} and {} before starting the bank to create the first admin.", ADMIN_USER_ID_VAR, ADMIN_PASSWORD_VAR);
+            return;
+        }
+        let acting = match self.staff_login_process() {
+            Some(id) => id,
+            None => return,
+        };
+        self.staff = Some(acting);
+        self.database.set_audit_context(AuditContext { actor: AuditActor::Staff(acting), channel: AuditChannel::StaffConsole });
+        let member = self.database.get_staff_member(acting).unwrap();
+        println!("\r\nWelcome {} ({})!", member.name, member.role.description());
+        let role = member.role;
+
+        loop {
+            self.database.tick();
+            for message in self.database.take_staff_notifications(acting) {
+                println!("{}", message);
+            }
+            println!("\r\nStaff console:");
+            let supported_actions: Vec<StaffActions> = STAFF_ACTIONS.iter()
+                .filter(|action| action.required_permission().is_none_or(|permission| role.has_permission(permission)))
+                .copied()
+                .collect();
+            for (i, action) in supported_actions.iter().enumerate() {
+                println!("{}. {}", i+1, action.description());
+            }
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = (get_user_input_as_i32() - 1) as usize; // subtract one for zero-indexing
+            if user_input >= supported_actions.len() {
+                println!("Invalid input. Please try again.");
+                continue;
+            }
+            match supported_actions[user_input] {
+                StaffActions::SearchCustomers => self.staff_search_customers(acting),
+                StaffActions::ViewCustomer => self.staff_view_customer(acting),
+                StaffActions::ViewHistory => self.staff_view_history(acting),
+                StaffActions::PostAdjustment => self.staff_post_adjustment(acting),
+                StaffActions::ReturnCheque => self.staff_return_cheque(acting),
+                StaffActions::ReverseTransaction => self.staff_reverse_transaction(acting),
+                StaffActions::WaiveFee => self.staff_waive_fee(acting),
+                StaffActions::LockAccount => self.staff_set_account_locked(acting, true),
+                StaffActions::UnlockAccount => self.staff_set_account_locked(acting, false),
+                StaffActions::ResetPassword => self.staff_reset_password(acting),
+                StaffActions::ReviewIdentity => self.staff_review_identity(acting),
+                StaffActions::ReviewApprovals => self.staff_review_approvals(acting),
+                StaffActions::ManageStaff => self.staff_manage_staff(acting),
+                StaffActions::VerifyIntegrity => self.staff_verify_integrity(acting),
+                StaffActions::ExportAudit => self.staff_export_audit(acting),
+                StaffActions::Logout => {
+                    self.staff = None;
+                    self.set_audit_actor(AuditActor::Anonymous);
+                    return;
+                },
+            }
+        }
+    }
+
+    fn staff_login_process(self: &mut Self) -> Option<StaffID> {
+        for _ in 0..3 {
+            print!("Staff user ID: ");
+            let _ = std::io::stdout().flush();
+            let user_id = get_user_input_as_str();
+            let password = get_user_input_as_secret("Password: ");
+            match self.database.authenticate_staff(&user_id, &password) {
+                Ok(id) => return Some(id),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+        println!("Too many incorrect attempts, please try again later...");
+        return None;
+    }
+
+    fn staff_details_prompt(self: &Self) -> (String, String, SecretString) {
+        print!("Staff user ID: ");
+        let _ = std::io::stdout().flush();
+        let user_id = get_user_input_as_str();
+        print!("Name: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        let password = loop {
+            let password = get_user_input_as_secret("Password: ");
+            if self.password_meets_policy(None, password.expose()) {
+                break password;
+            }
+        };
+        return (user_id, name, password);
+    }
+
+    fn staff_search_customers(self: &mut Self, acting: StaffID) {
+        print!("Name, user ID, customer number or email: ");
+        let _ = std::io::stdout().flush();
+        let query = get_user_input_as_str();
+        match self.database.search_customers(acting, &query) {
+            Ok(matches) if matches.is_empty() => println!("No customers found."),
+            Ok(matches) => {
+                for ptr_bank_account in matches {
+                    let bank_account = ptr_bank_account.borrow();
+                    println!("{} {} (user ID \"{}\", {}) {}", bank_account.id, bank_account.name, bank_account.userid, bank_account.contact.email, bank_account.onboarding_status.description());
+                }
+            },
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_view_customer(self: &mut Self, acting: StaffID) {
+        print!("Customer number: ");
+        let _ = std::io::stdout().flush();
+        let owner = get_user_input_as_i32() as BankAccountID;
+        let ptr_bank_account = match self.database.staff_view_customer(acting, owner) {
+            Ok(acc) => acc,
+            Err(err) => {
+                println!("Sorry, {}.", err.as_str().to_lowercase());
+                return;
+            },
+        };
+        let bank_account = ptr_bank_account.borrow();
+        println!("Customer number: {}", bank_account.id);
+        println!("User ID: {}", bank_account.userid);
+        println!("Name: {}", bank_account.name);
+        println!("Date of birth: {}", bank_account.dob);
+        println!("Email: {}", bank_account.contact.email);
+        println!("Phone: {}", bank_account.contact.phone);
+        println!("Address: {}", bank_account.contact.address);
+        println!("Identity: {}", bank_account.onboarding_status.description());
+        if bank_account.accounts.is_empty() {
+            println!("No accounts.");
+        }
+        for ptr_money_account in &bank_account.accounts {
+            let money_account = ptr_money_account.borrow();
+            let locked = if money_account.locked { " (locked)" } else { "" };
+            println!("{} {} \"{}\" {}{}", money_account.id, money_account.get_str(), money_account.name, money_account.format_amount(money_account.balance), locked);
+        }
+    }
+
+    fn staff_view_history(self: &mut Self, acting: StaffID) {
+        print!("Account number: ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        match self.database.staff_view_history(acting, id) {
+            Ok(ptr_money_account) => self.print_history(&ptr_money_account.borrow()),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_post_adjustment(self: &mut Self, acting: StaffID) {
+        print!("Account number: ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let currency = match self.database.get_money_account_with_id_u32(id) {
+            Some(acc) => acc.borrow().currency,
+            None => {
+                println!("Sorry, {}.", errors::StaffError::AccountNotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        print!("Amount (negative to debit): ");
+        let _ = std::io::stdout().flush();
+        let amount = get_user_input_as_amount(currency);
+        print!("Reason: ");
+        let _ = std::io::stdout().flush();
+        let reason = get_user_input_as_str();
+        if self.database.adjustment_needs_approval(id, amount) {
+            println!("Adjustments of this size have to be approved by a second staff member.");
+            if !get_user_confirmation(&format!("Submit {} to account {} for approval? (y/n): ", currency.format(amount), id)) {
+                return;
+            }
+            match self.database.request_adjustment(acting, id, amount, &reason) {
+                Ok(request) => println!("Request {} submitted.", request),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+            return;
+        }
+        if !get_user_confirmation(&format!("Post {} to account {}? (y/n): ", currency.format(amount), id)) {
+            return;
+        }
+        match self.database.post_adjustment(acting, id, amount, &reason) {
+            Ok(balance) => println!("Adjustment posted. The balance is now {}.", currency.format(balance)),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_return_cheque(self: &mut Self, acting: StaffID) {
+        print!("Account number: ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let currency = match self.database.get_money_account_with_id_u32(id) {
+            Some(acc) => acc.borrow().currency,
+            None => {
+                println!("Sorry, {}.", errors::StaffError::AccountNotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        let cheques: Vec<(DepositID, i32, NaiveDate)> = self.database.get_pending_deposits(id).iter()
+            .filter(|deposit| deposit.channel == DepositChannel::Cheque)
+            .map(|deposit| (deposit.id, deposit.amount, deposit.deposited_on))
+            .collect();
+        if cheques.is_empty() {
+            println!("No cheque deposits are waiting to clear on this account.");
+            return;
+        }
+        for (deposit_id, amount, deposited_on) in &cheques {
+            println!("#{} {} deposited {}", deposit_id, currency.format(*amount), deposited_on);
+        }
+        print!("Deposit to return (0 to go back): #");
+        let _ = std::io::stdout().flush();
+        let deposit_id = get_user_input_as_i32() as DepositID;
+        if deposit_id == 0 {
+            return;
+        }
+        if !get_user_confirmation(&format!("Return cheque deposit #{} unpaid? (y/n): ", deposit_id)) {
+            return;
+        }
+        match self.database.bounce_deposit(acting, deposit_id) {
+            Ok(balance) => println!("Cheque returned. The balance is now {}.", currency.format(balance)),
+            Err(errors::ClearingError::ApprovalRequired) => {
+                println!("Cheques of this size have to be approved by a second staff member.");
+                if !get_user_confirmation("Submit the return for approval? (y/n): ") {
+                    return;
+                }
+                match self.database.request_deposit_return(acting, deposit_id) {
+                    Ok(request) => println!("Request {} submitted.", request),
+                    Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                }
+            },
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_reverse_transaction(self: &mut Self, acting: StaffID) {
+        print!("Transaction number: #");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as TransactionID;
+        let (money_account, remaining) = match self.database.get_transaction(id) {
+            Some(txn) => {
+                println!("#{} {} {} on account {}: {}", txn.id, txn.posted_at.format("%Y-%m-%d %H:%M"), txn.kind.description(), txn.money_account, txn.memo);
+                (txn.money_account, txn.reversible_amount())
+            },
+            None => {
+                println!("Sorry, {}.", errors::ReversalError::NotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        let currency = match self.database.get_money_account_with_id_u32(money_account) {
+            Some(acc) => acc.borrow().currency,
+            None => {
+                println!("Sorry, {}.", errors::ReversalError::AccountNotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        println!("{} can still be reversed.", currency.format(remaining));
+        print!("Amount to reverse (leave empty for all of it): ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_str();
+        let amount = if user_input.trim().is_empty() {
+            None
+        } else {
+            match currency.parse_amount(user_input.trim()) {
+                Some(amount) => Some(amount),
+                None => {
+                    println!("Sorry, {}.", errors::ReversalError::InvalidAmount.as_str().to_lowercase());
+                    return;
+                },
+            }
+        };
+        let reason = choose_reason_code();
+        let shown_amount = currency.format(amount.unwrap_or(remaining));
+        if !get_user_confirmation(&format!("Reverse {} of #{} ({})? (y/n): ", shown_amount, id, reason.description().to_lowercase())) {
+            return;
+        }
+        match self.database.reverse_transaction(acting, id, amount, reason, None) {
+            Ok(reversal) => println!("Reversal #{} posted.", reversal),
+            Err(errors::ReversalError::ApprovalRequired) => {
+                println!("Reversals of this size have to be approved by a second staff member.");
+                if !get_user_confirmation("Submit the reversal for approval? (y/n): ") {
+                    return;
+                }
+                match self.database.request_reversal(acting, id, amount, reason) {
+                    Ok(request) => println!("Request {} submitted.", request),
+                    Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                }
+            },
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_waive_fee(self: &mut Self, acting: StaffID) {
+        print!("Fee transaction number: #");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as TransactionID;
+        let description = match self.database.get_transaction(id) {
+            Some(txn) => match self.database.get_money_account_with_id_u32(txn.money_account) {
+                Some(acc) => format!("{} on account {}: {}", acc.borrow().format_amount(txn.amount), txn.money_account, txn.memo),
+                None => txn.memo.clone(),
+            },
+            None => {
+                println!("Sorry, {}.", errors::ReversalError::NotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        if !get_user_confirmation(&format!("Refund {}? (y/n): ", description)) {
+            return;
+        }
+        match self.database.waive_fee(acting, id) {
+            Ok(reversal) => println!("Fee refunded as #{}.", reversal),
+            Err(errors::ReversalError::ApprovalRequired) => {
+                println!("Refunds of this size have to be approved by a second staff member.");
+                if !get_user_confirmation("Submit the refund for approval? (y/n): ") {
+                    return;
+                }
+                match self.database.request_fee_waiver(acting, id) {
+                    Ok(request) => println!("Request {} submitted.", request),
+                    Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                }
+            },
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_set_account_locked(self: &mut Self, acting: StaffID, locked: bool) {
+        print!("Account number: ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        print!("Reason: ");
+        let _ = std::io::stdout().flush();
+        let reason = get_user_input_as_str();
+        match self.database.set_account_locked(acting, id, locked, &reason) {
+            Ok(()) if locked => println!("Account {} locked.", id),
+            Ok(()) => println!("Account {} unlocked.", id),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_reset_password(self: &mut Self, acting: StaffID) {
+        print!("Customer number: ");
+        let _ = std::io::stdout().flush();
+        let owner = get_user_input_as_i32() as BankAccountID;
+        if !get_user_confirmation(&format!("Reset the password of customer {}? (y/n): ", owner)) {
+            return;
+        }
+        match self.database.reset_customer_password(acting, owner) {
+            Ok(temporary_password) => println!("Temporary password: {}\nThe customer has to change it when they next log in.", temporary_password.expose()),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_review_identity(self: &mut Self, acting: StaffID) {
+        print!("Customer number: ");
+        let _ = std::io::stdout().flush();
+        let owner = get_user_input_as_i32() as BankAccountID;
+        println!("1. Verified");
+        println!("2. Rejected");
+        println!("0. Back");
+        let status = loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => break OnboardingStatus::Verified,
+                2 => break OnboardingStatus::Rejected,
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        };
+        print!("Note for the customer (optional): ");
+        let _ = std::io::stdout().flush();
+        let note = get_user_input_as_str();
+        let note = if note.is_empty() { None } else { Some(note) };
+        match self.database.staff_set_onboarding_status(acting, owner, status, note) {
+            Ok(()) => println!("Customer {} is now {}.", owner, status.description().to_lowercase()),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    // Four eyes: requests raised by one person are decided by another.
+    fn staff_review_approvals(self: &mut Self, acting: StaffID) {
+        loop {
+            let pending: Vec<ApprovalID> = self.database.get_pending_approvals().iter().map(|request| request.id).collect();
+            if pending.is_empty() {
+                println!("No requests are waiting for approval.");
+                return;
+            }
+            for id in &pending {
+                let request = self.database.get_approval_request(*id).unwrap();
+                let ptr_money_account = self.database.get_money_account_with_id_u32(request.operation.money_account()).unwrap();
+                let amount = ptr_money_account.borrow().format_amount(request.operation.amount().abs());
+                println!("{}. {}, {} by {} on {}, expires {}", request.id, request.operation.description(), amount, request.requested_by.description(), request.requested_at.format("%Y-%m-%d %H:%M"), request.expires_at.format("%Y-%m-%d %H:%M"));
+            }
+            print!("Request number to decide (0 to go back): ");
+            let _ = std::io::stdout().flush();
+            let id = get_user_input_as_i32() as ApprovalID;
+            if id == 0 {
+                return;
+            }
+            if !pending.contains(&id) {
+                println!("Invalid input. Please try again.");
+                continue;
+            }
+            println!("1. Approve");
+            println!("2. Reject");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let result = match get_user_input_as_i32() {
+                1 => self.database.approve_request(acting, id),
+                2 => {
+                    print!("Reason: ");
+                    let _ = std::io::stdout().flush();
+                    let reason = get_user_input_as_str();
+                    self.database.reject_request(acting, id, &reason)
+                },
+                _ => continue,
+            };
+            match result {
+                Ok(()) => println!("Request {} is now {}.", id, self.database.get_approval_request(id).unwrap().status.description().to_lowercase()),
+                Err(errors::ApprovalError::Failed) => println!("Sorry, {}: {}.", errors::ApprovalError::Failed.as_str().to_lowercase(), self.database.get_approval_request(id).unwrap().note.clone().unwrap_or_default()),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+    }
+
+    fn staff_manage_staff(self: &mut Self, acting: StaffID) {
+        loop {
+            println!("1. List staff");
+            println!("2. Add staff member");
+            println!("3. Deactivate staff member");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => match self.database.get_staff(acting) {
+                    Ok(members) => {
+                        for member in members {
+                            let active = if member.active { "" } else { " (deactivated)" };
+                            println!("{} {} \"{}\" {}{}", member.id, member.name, member.user_id, member.role.description(), active);
+                        }
+                    },
+                    Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                },
+                2 => {
+                    let (user_id, name, password) = self.staff_details_prompt();
+                    let roles = [StaffRole::Teller, StaffRole::Supervisor, StaffRole::Admin];
+                    for (i, role) in roles.iter().enumerate() {
+                        println!("{}. {}", i+1, role.description());
+                    }
+                    let role = loop {
+                        print!("Enter a number: ");
+                        let _ = std::io::stdout().flush();
+                        let user_input = get_user_input_as_i32();
+                        if user_input >= 1 && user_input <= (roles.len() as i32) {
+                            break roles[(user_input - 1) as usize];
+                        }
+                        println!("Invalid input. Please try again.");
+                    };
+                    match self.database.create_staff(acting, user_id, name, password, role) {
+                        Ok(id) => println!("Staff member added with staff number {}.", id),
+                        Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                    }
+                },
+                3 => {
+                    print!("Staff number: ");
+                    let _ = std::io::stdout().flush();
+                    let id = get_user_input_as_i32() as StaffID;
+                    match self.database.deactivate_staff(acting, id) {
+                        Ok(()) => println!("Staff member {} deactivated.", id),
+                        Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                    }
+                },
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn staff_verify_integrity(self: &mut Self, acting: StaffID) {
+        match self.database.staff_verify_integrity(acting) {
+            Ok(report) => print_integrity_report(&report),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn staff_export_audit(self: &mut Self, acting: StaffID) {
+        print!("File to write: ");
+        let _ = std::io::stdout().flush();
+        let path = get_user_input_as_str();
+        print!("Customer number (0 for everyone): ");
+        let _ = std::io::stdout().flush();
+        let customer = match get_user_input_as_i32() {
+            0 => None,
+            owner => Some(owner as BankAccountID),
+        };
+        let (from, to) = if get_user_confirmation("Limit to a date range? (y/n): ") {
+            let from = get_user_input_as_date("From (YYYY-MM-DD): ");
+            let to = get_user_input_as_date("To, inclusive (YYYY-MM-DD): ");
+            (from.and_hms_opt(0, 0, 0), to.succ_opt().and_then(|to| to.and_hms_opt(0, 0, 0)))
+        } else {
+            (None, None)
+        };
+        match self.database.staff_export_audit_log(acting, Path::new(&path), customer, from, to) {
+            Ok(count) => println!("Exported {} events to {}.", count, path),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn session_display_name(self: &Self) -> String {
+        let bank_account = self.bank_account_for_session();
+        let bank_account = bank_account.borrow();
+        let name = match self.business_user.and_then(|user| bank_account.business_user(user)) {
+            Some(business_user) => business_user.name.clone(),
+            None => bank_account.name.clone(),
+        };
+        match &bank_account.business {
+            Some(business) => return format!("{} ({})", name, business.company_name),
+            None => return name,
+        }
+    }
+
     // Returns the unwrapped BankAccount.
     // Should always be used after login, and should be valid.
     fn bank_account_for_session(self: &Self) -> Rc<RefCell<BankAccount>> {
@@ -184,13 +966,14 @@ impl Session {
         print!("Please enter your name for this account: ");
         let _ = std::io::stdout().flush();
         let name = get_user_input_as_str();
+        let currency = choose_currency();
         let ptr_bank_account = self.bank_account_for_session();
-        let ptr_new_acc = self.database.make_new_money_account(ptr_bank_account, name, acc_type);
+        let ptr_new_acc = self.database.make_new_money_account(ptr_bank_account, name, acc_type, currency);
         let new_acc = ptr_new_acc.borrow();
-        println!("Thank you, your new {}, {} ({}) has been created!", new_acc.get_str(), new_acc.name, new_acc.id);
+        println!("Thank you, your new {} {}, {} ({}) has been created!", new_acc.currency.code(), new_acc.get_str(), new_acc.name, new_acc.id);
     }
     
-    fn action_deposit(self: &Self) {
+    fn action_deposit(self: &mut Self) {
         println!("Deposit to which account?");
         let mut user_selectable_accs: Vec<Rc<RefCell<MoneyAccount>>> = vec!();
         for ptr_acc in &self.bank_account_for_session().borrow().accounts {
@@ -207,12 +990,23 @@ impl Session {
             } else if user_input <= (user_selectable_accs.len() as i32) {
                 let selected_acc_index = (user_input - 1) as usize; // subtract one for zero-indexing
                 let ptr_money_account = user_selectable_accs[selected_acc_index].clone();
-                let mut money_account = ptr_money_account.borrow_mut();
-                println!("Depositing into {}...", money_account.name);
+                let money_account_id = ptr_money_account.borrow().id;
+                let currency = ptr_money_account.borrow().currency;
+                println!("Depositing into {}...", ptr_money_account.borrow().name);
                 print!("Enter deposit value: ");
                 let _ = std::io::stdout().flush();
-                let _ = money_account.add_balance(get_user_input_as_i32());
-                println!("Deposit successful. Your current balance is ${}", money_account.balance);
+                let amount = get_user_input_as_amount(currency);
+                let channel = choose_deposit_channel();
+                match self.database.deposit(money_account_id, amount, channel, None) {
+                    Ok(new_balance) => {
+                        println!("Deposit successful. Your current balance is {}", currency.format(new_balance));
+                        let pending_balance = ptr_money_account.borrow().get_pending_balance();
+                        if pending_balance > 0 {
+                            println!("{} is pending and will be available once your deposit clears.", currency.format(pending_balance));
+                        }
+                    },
+                    Err(err) => println!("Sorry, your deposit could not be made. {}.", err.as_str()),
+                }
                 return;
             } else {
                 println!("Invalid input. Please try again.");
@@ -220,7 +1014,7 @@ impl Session {
         }
     }
 
-    fn action_withdraw(self: &Self) {
+    fn action_withdraw(self: &mut Self) {
         println!("Withdraw from which account?");
         
         let mut user_selectable_accs: Vec<Rc<RefCell<MoneyAccount>>> = vec!();
@@ -239,14 +1033,28 @@ impl Session {
             } else if user_input <= (user_selectable_accs.len() as i32) {
                 let selected_acc_index = (user_input - 1) as usize; // subtract one for zero-indexing
                 let ptr_money_account = user_selectable_accs[selected_acc_index].clone();
-                let mut money_account = ptr_money_account.borrow_mut();
-                println!("Withdrawing from {}...", money_account.name);
+                let money_account_id = ptr_money_account.borrow().id;
+                let currency = ptr_money_account.borrow().currency;
+                println!("Withdrawing from {}...", ptr_money_account.borrow().name);
+                self.show_remaining_withdrawals(money_account_id);
                 print!("Enter withdraw value: ");
                 let _ = std::io::stdout().flush();
-                match money_account.subtract_balance(get_user_input_as_i32()) {
-                    Ok(new_balance) => println!("Withdraw successful. Your current balance is ${}", new_balance),
+                let amount = get_user_input_as_amount(currency);
+                if !confirm_fees(&self.database.preview_fees(money_account_id, TransactionKind::Withdrawal, amount), currency) {
+                    return;
+                }
+                let owner = self.bank_account_for_session().borrow().id;
+                if !self.step_up_for_amount(amount, currency) {
+                    return;
+                }
+                if !self.outflow_approved(owner, ApprovalOperation::Withdrawal { id: money_account_id, amount: amount }) {
+                    return;
+                }
+                match self.database.withdraw(money_account_id, amount, None) {
+                    Ok(new_balance) => println!("Withdraw successful. Your current balance is {}", currency.format(new_balance)),
                     Err(err) => match err {
                         errors::BalanceError::NotEnough => println!("Sorry, you do not have enough balance for this transaction."),
+                        errors::BalanceError::InvalidAmount | errors::BalanceError::WithdrawalLimitReached | errors::BalanceError::Locked => println!("Sorry, {}.", err.as_str().to_lowercase()),
                         _ => println!("Sorry, something went wrong with your transaction."),
                     }
                 }
@@ -276,8 +1084,18 @@ impl Session {
             } else if user_input <= (user_selectable_accs.len() as i32) {
                 let selected_acc_index = (user_input - 1) as usize; // subtract one for zero-indexing
                 let ptr_money_account = user_selectable_accs[selected_acc_index].clone();
-                let mut money_account = ptr_money_account.borrow();
-                println!("Your current balance is ${}", money_account.balance);
+                let money_account = ptr_money_account.borrow();
+                println!("Your current balance is {}", money_account.format_amount(money_account.balance));
+                if money_account.credit_limit > 0 {
+                    println!("Available credit: {} of {}", money_account.format_amount(money_account.balance + money_account.credit_limit), money_account.format_amount(money_account.credit_limit));
+                }
+                if money_account.pending_balance > 0 {
+                    println!("Pending deposits: {}", money_account.format_amount(money_account.pending_balance));
+                    for deposit in self.database.get_pending_deposits(money_account.id) {
+                        println!("  {} of {}, {} available on {}", deposit.channel.description(), money_account.format_amount(deposit.amount), money_account.format_amount(deposit.held_amount()), deposit.available_on);
+                    }
+                }
+                self.show_remaining_withdrawals(money_account.id);
                 return;
             } else {
                 println!("Invalid input. Please try again.");
@@ -285,49 +1103,1504 @@ impl Session {
         }
     }
 
-    fn action_withdraw_overdraft(self: &Self) {
-        
+    fn action_show_history(self: &Self) {
+        let ptr_money_account = match self.choose_money_account("Show history for which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        self.print_history(&ptr_money_account.borrow());
     }
 
-    fn get_valid_actions(self: &Self, acc: Rc<RefCell<BankAccount>>) -> Vec<UserActions> {
-        let mut supported_user_actions: Vec<UserActions> = vec![];
-        
-        let mut has_checkings_acc: bool = false;
-        let mut has_savings_acc: bool = false;
-        let mut has_positive_balance: bool = false;
+    fn print_history(self: &Self, money_account: &MoneyAccount) {
+        let history = self.database.get_transaction_history(money_account.id);
+        if history.is_empty() {
+            println!("No transactions yet.");
+            return;
+        }
+        // reversals are listed underneath the entry they reverse
+        for txn in history.iter().filter(|txn| !txn.is_reversal()) {
+            println!("#{} {} {:<16} {:>12} {:>12}  {}", txn.id, txn.posted_at.format("%Y-%m-%d %H:%M"), txn.kind.description(), money_account.format_amount(txn.amount), money_account.format_amount(txn.balance_after), txn.memo);
+            for reversal in self.database.get_reversals_of(txn.id) {
+                println!("  -> #{} {} {:<13} {:>12} {:>12}  {}", reversal.id, reversal.posted_at.format("%Y-%m-%d %H:%M"), reversal.kind.description(), money_account.format_amount(reversal.amount), money_account.format_amount(reversal.balance_after), reversal.memo);
+            }
+        }
+        println!("Current balance: {}", money_account.format_amount(money_account.balance));
+    }
 
-        // Check what accounts exist
-        for ptr_acc in &acc.borrow().accounts {
-            let money_acc = ptr_acc.borrow();
-            match money_acc.account_type  {
-                MoneyAccountType::Checking => has_checkings_acc = true,
-                MoneyAccountType::Savings => has_savings_acc = true,
-                _ => (),
+    fn action_statements(self: &Self) {
+        let ptr_money_account = match self.choose_money_account("Statements for which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let statements: Vec<StatementID> = self.database.get_statements(ptr_money_account.borrow().id).iter().map(|statement| statement.id).collect();
+        if statements.is_empty() {
+            println!("No statements yet. A statement is issued after each month ends.");
+            return;
+        }
+        for (i, id) in statements.iter().enumerate() {
+            println!("{}. {}", i + 1, self.database.get_statement(*id).unwrap().period_label());
+        }
+        println!("0. Back");
+        let id = loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = get_user_input_as_i32();
+            if user_input == 0 {
+                return;
+            } else if user_input > 0 && user_input <= statements.len() as i32 {
+                break statements[(user_input - 1) as usize];
             }
-            if money_acc.balance > 0 {
-                has_positive_balance = true;
+            println!("Invalid input. Please try again.");
+        };
+        println!("1. Show here");
+        println!("2. Save to a file");
+        println!("0. Back");
+        loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => {
+                    if let Ok(rendered) = self.database.render_statement(id, StatementFormat::Text) {
+                        print!("{}", rendered);
+                    }
+                    return;
+                },
+                2 => break,
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
             }
         }
-    
-        if has_savings_acc || has_checkings_acc {
-            supported_user_actions.push(UserActions::Deposit);
-            supported_user_actions.push(UserActions::ShowBalance);
-            if has_positive_balance {
-                supported_user_actions.push(UserActions::Withdraw);
-            }
+        let format = choose_statement_format();
+        let file_name = self.database.get_statement(id).unwrap().file_name(format);
+        print!("File to write (leave empty for {}): ", file_name);
+        let _ = std::io::stdout().flush();
+        let mut path = get_user_input_as_str();
+        if path.is_empty() {
+            path = file_name;
         }
-        if !has_checkings_acc {
-            supported_user_actions.push(UserActions::CreateCheckings);
+        match self.database.save_statement(id, format, Path::new(&path)) {
+            Ok(()) => println!("Statement saved to {}.", path),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
         }
-        if !has_savings_acc {
+    }
+
+    fn action_export_transactions(self: &Self) {
+        let ptr_money_account = match self.choose_money_account("Export transactions from which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let id = ptr_money_account.borrow().id;
+        let from = get_user_input_as_date("From (YYYY-MM-DD): ");
+        let to = get_user_input_as_date("To, inclusive (YYYY-MM-DD): ");
+        let export = match self.database.get_transaction_export(id, from, to) {
+            Ok(export) => export,
+            Err(err) => {
+                println!("Sorry, {}.", err.as_str().to_lowercase());
+                return;
+            },
+        };
+        let format = choose_export_format();
+        let file_name = export.file_name(format);
+        print!("File to write (leave empty for {}): ", file_name);
+        let _ = std::io::stdout().flush();
+        let mut path = get_user_input_as_str();
+        if path.is_empty() {
+            path = file_name;
+        }
+        match self.database.save_transaction_export(id, from, to, format, Path::new(&path)) {
+            Ok(count) => println!("Exported {} transactions to {}.", count, path),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    // Lists the session's money accounts and returns the one picked, or None if the user backs out.
+    fn choose_money_account(self: &Self, prompt: &str) -> Option<Rc<RefCell<MoneyAccount>>> {
+        println!("{}", prompt);
+        let mut user_selectable_accs: Vec<Rc<RefCell<MoneyAccount>>> = vec!();
+        for ptr_acc in &self.bank_account_for_session().borrow().accounts {
+            user_selectable_accs.push(ptr_acc.clone());
+            println!("{}. {} ({})", user_selectable_accs.len(), ptr_acc.borrow().name, ptr_acc.borrow().id);
+        }
+        println!("0. Exit");
+
+        loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = get_user_input_as_i32();
+            if user_input == 0 {
+                return None;
+            } else if user_input > 0 && user_input <= (user_selectable_accs.len() as i32) {
+                let selected_acc_index = (user_input - 1) as usize; // subtract one for zero-indexing
+                return Some(user_selectable_accs[selected_acc_index].clone());
+            } else {
+                println!("Invalid input. Please try again.");
+            }
+        }
+    }
+
+    fn show_onboarding_status(self: &Self) {
+        let bank_account = self.bank_account_for_session();
+        let bank_account = bank_account.borrow();
+        match bank_account.onboarding_status {
+            OnboardingStatus::Verified => (),
+            OnboardingStatus::Rejected => println!("We could not verify your identity ({}). Please contact us before using your account.", bank_account.onboarding_note.clone().unwrap_or_default()),
+            _ => println!("Your identity is still being verified. You will be able to make transactions once this is complete."),
+        }
+    }
+
+    fn show_remaining_withdrawals(self: &Self, id: MoneyAccountID) {
+        if let Some(remaining) = self.database.get_remaining_withdrawals(id) {
+            println!("Withdrawals remaining this statement cycle: {}", remaining);
+        }
+    }
+
+    fn show_notifications(self: &mut Self) {
+        let bank_account_id = self.bank_account_for_session().borrow().id;
+        for message in self.database.take_notifications(bank_account_id) {
+            println!("* {}", message);
+        }
+    }
+
+    fn action_standing_orders(self: &mut Self) {
+        loop {
+            println!("Standing orders:");
+            println!("1. Set up a scheduled transfer");
+            println!("2. List standing orders");
+            println!("3. Edit a standing order");
+            println!("4. Cancel a standing order");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.standing_order_create(),
+                2 => self.standing_order_list(),
+                3 => self.standing_order_edit(),
+                4 => self.standing_order_cancel(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn standing_order_create(self: &mut Self) {
+        let ptr_from_account = match self.choose_money_account("Transfer from which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let from = ptr_from_account.borrow().id;
+        let currency = ptr_from_account.borrow().currency;
+        print!("Transfer to account number: ");
+        let _ = std::io::stdout().flush();
+        let to = get_user_input_as_i32() as MoneyAccountID;
+        print!("Enter transfer value in {}: ", currency.code());
+        let _ = std::io::stdout().flush();
+        let amount = get_user_input_as_amount(currency);
+        let frequency = choose_frequency();
+        let start = get_user_input_as_date("First transfer date (YYYY-MM-DD): ");
+        if !self.step_up_for_amount(amount, currency) {
+            return;
+        }
+
+        let owner = self.bank_account_for_session().borrow().id;
+        if !self.outflow_approved(owner, ApprovalOperation::StandingOrder { owner: owner, from: from, to: to, amount: amount, frequency: frequency, start: start }) {
+            return;
+        }
+        match self.database.create_standing_order(owner, from, to, amount, frequency, start) {
+            Ok(id) => println!("Standing order #{} has been set up.", id),
+            Err(err) => println!("Sorry, the standing order could not be set up. {}.", err.as_str()),
+        }
+    }
+
+    fn standing_order_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let orders = self.database.get_standing_orders(owner);
+        if orders.is_empty() {
+            println!("You have no standing orders.");
+        }
+        for order in orders {
+            let next = if order.status == OrderStatus::Active { format!(", next on {}", order.due_date()) } else { String::new() };
+            let amount = match self.database.get_money_account_with_id_u32(order.from) {
+                Some(acc) => acc.borrow().format_amount(order.amount),
+                None => order.amount.to_string(),
+            };
+            println!("#{} {} from {} to {}, {} [{}]{}", order.id, amount, order.from, order.to, order.frequency.description(), order.status.description(), next);
+        }
+    }
+
+    fn standing_order_edit(self: &mut Self) {
+        self.standing_order_list();
+        print!("Edit which standing order? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as StandingOrderID;
+        let owner = self.bank_account_for_session().borrow().id;
+        let currency = match self.database.get_standing_orders(owner).iter().find(|order| order.id == id) {
+            Some(order) => self.database.get_money_account_with_id_u32(order.from).map(|acc| acc.borrow().currency),
+            None => None,
+        };
+        let amount = match currency {
+            Some(currency) => loop {
+                print!("New transfer value (blank to keep): ");
+                let _ = std::io::stdout().flush();
+                let amount_str = get_user_input_as_str();
+                if amount_str.is_empty() {
+                    break None;
+                }
+                match currency.parse_amount(&amount_str) {
+                    Some(amount) => break Some(amount),
+                    None => println!("Invalid amount! Please try again."),
+                }
+            },
+            None => None,
+        };
+        let next_run = loop {
+            print!("New next transfer date (YYYY-MM-DD, blank to keep): ");
+            let _ = std::io::stdout().flush();
+            let date_str = get_user_input_as_str();
+            if date_str.is_empty() {
+                break None;
+            }
+            match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                Ok(date) => break Some(date),
+                Err(_) => println!("Invalid date! Please enter in the format specified."),
+            }
+        };
+
+        if let (Some(amount), Some(currency)) = (amount, currency) {
+            if !self.step_up_for_amount(amount, currency) {
+                return;
+            }
+        }
+
+        match self.database.edit_standing_order(owner, id, amount, next_run) {
+            Ok(()) => println!("Standing order #{} has been updated.", id),
+            Err(err) => println!("Sorry, the standing order could not be updated. {}.", err.as_str()),
+        }
+    }
+
+    fn standing_order_cancel(self: &mut Self) {
+        self.standing_order_list();
+        print!("Cancel which standing order? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as StandingOrderID;
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.cancel_standing_order(owner, id) {
+            Ok(()) => println!("Standing order #{} has been cancelled.", id),
+            Err(err) => println!("Sorry, the standing order could not be cancelled. {}.", err.as_str()),
+        }
+    }
+
+    fn action_pay_bill(self: &mut Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let payees = self.database.get_payees(owner);
+        if payees.is_empty() {
+            println!("You have no payees yet. Please add one first.");
+            self.payee_add();
+            return;
+        }
+        println!("Pay which payee?");
+        for (i, payee) in payees.iter().enumerate() {
+            println!("{}. {} ({})", i+1, payee.name, payee.external_account);
+        }
+        println!("0. Exit");
+        let payee = loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = get_user_input_as_i32();
+            if user_input == 0 {
+                return;
+            } else if user_input > 0 && user_input <= (payees.len() as i32) {
+                break &payees[(user_input - 1) as usize];
+            }
+            println!("Invalid input. Please try again.");
+        };
+        let ptr_money_account = match self.choose_money_account("Pay from which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let from = ptr_money_account.borrow().id;
+        let currency = ptr_money_account.borrow().currency;
+        print!("Enter payment value: ");
+        let _ = std::io::stdout().flush();
+        let amount = get_user_input_as_amount(currency);
+        print!("Reference (blank for \"{}\"): ", payee.reference);
+        let _ = std::io::stdout().flush();
+        let reference = match get_user_input_as_str() {
+            reference if reference.is_empty() => None,
+            reference => Some(reference),
+        };
+        if !confirm_fees(&self.database.preview_fees(from, TransactionKind::BillPayment, amount), currency) {
+            return;
+        }
+        if !self.step_up_for_amount(amount, currency) {
+            return;
+        }
+        let operation = ApprovalOperation::BillPayment { owner: owner, from: from, payee: payee.id, amount: amount, reference: reference.clone() };
+        if !self.outflow_approved(owner, operation) {
+            return;
+        }
+        match self.database.pay_bill(owner, from, payee.id, amount, reference, None) {
+            Ok(id) => println!("Payment #{} to {} has been queued. Your current balance is {}", id, payee.name, currency.format(ptr_money_account.borrow().balance)),
+            Err(err) => println!("Sorry, your payment could not be made. {}.", err.as_str()),
+        }
+    }
+
+    fn action_manage_payees(self: &mut Self) {
+        loop {
+            println!("Payees:");
+            println!("1. Add a payee");
+            println!("2. List payees");
+            println!("3. Remove a payee");
+            println!("4. Show payment status");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.payee_add(),
+                2 => self.payee_list(),
+                3 => self.payee_remove(),
+                4 => self.payment_list(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn payee_add(self: &mut Self) {
+        print!("Payee name: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        print!("IBAN or account number: ");
+        let _ = std::io::stdout().flush();
+        let external_account = get_user_input_as_str();
+        print!("Default reference: ");
+        let _ = std::io::stdout().flush();
+        let reference = get_user_input_as_str();
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.add_payee(owner, name, &external_account, reference) {
+            Ok(_) => println!("Payee added."),
+            Err(err) => println!("Sorry, the payee could not be added. {}.", err.as_str()),
+        }
+    }
+
+    fn payee_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let payees = self.database.get_payees(owner);
+        if payees.is_empty() {
+            println!("You have no payees.");
+        }
+        for payee in payees {
+            println!("#{} {} ({}), ref \"{}\"", payee.id, payee.name, payee.external_account, payee.reference);
+        }
+    }
+
+    fn payee_remove(self: &mut Self) {
+        self.payee_list();
+        print!("Remove which payee? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as PayeeID;
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.remove_payee(owner, id) {
+            Ok(()) => println!("Payee removed."),
+            Err(err) => println!("Sorry, the payee could not be removed. {}.", err.as_str()),
+        }
+    }
+
+    fn payment_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let payments = self.database.get_outbound_payments(owner);
+        if payments.is_empty() {
+            println!("You have not made any payments.");
+        }
+        for payment in payments {
+            let reason = match &payment.rejection_reason {
+                Some(reason) => format!(" ({})", reason),
+                None => String::new(),
+            };
+            println!("#{} {} {} to {}, ref \"{}\" [{}]{}", payment.id, payment.created_at.format("%Y-%m-%d"), payment.currency.format(payment.amount), payment.payee.name, payment.reference, payment.status.description(), reason);
+        }
+    }
+
+    fn action_term_deposits(self: &mut Self) {
+        loop {
+            println!("Term deposits:");
+            println!("1. Open a term deposit");
+            println!("2. List term deposits");
+            println!("3. Close a term deposit early");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.term_deposit_open(),
+                2 => self.term_deposit_list(),
+                3 => self.term_deposit_close(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn term_deposit_open(self: &mut Self) {
+        let policy = self.database.get_term_deposit_policy();
+        println!("We currently offer {:.2}% a year for terms of {} to {} months.", policy.offered_rate * 100.0, policy.min_term_months, policy.max_term_months);
+        let ptr_funding_account = match self.choose_money_account("Fund from which checkings account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let funding = ptr_funding_account.borrow().id;
+        let currency = ptr_funding_account.borrow().currency;
+        print!("Please enter your name for this account: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        print!("Enter deposit value (at least {}): ", currency.format(policy.min_principal));
+        let _ = std::io::stdout().flush();
+        let principal = get_user_input_as_amount(currency);
+        print!("Term in months: ");
+        let _ = std::io::stdout().flush();
+        let term_months = get_user_input_as_i32().max(0) as u32;
+        let terms = TermDepositTerms {
+            principal: principal,
+            term_months: term_months,
+            annual_rate: policy.offered_rate,
+            interest_payment: choose_interest_payment(),
+            at_maturity: choose_maturity_instruction(),
+        };
+
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.open_term_deposit(owner, funding, name, terms) {
+            Ok(id) => {
+                let maturity_date = self.database.get_term_deposit(id).map(|deposit| deposit.maturity_date);
+                println!("Your term deposit ({}) of {} has been opened and matures on {}.", id, currency.format(principal), maturity_date.unwrap());
+            },
+            Err(err) => println!("Sorry, the term deposit could not be opened. {}.", err.as_str()),
+        }
+    }
+
+    fn term_deposit_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let deposits = self.database.get_term_deposits(owner);
+        if deposits.is_empty() {
+            println!("You have no term deposits.");
+        }
+        for deposit in deposits {
+            let amount = match self.database.get_money_account_with_id_u32(deposit.money_account) {
+                Some(acc) => acc.borrow().format_amount(deposit.terms.principal),
+                None => deposit.terms.principal.to_string(),
+            };
+            let next = if deposit.status == TermDepositStatus::Active { format!(", matures on {}", deposit.maturity_date) } else { String::new() };
+            println!("{} {} for {} months at {:.2}%, {}, {} [{}]{}", deposit.money_account, amount, deposit.terms.term_months, deposit.terms.annual_rate * 100.0,
+                deposit.terms.interest_payment.description().to_lowercase(), deposit.terms.at_maturity.description().to_lowercase(), deposit.status.description(), next);
+        }
+    }
+
+    fn term_deposit_close(self: &mut Self) {
+        self.term_deposit_list();
+        print!("Close which term deposit? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let owner = self.bank_account_for_session().borrow().id;
+        let quote = match self.database.quote_early_closure(owner, id) {
+            Ok(quote) => quote,
+            Err(err) => {
+                println!("Sorry, the term deposit cannot be closed. {}.", err.as_str());
+                return;
+            },
+        };
+        let ptr_money_account = self.database.get_money_account_with_id_u32(id).unwrap();
+        let money_account = ptr_money_account.borrow();
+        println!("Closing before maturity:");
+        println!("  Balance: {}", money_account.format_amount(money_account.balance));
+        println!("  Interest earned: {}", money_account.format_amount(quote.accrued_interest));
+        println!("  Early closure penalty: -{}", money_account.format_amount(quote.penalty));
+        println!("  You will receive: {}", money_account.format_amount(quote.payout));
+        drop(money_account);
+        if !get_user_confirmation("Close this term deposit? (y/n): ") {
+            return;
+        }
+        match self.database.close_term_deposit_early(owner, id) {
+            Ok(payout) => println!("Your term deposit has been closed and {} paid to your linked account.", ptr_money_account.borrow().format_amount(payout)),
+            Err(err) => println!("Sorry, the term deposit cannot be closed. {}.", err.as_str()),
+        }
+    }
+
+    fn action_loans(self: &mut Self) {
+        loop {
+            println!("Loans:");
+            println!("1. Apply for a loan");
+            println!("2. List loans");
+            println!("3. Show repayment schedule");
+            println!("4. Pay off a loan");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.loan_apply(),
+                2 => self.loan_list(),
+                3 => self.loan_schedule(),
+                4 => self.loan_pay_off(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn loan_apply(self: &mut Self) {
+        let policy = self.database.get_loan_policy();
+        println!("We currently lend at {:.2}% a year for terms of {} to {} months.", policy.offered_rate * 100.0, policy.min_term_months, policy.max_term_months);
+        let ptr_checking_account = match self.choose_money_account("Pay the loan into which checkings account? Repayments will be collected from it.") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let disburse_to = ptr_checking_account.borrow().id;
+        let currency = ptr_checking_account.borrow().currency;
+        print!("Please enter your name for this account: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        print!("Enter loan value ({} to {}): ", currency.format(policy.min_principal), currency.format(policy.max_principal));
+        let _ = std::io::stdout().flush();
+        let principal = get_user_input_as_amount(currency);
+        print!("Term in months: ");
+        let _ = std::io::stdout().flush();
+        let term_months = get_user_input_as_i32().max(0) as u32;
+        let terms = LoanTerms {
+            principal: principal,
+            annual_rate: policy.offered_rate,
+            term_months: term_months,
+            method: choose_amortization_method(),
+        };
+
+        let schedule = self.database.preview_loan_schedule(&terms);
+        if let Some(first) = schedule.first() {
+            let total_interest: i32 = schedule.iter().map(|installment| installment.interest).sum();
+            println!("First payment {}, total interest {} over {} payments.", currency.format(first.total()), currency.format(total_interest), schedule.len());
+        }
+        if !get_user_confirmation("Take out this loan? (y/n): ") {
+            return;
+        }
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.originate_loan(owner, disburse_to, name, terms) {
+            Ok(id) => println!("Your loan ({}) of {} has been paid into {}.", id, currency.format(principal), disburse_to),
+            Err(err) => println!("Sorry, the loan could not be made. {}.", err.as_str()),
+        }
+    }
+
+    fn loan_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let loans = self.database.get_loans(owner);
+        if loans.is_empty() {
+            println!("You have no loans.");
+        }
+        for loan in loans {
+            let ptr_loan_account = match self.database.get_money_account_with_id_u32(loan.money_account) {
+                Some(acc) => acc,
+                None => continue,
+            };
+            let loan_account = ptr_loan_account.borrow();
+            let next = match loan.next_installment() {
+                Some(installment) if loan.status == LoanStatus::Active => format!(", next payment {} on {}", loan_account.format_amount(installment.total()), installment.due_date),
+                _ => String::new(),
+            };
+            println!("{} {} at {:.2}%, {} outstanding [{}]{}", loan.money_account, loan_account.format_amount(loan.terms.principal), loan.terms.annual_rate * 100.0,
+                loan_account.format_amount(loan.outstanding_principal()), loan.status.description(), next);
+        }
+    }
+
+    fn loan_schedule(self: &Self) {
+        self.loan_list();
+        print!("Show the schedule for which loan? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let owner = self.bank_account_for_session().borrow().id;
+        let loan = match self.database.get_loan(id).filter(|loan| loan.owner == owner) {
+            Some(loan) => loan,
+            None => {
+                println!("Sorry, {}.", errors::LoanError::NotFound.as_str().to_lowercase());
+                return;
+            },
+        };
+        let currency = self.database.get_money_account_with_id_u32(id).unwrap().borrow().currency;
+        println!("Loan {}: {} over {} months at {:.2}%, {}", id, currency.format(loan.terms.principal), loan.terms.term_months, loan.terms.annual_rate * 100.0, loan.terms.method.description().to_lowercase());
+        print_loan_schedule(&loan.installments, currency);
+    }
+
+    fn loan_pay_off(self: &mut Self) {
+        self.loan_list();
+        print!("Pay off which loan? ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let owner = self.bank_account_for_session().borrow().id;
+        let quote = match self.database.quote_loan_payoff(owner, id) {
+            Ok(quote) => quote,
+            Err(err) => {
+                println!("Sorry, the loan cannot be paid off. {}.", err.as_str());
+                return;
+            },
+        };
+        let currency = self.database.get_money_account_with_id_u32(id).unwrap().borrow().currency;
+        println!("To pay off this loan today:");
+        println!("  Principal: {}", currency.format(quote.principal));
+        println!("  Interest: {}", currency.format(quote.interest));
+        if quote.penalty > 0 {
+            println!("  Late payment penalty: {}", currency.format(quote.penalty));
+        }
+        println!("  Total: {}", currency.format(quote.total));
+        let ptr_from_account = match self.choose_money_account("Pay from which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let from = ptr_from_account.borrow().id;
+        if !get_user_confirmation("Pay off this loan? (y/n): ") {
+            return;
+        }
+        match self.database.pay_off_loan(owner, id, from) {
+            Ok(paid) => println!("Your loan has been paid off with {}. Your current balance is {}", currency.format(paid), currency.format(ptr_from_account.borrow().balance)),
+            Err(err) => println!("Sorry, the loan could not be paid off. {}.", err.as_str()),
+        }
+    }
+
+    fn action_credit_cards(self: &mut Self) {
+        loop {
+            println!("Credit cards:");
+            println!("1. Open a credit card");
+            println!("2. Show credit card summary");
+            println!("3. Make a card purchase");
+            println!("4. Pay a credit card");
+            println!("5. Show statements");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.credit_card_open(),
+                2 => self.credit_card_list(),
+                3 => self.credit_card_purchase(),
+                4 => self.credit_card_pay(),
+                5 => self.credit_card_statements(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn credit_card_open(self: &mut Self) {
+        let policy = self.database.get_credit_policy();
+        println!("Our cards charge {:.2}% a year on purchases and {:.2}% on cash advances.", policy.purchase_apr * 100.0, policy.cash_advance_apr * 100.0);
+        print!("Please enter your name for this account: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        let currency = choose_currency();
+        print!("Credit limit (up to {}): ", currency.format(policy.max_limit));
+        let _ = std::io::stdout().flush();
+        let limit = get_user_input_as_amount(currency);
+        let terms = CreditTerms {
+            limit: limit,
+            purchase_apr: policy.purchase_apr,
+            cash_advance_apr: policy.cash_advance_apr,
+        };
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.open_credit_line(owner, name, currency, terms) {
+            Ok(id) => println!("Your credit card ({}) with a limit of {} has been opened.", id, currency.format(limit)),
+            Err(err) => println!("Sorry, the credit card could not be opened. {}.", err.as_str()),
+        }
+    }
+
+    fn credit_card_list(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let lines = self.database.get_credit_lines(owner);
+        if lines.is_empty() {
+            println!("You have no credit cards.");
+        }
+        for line in lines {
+            let ptr_card_account = match self.database.get_money_account_with_id_u32(line.money_account) {
+                Some(acc) => acc,
+                None => continue,
+            };
+            let card_account = ptr_card_account.borrow();
+            println!("{} {}: {} owed, {} available of {}", line.money_account, card_account.name, card_account.format_amount((-card_account.balance).max(0)),
+                card_account.format_amount(card_account.balance + line.terms.limit), card_account.format_amount(line.terms.limit));
+            println!("  Purchases {} at {:.2}%, cash advances {} at {:.2}%, interest and fees {}", card_account.format_amount(line.purchase_balance), line.terms.purchase_apr * 100.0,
+                card_account.format_amount(line.cash_advance_balance), line.terms.cash_advance_apr * 100.0, card_account.format_amount(line.interest_and_fees_balance));
+            match line.last_statement() {
+                Some(statement) if statement.minimum_outstanding() > 0 => println!("  Minimum payment of {} due by {}", card_account.format_amount(statement.minimum_outstanding()), statement.due_date),
+                _ => (),
+            }
+            println!("  Next statement on {}", line.next_statement_date());
+        }
+    }
+
+    // Lists the session's credit cards and returns the one picked.
+    fn choose_credit_card(self: &Self, prompt: &str) -> Option<MoneyAccountID> {
+        self.credit_card_list();
+        print!("{} ", prompt);
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        let owner = self.bank_account_for_session().borrow().id;
+        if self.database.get_credit_line(id).is_some_and(|line| line.owner == owner) {
+            return Some(id);
+        }
+        println!("Sorry, {}.", errors::CreditError::NotFound.as_str().to_lowercase());
+        return None;
+    }
+
+    fn credit_card_purchase(self: &mut Self) {
+        let id = match self.choose_credit_card("Which card?") {
+            Some(id) => id,
+            None => return,
+        };
+        let currency = self.database.get_money_account_with_id_u32(id).unwrap().borrow().currency;
+        print!("Merchant: ");
+        let _ = std::io::stdout().flush();
+        let merchant = get_user_input_as_str();
+        print!("Enter purchase value: ");
+        let _ = std::io::stdout().flush();
+        let amount = get_user_input_as_amount(currency);
+        match self.database.card_purchase(id, amount, &merchant, None) {
+            Ok(new_balance) => println!("Purchase approved. Your current balance is {}", currency.format(new_balance)),
+            Err(errors::BalanceError::NotEnough) => println!("Sorry, this purchase would take you over your credit limit."),
+            Err(err) => println!("Sorry, the purchase could not be made. {}.", err.as_str()),
+        }
+    }
+
+    fn credit_card_pay(self: &mut Self) {
+        let id = match self.choose_credit_card("Pay which card?") {
+            Some(id) => id,
+            None => return,
+        };
+        let ptr_from_account = match self.choose_money_account("Pay from which account?") {
+            Some(acc) => acc,
+            None => return,
+        };
+        let from = ptr_from_account.borrow().id;
+        let currency = ptr_from_account.borrow().currency;
+        print!("Enter payment value: ");
+        let _ = std::io::stdout().flush();
+        let amount = get_user_input_as_amount(currency);
+        if !self.step_up_for_amount(amount, currency) {
+            return;
+        }
+        match self.database.transfer(from, id, amount, None) {
+            Ok(new_balance) => println!("Payment made. Your current balance is {}", currency.format(new_balance)),
+            Err(err) => println!("Sorry, your payment could not be made. {}.", err.as_str()),
+        }
+    }
+
+    fn credit_card_statements(self: &Self) {
+        let id = match self.choose_credit_card("Show statements for which card?") {
+            Some(id) => id,
+            None => return,
+        };
+        let line = self.database.get_credit_line(id).unwrap();
+        if line.statements.is_empty() {
+            println!("No statements yet. Your first statement is on {}.", line.next_statement_date());
+        }
+        let currency = self.database.get_money_account_with_id_u32(id).unwrap().borrow().currency;
+        for statement in &line.statements {
+            let paid = match statement.paid_by_due_date {
+                Some(true) => "paid in full",
+                Some(false) => "not paid in full",
+                None => "open",
+            };
+            println!("{} balance {}, interest {}, minimum {} due {}, received {} [{}]", statement.closing_date, currency.format(statement.balance), currency.format(statement.interest_charged),
+                currency.format(statement.minimum_payment), statement.due_date, currency.format(statement.payments), paid);
+        }
+    }
+
+    fn action_profile(self: &mut Self) {
+        loop {
+            println!("Profile:");
+            println!("1. View profile");
+            println!("2. Change password");
+            println!("3. Update name");
+            println!("4. Update contact details");
+            println!("5. Change user ID");
+            println!("6. Show change history");
+            println!("7. Two-factor authentication");
+            println!("8. Show recent security activity");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.profile_view(),
+                2 => self.profile_change_password(),
+                3 => self.profile_update_name(),
+                4 => self.profile_update_contact(),
+                5 => self.profile_change_user_id(),
+                6 => self.profile_history(),
+                7 => self.profile_two_factor(),
+                8 => self.profile_security_activity(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn profile_view(self: &Self) {
+        let ptr_bank_account = self.bank_account_for_session();
+        let bank_account = ptr_bank_account.borrow();
+        println!("Customer number: {}", bank_account.id);
+        println!("User ID: {}", bank_account.userid);
+        println!("Name: {}", bank_account.name);
+        println!("Date of birth: {}", bank_account.dob);
+        println!("Email: {}", bank_account.contact.email);
+        println!("Phone: {}", bank_account.contact.phone);
+        println!("Address: {}", bank_account.contact.address);
+        println!("Identity: {}", bank_account.onboarding_status.description());
+    }
+
+    // Lists every rule the password breaks, so the customer can fix them all in one go.
+    fn password_meets_policy(self: &Self, owner: Option<BankAccountID>, password: &str) -> bool {
+        let failed_rules = self.database.check_new_password(owner, password);
+        if failed_rules.is_empty() {
+            return true;
+        }
+        let policy = self.database.get_password_policy();
+        println!("Sorry, that password is not allowed. Your password:");
+        for rule in failed_rules {
+            println!("  - {}", rule.description(&policy));
+        }
+        return false;
+    }
+
+    // Asks for a new password until one is accepted.
+    fn force_password_change(self: &mut Self, owner: BankAccountID, current_password: &SecretString) {
+        loop {
+            let new_password = get_user_input_as_secret("New password: ");
+            if !self.password_meets_policy(Some(owner), new_password.expose()) {
+                continue;
+            }
+            if get_user_input_as_secret("Confirm new password: ") != new_password {
+                println!("Sorry, the new passwords do not match.");
+                continue;
+            }
+            match self.database.change_password(owner, current_password, new_password) {
+                Ok(()) => {
+                    println!("Your password has been changed.");
+                    return;
+                },
+                Err(err) => println!("Sorry, your password could not be changed. {}.", err.as_str()),
+            }
+        }
+    }
+
+    fn profile_change_password(self: &mut Self) {
+        let current_password = get_user_input_as_secret("Current password: ");
+        let owner = self.bank_account_for_session().borrow().id;
+        let new_password = get_user_input_as_secret("New password: ");
+        if !self.password_meets_policy(Some(owner), new_password.expose()) {
+            return;
+        }
+        if get_user_input_as_secret("Confirm new password: ") != new_password {
+            println!("Sorry, the new passwords do not match.");
+            return;
+        }
+        if !self.step_up_verification() {
+            return;
+        }
+        match self.database.change_password(owner, &current_password, new_password) {
+            Ok(()) => println!("Your password has been changed."),
+            Err(err) => println!("Sorry, your password could not be changed. {}.", err.as_str()),
+        }
+    }
+
+    fn profile_update_name(self: &mut Self) {
+        print!("New name: ");
+        let _ = std::io::stdout().flush();
+        let name = get_user_input_as_str();
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.update_name(owner, name) {
+            Ok(()) => println!("Your name has been updated."),
+            Err(err) => println!("Sorry, your name could not be updated. {}.", err.as_str()),
+        }
+    }
+
+    fn profile_update_contact(self: &mut Self) {
+        let mut contact = self.bank_account_for_session().borrow().contact.clone();
+        for (prompt, value) in [("Email", &mut contact.email), ("Phone", &mut contact.phone), ("Address", &mut contact.address)] {
+            print!("{} (blank to keep \"{}\"): ", prompt, value);
+            let _ = std::io::stdout().flush();
+            let input = get_user_input_as_str();
+            if !input.is_empty() {
+                *value = input;
+            }
+        }
+        if !self.step_up_verification() {
+            return;
+        }
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.update_contact_details(owner, contact) {
+            Ok(()) => println!("Your contact details have been updated."),
+            Err(err) => println!("Sorry, your contact details could not be updated. {}.", err.as_str()),
+        }
+    }
+
+    fn profile_change_user_id(self: &mut Self) {
+        print!("New user ID: ");
+        let _ = std::io::stdout().flush();
+        let new_user_id = get_user_input_as_str();
+        if !self.step_up_verification() {
+            return;
+        }
+        let owner = self.bank_account_for_session().borrow().id;
+        match self.database.change_user_id(owner, new_user_id) {
+            Ok(()) => println!("Your user ID has been changed. Please use it next time you log in."),
+            Err(err) => println!("Sorry, your user ID could not be changed. {}.", err.as_str()),
+        }
+    }
+
+    fn profile_two_factor(self: &mut Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        if !self.database.two_factor_enabled(owner) {
+            if get_user_confirmation("Two-factor authentication is off. Set it up now? (y/n): ") {
+                self.two_factor_enroll(owner);
+            }
+            return;
+        }
+        loop {
+            println!("Two-factor authentication is on ({} recovery codes left).", self.database.remaining_recovery_codes(owner));
+            println!("1. Generate new recovery codes");
+            println!("2. Turn off two-factor authentication");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = get_user_input_as_i32();
+            if user_input == 0 {
+                return;
+            } else if user_input != 1 && user_input != 2 {
+                println!("Invalid input. Please try again.");
+                continue;
+            }
+            let code = get_user_input_as_secret("Authentication code (or a recovery code): ");
+            if user_input == 1 {
+                match self.database.regenerate_recovery_codes(owner, code.expose()) {
+                    Ok(recovery_codes) => print_recovery_codes(&recovery_codes),
+                    Err(err) => println!("Sorry, new recovery codes could not be generated. {}.", err.as_str()),
+                }
+            } else {
+                match self.database.disable_two_factor(owner, code.expose()) {
+                    Ok(()) => println!("Two-factor authentication has been turned off."),
+                    Err(err) => println!("Sorry, two-factor authentication could not be turned off. {}.", err.as_str()),
+                }
+            }
+            return;
+        }
+    }
+
+    fn two_factor_enroll(self: &mut Self, owner: BankAccountID) {
+        let enrollment = match self.database.begin_two_factor_enrollment(owner) {
+            Ok(enrollment) => enrollment,
+            Err(err) => {
+                println!("Sorry, two-factor authentication could not be set up. {}.", err.as_str());
+                return;
+            },
+        };
+        println!("Add this account to your authenticator app, either with the link or by entering the key.");
+        println!("Link: {}", enrollment.uri.expose());
+        println!("Key: {}", enrollment.secret.expose());
+        let code = get_user_input_as_secret("Enter the code your app shows to confirm: ");
+        match self.database.confirm_two_factor_enrollment(owner, code.expose()) {
+            Ok(recovery_codes) => {
+                println!("Two-factor authentication is now on. You will be asked for a code when you log in.");
+                print_recovery_codes(&recovery_codes);
+            },
+            Err(err) => println!("Sorry, two-factor authentication could not be set up. {}.", err.as_str()),
+        }
+    }
+
+    // Logins, credential and profile changes from the last 30 days. Balance changes are in the account history.
+    fn profile_security_activity(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let events: Vec<_> = self.database.get_recent_audit_events(owner, 30).into_iter()
+            .filter(|event| event.action != AuditAction::BalanceChange)
+            .collect();
+        if events.is_empty() {
+            println!("No security activity in the last 30 days.");
+        }
+        for event in events {
+            let detail = if event.detail.is_empty() { String::new() } else { format!(" - {}", event.detail) };
+            println!("{} {} via {}: {}{}", event.at.format("%Y-%m-%d %H:%M"), event.action.description(), event.channel.description(), event.outcome.description(), detail);
+        }
+    }
+
+    fn profile_history(self: &Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let history = self.database.get_profile_history(owner);
+        if history.is_empty() {
+            println!("Your profile has not been changed.");
+        }
+        for change in history {
+            match (&change.old_value, &change.new_value) {
+                (Some(old_value), Some(new_value)) => println!("{} {} changed from \"{}\" to \"{}\"", change.changed_at.format("%Y-%m-%d %H:%M"), change.field.description(), old_value, new_value),
+                _ => println!("{} {} changed", change.changed_at.format("%Y-%m-%d %H:%M"), change.field.description()),
+            }
+        }
+    }
+
+    // Money leaving an account above the bank's threshold, or a guardian's limit, waits for
+    // approval. True if it can go ahead now, otherwise it has been submitted or dropped.
+    fn outflow_approved(self: &mut Self, owner: BankAccountID, operation: ApprovalOperation) -> bool {
+        if let Some(user) = self.business_user {
+            return self.business_payment_authorized(owner, user, operation);
+        }
+        let reviewer = match self.database.outflow_reviewer(operation.money_account(), -operation.amount()) {
+            Some(reviewer) => reviewer,
+            None => return true,
+        };
+        let approver = match reviewer {
+            Reviewer::Bank => "the bank",
+            _ if self.database.outflow_needs_approval(operation.money_account(), -operation.amount()) => "your guardian and then the bank",
+            _ => "your guardian",
+        };
+        println!("Amounts of this size have to be approved by {} before the money is released.", approver);
+        if !get_user_confirmation("Submit it for approval? (y/n): ") {
+            return false;
+        }
+        let result = match operation {
+            ApprovalOperation::Withdrawal { id, amount } => self.database.request_withdrawal(owner, id, amount).map_err(|err| err.as_str()),
+            ApprovalOperation::BillPayment { from, payee, amount, reference, .. } => self.database.request_bill_payment(owner, from, payee, amount, reference).map_err(|err| err.as_str()),
+            ApprovalOperation::StandingOrder { from, to, amount, frequency, start, .. } => self.database.request_standing_order(owner, from, to, amount, frequency, start).map_err(|err| err.as_str()),
+            _ => Err(errors::ApprovalError::PermissionDenied.as_str()),
+        };
+        match result {
+            Ok(id) => println!("Request {} submitted. You will be notified once it has been reviewed.", id),
+            Err(err) => println!("Sorry, {}.", err.to_lowercase()),
+        }
+        return false;
+    }
+
+    // Business users pay up to their spending limit. Above it, or from the step-up amount on, the
+    // payment waits for another user who may approve it, and then for the bank if its threshold
+    // applies too. The request is recorded as the user's. True if the payment can go ahead now.
+    fn business_payment_authorized(self: &mut Self, owner: BankAccountID, user: BusinessUserID, operation: ApprovalOperation) -> bool {
+        match self.database.authorize_business_payment(owner, user, &operation) {
+            Ok(()) => {
+                if self.database.outflow_reviewer(operation.money_account(), -operation.amount()).is_none() {
+                    return true;
+                }
+                println!("Amounts of this size have to be approved by the bank before the money is released.");
+            },
+            Err(errors::BusinessError::ApprovalRequired) => println!("This payment has to be approved by another user before the money is released."),
+            Err(err) => {
+                println!("Sorry, {}.", err.as_str().to_lowercase());
+                return false;
+            },
+        }
+        if get_user_confirmation("Submit it for approval? (y/n): ") {
+            match self.database.request_business_approval(owner, user, operation) {
+                Ok(id) => println!("Request {} submitted.", id),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+        return false;
+    }
+
+    fn action_manage_users(self: &mut Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        loop {
+            println!("Business users:");
+            println!("1. List users");
+            println!("2. Add user");
+            println!("3. Change permissions");
+            println!("4. Remove user");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.business_user_list(),
+                2 => {
+                    print!("User ID: ");
+                    let _ = std::io::stdout().flush();
+                    let user_id = get_user_input_as_str();
+                    print!("Name: ");
+                    let _ = std::io::stdout().flush();
+                    let name = get_user_input_as_str();
+                    let (permission, spending_limit) = self.choose_business_access();
+                    match self.database.add_business_user(owner, user_id, name, permission, spending_limit) {
+                        Ok((id, temporary_password)) => println!("User {} added. Their temporary password is {}\nThey will choose their own when they first log in.", id, temporary_password.expose()),
+                        Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                    }
+                },
+                3 => {
+                    print!("User number: ");
+                    let _ = std::io::stdout().flush();
+                    let user = get_user_input_as_i32() as BusinessUserID;
+                    let (permission, spending_limit) = self.choose_business_access();
+                    match self.database.update_business_user(owner, user, permission, spending_limit) {
+                        Ok(()) => println!("User {} updated.", user),
+                        Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                    }
+                },
+                4 => {
+                    print!("User number: ");
+                    let _ = std::io::stdout().flush();
+                    let user = get_user_input_as_i32() as BusinessUserID;
+                    match self.database.deactivate_business_user(owner, user) {
+                        Ok(()) => println!("User {} removed.", user),
+                        Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+                    }
+                },
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn business_user_list(self: &Self) {
+        let ptr_bank_account = self.bank_account_for_session();
+        let bank_account = ptr_bank_account.borrow();
+        if bank_account.business_users.is_empty() {
+            println!("No users yet.");
+        }
+        let currency = bank_account.accounts.first().map(|acc| acc.borrow().currency);
+        for business_user in &bank_account.business_users {
+            let limit = match (business_user.spending_limit, currency) {
+                (Some(limit), Some(currency)) => format!("limit {}", currency.format(limit)),
+                (Some(limit), None) => format!("limit {}", limit),
+                (None, _) => String::from("no limit"),
+            };
+            let active = if business_user.active { "" } else { " (removed)" };
+            println!("{} {} \"{}\" {}, {}{}", business_user.id, business_user.name, business_user.user_id, business_user.permission.description(), limit, active);
+        }
+    }
+
+    // Limits are entered in the currency of the business's first account.
+    fn choose_business_access(self: &Self) -> (BusinessPermission, Option<i32>) {
+        for (i, permission) in BUSINESS_PERMISSIONS.iter().enumerate() {
+            println!("{}. {}", i+1, permission.description());
+        }
+        let permission = loop {
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let user_input = get_user_input_as_i32();
+            if user_input >= 1 && user_input <= (BUSINESS_PERMISSIONS.len() as i32) {
+                break BUSINESS_PERMISSIONS[(user_input - 1) as usize];
+            }
+            println!("Invalid input. Please try again.");
+        };
+        if permission == BusinessPermission::ViewOnly || !get_user_confirmation("Set a spending limit per payment? (y/n): ") {
+            return (permission, None);
+        }
+        let currency = self.bank_account_for_session().borrow().accounts.first().map(|acc| acc.borrow().currency).unwrap_or(Currency::Usd);
+        print!("Spending limit: ");
+        let _ = std::io::stdout().flush();
+        return (permission, Some(get_user_input_as_amount(currency)));
+    }
+
+    fn action_payment_approvals(self: &mut Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        loop {
+            let pending: Vec<ApprovalID> = self.database.get_business_pending_approvals(owner).iter().map(|request| request.id).collect();
+            if pending.is_empty() {
+                println!("No payments are waiting for approval.");
+                return;
+            }
+            for id in &pending {
+                let request = self.database.get_approval_request(*id).unwrap();
+                let ptr_money_account = self.database.get_money_account_with_id_u32(request.operation.money_account()).unwrap();
+                let amount = ptr_money_account.borrow().format_amount(request.operation.amount().abs());
+                println!("{}. {}, {} by {} on {}", request.id, request.operation.description(), amount, request.requested_by.description(), request.requested_at.format("%Y-%m-%d %H:%M"));
+            }
+            print!("Request number to decide (0 to go back): ");
+            let _ = std::io::stdout().flush();
+            let id = get_user_input_as_i32() as ApprovalID;
+            if id == 0 {
+                return;
+            }
+            if !pending.contains(&id) {
+                println!("Invalid input. Please try again.");
+                continue;
+            }
+            println!("1. Approve");
+            println!("2. Reject");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let result = match get_user_input_as_i32() {
+                1 => {
+                    let operation = self.database.get_approval_request(id).unwrap().operation.clone();
+                    let currency = self.database.get_money_account_with_id_u32(operation.money_account()).unwrap().borrow().currency;
+                    if !self.step_up_for_amount(operation.amount().abs(), currency) {
+                        continue;
+                    }
+                    self.database.approve_business_request(owner, self.business_user, id)
+                },
+                2 => {
+                    print!("Reason: ");
+                    let _ = std::io::stdout().flush();
+                    let reason = get_user_input_as_str();
+                    self.database.reject_business_request(owner, self.business_user, id, &reason)
+                },
+                _ => continue,
+            };
+            match result {
+                Ok(()) => println!("Request {} is now {}.", id, self.database.get_approval_request(id).unwrap().status.description().to_lowercase()),
+                Err(errors::ApprovalError::Failed) => println!("Sorry, {}: {}.", errors::ApprovalError::Failed.as_str().to_lowercase(), self.database.get_approval_request(id).unwrap().note.clone().unwrap_or_default()),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+    }
+
+    fn action_children(self: &mut Self) {
+        loop {
+            println!("Children's accounts:");
+            println!("1. Open an account for a child");
+            println!("2. View a child's accounts");
+            println!("3. Review withdrawal requests");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            match get_user_input_as_i32() {
+                1 => self.child_open_account(),
+                2 => self.child_view_accounts(),
+                3 => self.child_review_requests(),
+                0 => return,
+                _ => println!("Invalid input. Please try again."),
+            }
+        }
+    }
+
+    fn child_open_account(self: &mut Self) {
+        let guardian = self.bank_account_for_session().borrow().id;
+        let policy = self.database.get_guardian_policy();
+        let currency = self.bank_account_for_session().borrow().accounts.first().map(|acc| acc.borrow().currency).unwrap_or(Currency::Usd);
+        println!("Your child gets their own login. Payments and withdrawals above {} need your approval until they come of age.", currency.format(policy.approval_limit.get(currency)));
+        println!("Please enter your child's details...");
+        let user_id = loop {
+            print!("User ID: ");
+            let _ = std::io::stdout().flush();
+            let user_id = get_user_input_as_str();
+            if !self.database.user_id_in_use(&user_id) {
+                break user_id;
+            }
+            println!("That user ID is already in use, please enter another.")
+        };
+        let password = loop {
+            let password = get_user_input_as_secret("Password: ");
+            if self.password_meets_policy(None, password.expose()) {
+                break password;
+            }
+        };
+        let name = get_user_input_validated("Name: ", |name| !name.is_empty(), errors::OnboardingError::MissingName);
+        let dob = get_user_input_as_date("Date of Birth (YYYY-MM-DD): ");
+        let email = get_user_input_validated("Email: ", onboarding::is_valid_email, errors::OnboardingError::InvalidEmail);
+        let phone = get_user_input_validated("Phone: ", onboarding::is_valid_phone, errors::OnboardingError::InvalidPhone);
+        let address = get_user_input_validated("Address: ", |address| !address.is_empty(), errors::OnboardingError::MissingAddress);
+        let id_document = get_user_input_validated("Passport or national ID number: ", onboarding::is_valid_id_document, errors::OnboardingError::InvalidIdDocument);
+
+        let application = Application {
+            user_id: user_id,
+            password: password,
+            name: name,
+            dob: dob,
+            contact: ContactDetails { email: email, phone: phone, address: address },
+            id_document: id_document,
+            business: None,
+        };
+        match self.database.register_minor(guardian, application) {
+            Ok(acc) => println!("Account created! Your child's account number is {}. They can now log in and open accounts.", acc.borrow().id),
+            Err(err) => println!("Sorry, the account could not be created. {}.", err.as_str()),
+        }
+    }
+
+    fn child_view_accounts(self: &mut Self) {
+        let guardian = self.bank_account_for_session().borrow().id;
+        let wards = self.database.get_wards(guardian);
+        if wards.is_empty() {
+            println!("You have not opened an account for a child yet.");
+            return;
+        }
+        let mut money_account_ids: Vec<MoneyAccountID> = vec![];
+        for ptr_bank_account in &wards {
+            let bank_account = ptr_bank_account.borrow();
+            println!("{} ({}), born {}:", bank_account.name, bank_account.id, bank_account.dob);
+            if bank_account.accounts.is_empty() {
+                println!("  No accounts.");
+            }
+            for ptr_money_account in &bank_account.accounts {
+                let money_account = ptr_money_account.borrow();
+                money_account_ids.push(money_account.id);
+                println!("  {} {} \"{}\" {}", money_account.id, money_account.get_str(), money_account.name, money_account.format_amount(money_account.balance));
+            }
+        }
+        if money_account_ids.is_empty() {
+            return;
+        }
+        print!("Account number to show history for (0 to go back): ");
+        let _ = std::io::stdout().flush();
+        let id = get_user_input_as_i32() as MoneyAccountID;
+        if id == 0 {
+            return;
+        }
+        match self.database.guardian_view_account(guardian, id) {
+            Ok(ptr_money_account) => self.print_history(&ptr_money_account.borrow()),
+            Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+        }
+    }
+
+    fn child_review_requests(self: &mut Self) {
+        let guardian = self.bank_account_for_session().borrow().id;
+        loop {
+            let pending: Vec<ApprovalID> = self.database.get_guardian_pending_approvals(guardian).iter().map(|request| request.id).collect();
+            if pending.is_empty() {
+                println!("No requests are waiting for your approval.");
+                return;
+            }
+            for id in &pending {
+                let request = self.database.get_approval_request(*id).unwrap();
+                let ptr_money_account = self.database.get_money_account_with_id_u32(request.operation.money_account()).unwrap();
+                let amount = ptr_money_account.borrow().format_amount(request.operation.amount().abs());
+                println!("{}. {}, {} by {} on {}", request.id, request.operation.description(), amount, request.requested_by.description(), request.requested_at.format("%Y-%m-%d %H:%M"));
+            }
+            print!("Request number to decide (0 to go back): ");
+            let _ = std::io::stdout().flush();
+            let id = get_user_input_as_i32() as ApprovalID;
+            if id == 0 {
+                return;
+            }
+            if !pending.contains(&id) {
+                println!("Invalid input. Please try again.");
+                continue;
+            }
+            println!("1. Approve");
+            println!("2. Reject");
+            println!("0. Back");
+            print!("Enter a number: ");
+            let _ = std::io::stdout().flush();
+            let result = match get_user_input_as_i32() {
+                1 => self.database.approve_guardian_request(guardian, id),
+                2 => {
+                    print!("Reason: ");
+                    let _ = std::io::stdout().flush();
+                    let reason = get_user_input_as_str();
+                    self.database.reject_guardian_request(guardian, id, &reason)
+                },
+                _ => continue,
+            };
+            match result {
+                Ok(()) => println!("Request {} is now {}.", id, self.database.get_approval_request(id).unwrap().status.description().to_lowercase()),
+                Err(errors::ApprovalError::Failed) => println!("Sorry, {}: {}.", errors::ApprovalError::Failed.as_str().to_lowercase(), self.database.get_approval_request(id).unwrap().note.clone().unwrap_or_default()),
+                Err(err) => println!("Sorry, {}.", err.as_str().to_lowercase()),
+            }
+        }
+    }
+
+    fn action_change_business_user_password(self: &mut Self) {
+        let owner = self.bank_account_for_session().borrow().id;
+        let user = self.business_user.unwrap();
+        let current_password = get_user_input_as_secret("Current password: ");
+        let new_password = get_user_input_as_secret("New password: ");
+        if !self.password_meets_policy(None, new_password.expose()) {
+            return;
+        }
+        if get_user_input_as_secret("Confirm new password: ") != new_password {
+            println!("Sorry, the new passwords do not match.");
+            return;
+        }
+        match self.database.change_business_user_password(owner, user, &current_password, new_password) {
+            Ok(()) => println!("Your password has been changed."),
+            Err(err) => println!("Sorry, your password could not be changed. {}.", err.as_str()),
+        }
+    }
+
+    // Business users only see what their permission allows.
+    fn get_valid_business_user_actions(self: &Self, acc: Rc<RefCell<BankAccount>>, user: BusinessUserID) -> Vec<UserActions> {
+        let mut supported_user_actions: Vec<UserActions> = vec![];
+        let bank_account = acc.borrow();
+        let permission = bank_account.business_user(user).map(|business_user| business_user.permission).unwrap_or(BusinessPermission::ViewOnly);
+        if bank_account.onboarding_status == OnboardingStatus::Verified && !bank_account.accounts.is_empty() {
+            if permission >= BusinessPermission::InitiatePayments {
+                supported_user_actions.push(UserActions::Deposit);
+            }
+            supported_user_actions.push(UserActions::ShowBalance);
+            supported_user_actions.push(UserActions::ShowHistory);
+            supported_user_actions.push(UserActions::Statements);
+            supported_user_actions.push(UserActions::ExportTransactions);
+            if permission >= BusinessPermission::InitiatePayments {
+                supported_user_actions.push(UserActions::PayBill);
+                if bank_account.accounts.iter().any(|acc| acc.borrow().balance > 0) {
+                    supported_user_actions.push(UserActions::Withdraw);
+                }
+            }
+            if permission >= BusinessPermission::ApprovePayments {
+                supported_user_actions.push(UserActions::PaymentApprovals);
+            }
+        }
+        supported_user_actions.push(UserActions::ChangePassword);
+        supported_user_actions.push(UserActions::Logout);
+        return supported_user_actions;
+    }
+
+    fn get_valid_actions(self: &Self, acc: Rc<RefCell<BankAccount>>) -> Vec<UserActions> {
+        if let Some(user) = self.business_user {
+            return self.get_valid_business_user_actions(acc, user);
+        }
+        let mut supported_user_actions: Vec<UserActions> = vec![];
+        if acc.borrow().onboarding_status != OnboardingStatus::Verified {
+            supported_user_actions.push(UserActions::Profile);
+            supported_user_actions.push(UserActions::Logout);
+            return supported_user_actions;
+        }
+        
+        let mut has_checkings_acc: bool = false;
+        let mut has_savings_acc: bool = false;
+        let mut has_positive_balance: bool = false;
+
+        // Check what accounts exist
+        for ptr_acc in &acc.borrow().accounts {
+            let money_acc = ptr_acc.borrow();
+            match money_acc.account_type  {
+                MoneyAccountType::Checking => has_checkings_acc = true,
+                MoneyAccountType::Savings => has_savings_acc = true,
+                _ => (),
+            }
+            if money_acc.balance > 0 {
+                has_positive_balance = true;
+            }
+        }
+    
+        if has_savings_acc || has_checkings_acc {
+            supported_user_actions.push(UserActions::Deposit);
+            supported_user_actions.push(UserActions::ShowBalance);
+            supported_user_actions.push(UserActions::ShowHistory);
+            supported_user_actions.push(UserActions::Statements);
+            supported_user_actions.push(UserActions::ExportTransactions);
+            supported_user_actions.push(UserActions::StandingOrders);
+            supported_user_actions.push(UserActions::PayBill);
+            supported_user_actions.push(UserActions::ManagePayees);
+            if has_checkings_acc {
+                supported_user_actions.push(UserActions::TermDeposits);
+                // no borrowing until they come of age
+                if acc.borrow().guardian.is_none() {
+                    supported_user_actions.push(UserActions::Loans);
+                    supported_user_actions.push(UserActions::CreditCards);
+                }
+            }
+            if has_positive_balance {
+                supported_user_actions.push(UserActions::Withdraw);
+            }
+        }
+        if !has_checkings_acc {
+            supported_user_actions.push(UserActions::CreateCheckings);
+        }
+        if !has_savings_acc {
             supported_user_actions.push(UserActions::CreateSavings);
         }
+        if acc.borrow().business.is_some() {
+            supported_user_actions.push(UserActions::ManageUsers);
+            supported_user_actions.push(UserActions::PaymentApprovals);
+        } else if acc.borrow().guardian.is_none() {
+            supported_user_actions.push(UserActions::Children);
+        }
+        supported_user_actions.push(UserActions::Profile);
         supported_user_actions.push(UserActions::Logout);
         //supported_user_actions.push(UserActions::Exit);
         return supported_user_actions;
     }
 }
 
+impl Default for Session {
+    fn default() -> Self {
+        return Session::new();
+    }
+}
+
 fn get_user_input_as_i32() -> i32 {
     let mut user_input_buffer = String::new();
     loop {
@@ -340,11 +2613,281 @@ fn get_user_input_as_i32() -> i32 {
     }
 }
 
+fn choose_deposit_channel() -> DepositChannel {
+    let channels = [DepositChannel::Cash, DepositChannel::Cheque, DepositChannel::Transfer];
+    println!("How is this deposit being made?");
+    for (i, channel) in channels.iter().enumerate() {
+        println!("{}. {}", i+1, channel.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (channels.len() as i32) {
+            return channels[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+// The reasons staff can give. Rejected payments and waived fees are reversed by the bank itself.
+fn choose_reason_code() -> ReasonCode {
+    let reasons = [ReasonCode::CustomerRequest, ReasonCode::DuplicateEntry, ReasonCode::ProcessingError, ReasonCode::Fraud, ReasonCode::Refund];
+    println!("Why is it being reversed?");
+    for (i, reason) in reasons.iter().enumerate() {
+        println!("{}. {}", i+1, reason.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (reasons.len() as i32) {
+            return reasons[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn choose_export_format() -> ExportFormat {
+    println!("Which format?");
+    for (i, format) in EXPORT_FORMATS.iter().enumerate() {
+        println!("{}. {}", i + 1, format.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input > 0 && user_input <= EXPORT_FORMATS.len() as i32 {
+            return EXPORT_FORMATS[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn choose_statement_format() -> StatementFormat {
+    println!("Which format?");
+    for (i, format) in STATEMENT_FORMATS.iter().enumerate() {
+        println!("{}. {}", i + 1, format.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input > 0 && user_input <= STATEMENT_FORMATS.len() as i32 {
+            return STATEMENT_FORMATS[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn choose_frequency() -> Frequency {
+    println!("How often should this transfer run?");
+    println!("1. Once");
+    println!("2. Weekly");
+    println!("3. Monthly on a set day");
+    println!("4. Monthly on the last business day");
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        match get_user_input_as_i32() {
+            1 => return Frequency::Once,
+            2 => return Frequency::Weekly,
+            3 => loop {
+                print!("Day of the month (1-31): ");
+                let _ = std::io::stdout().flush();
+                let day = get_user_input_as_i32();
+                if (1..=31).contains(&day) {
+                    return Frequency::MonthlyOnDay(day as u32);
+                }
+                println!("Invalid input. Please try again.");
+            },
+            4 => return Frequency::LastBusinessDay,
+            _ => println!("Invalid input. Please try again."),
+        }
+    }
+}
+
+// Shows the fees an operation will incur and asks to go ahead. No fees, no question.
+fn confirm_fees(fees: &[FeeQuote], currency: Currency) -> bool {
+    if fees.is_empty() {
+        return true;
+    }
+    println!("This transaction will incur the following fees:");
+    for fee in fees {
+        println!("  {}: {}", fee.memo, currency.format(fee.amount));
+    }
+    return get_user_confirmation("Continue? (y/n): ");
+}
+
+fn get_user_confirmation(prompt: &str) -> bool {
+    loop {
+        print!("{}", prompt);
+        let _ = std::io::stdout().flush();
+        match get_user_input_as_str().to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Invalid input. Please try again."),
+        }
+    }
+}
+
+// Reports the first broken link in the hash chains and checkpoints, if any.
+fn print_integrity_report(report: &IntegrityReport) {
+    println!("Checked {} ledger entries, {} audit events and {} checkpoints.", report.ledger_entries, report.audit_events, report.checkpoints);
+    if !report.signing_key_configured {
+        println!("No signing key is configured, so no checkpoints are being taken.");
+    }
+    match &report.first_broken_link {
+        None => println!("All records are intact."),
+        Some(link) => {
+            match link.money_account {
+                Some(id) => println!("First broken link: account {}. {}.", id, link.reason.description()),
+                None => println!("First broken link: {} entry {}. {}.", link.chain.description(), link.position, link.reason.description()),
+            }
+        },
+    }
+}
+
+fn print_recovery_codes(recovery_codes: &[SecretString]) {
+    println!("Your recovery codes, each of which works once if you lose your device:");
+    for recovery_code in recovery_codes {
+        println!("  {}", recovery_code.expose());
+    }
+    println!("Please keep them somewhere safe, they will not be shown again.");
+}
+
+fn choose_interest_payment() -> InterestPayment {
+    let options = [InterestPayment::AtMaturity, InterestPayment::Monthly];
+    println!("When should interest be paid?");
+    for (i, option) in options.iter().enumerate() {
+        println!("{}. {}", i+1, option.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (options.len() as i32) {
+            return options[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn choose_maturity_instruction() -> MaturityInstruction {
+    let options = [MaturityInstruction::PayOut, MaturityInstruction::Rollover];
+    println!("What should happen at maturity?");
+    for (i, option) in options.iter().enumerate() {
+        println!("{}. {}", i+1, option.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (options.len() as i32) {
+            return options[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn choose_amortization_method() -> AmortizationMethod {
+    let options = [AmortizationMethod::Annuity, AmortizationMethod::StraightLine];
+    println!("How should the loan be repaid?");
+    for (i, option) in options.iter().enumerate() {
+        println!("{}. {}", i+1, option.description());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (options.len() as i32) {
+            return options[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+fn print_loan_schedule(installments: &[Installment], currency: Currency) {
+    println!("{:>3}  {:<10}  {:>12}  {:>12}  {:>12}  {:>12}  Status", "#", "Due", "Payment", "Principal", "Interest", "Balance");
+    for installment in installments {
+        println!("{:>3}  {:<10}  {:>12}  {:>12}  {:>12}  {:>12}  {}", installment.number, installment.due_date, currency.format(installment.total()),
+            currency.format(installment.principal), currency.format(installment.interest), currency.format(installment.balance_after), installment.status.description());
+    }
+}
+
+fn choose_currency() -> Currency {
+    println!("Which currency should this account hold?");
+    for (i, currency) in SUPPORTED_CURRENCIES.iter().enumerate() {
+        println!("{}. {} ({})", i+1, currency.code(), currency.symbol().trim());
+    }
+    loop {
+        print!("Enter a number: ");
+        let _ = std::io::stdout().flush();
+        let user_input = get_user_input_as_i32();
+        if user_input >= 1 && user_input <= (SUPPORTED_CURRENCIES.len() as i32) {
+            return SUPPORTED_CURRENCIES[(user_input - 1) as usize];
+        }
+        println!("Invalid input. Please try again.");
+    }
+}
+
+// Reads an amount such as "12.50" in the currency's minor units.
+fn get_user_input_as_amount(currency: Currency) -> i32 {
+    loop {
+        match currency.parse_amount(&get_user_input_as_str()) {
+            Some(amount) => return amount,
+            None => {
+                print!("Invalid amount! Please enter a value like {}: ", currency.format(10 * 10_i32.pow(currency.minor_units())));
+                let _ = std::io::stdout().flush();
+            },
+        }
+    }
+}
+
+// Asks until the answer passes `is_valid`, explaining what is wrong with it each time.
+fn get_user_input_validated(prompt: &str, is_valid: fn(&str) -> bool, error: errors::OnboardingError) -> String {
+    loop {
+        print!("{}", prompt);
+        let _ = std::io::stdout().flush();
+        let input = get_user_input_as_str();
+        if is_valid(&input) {
+            return input;
+        }
+        println!("{}. Please try again.", error.as_str());
+    }
+}
+
+fn get_user_input_as_date(prompt: &str) -> NaiveDate {
+    loop {
+        print!("{}", prompt);
+        let _ = std::io::stdout().flush();
+        match NaiveDate::parse_from_str(&get_user_input_as_str(), "%Y-%m-%d") {
+            Ok(date) => return date,
+            Err(_) => println!("Invalid date! Please enter in the format specified."),
+        }
+    }
+}
+
 fn get_user_input_as_str() -> String {
     let mut user_input_buffer = String::new();
-    loop {
-        user_input_buffer.clear();
-        let _ = std::io::stdin().read_line(&mut user_input_buffer).unwrap();
-        return String::from(user_input_buffer.trim());
+    let _ = std::io::stdin().read_line(&mut user_input_buffer).unwrap();
+    return String::from(user_input_buffer.trim());
+}
+
+// Passwords and codes. Not echoed when typed at a terminal; piped input is read like any other line.
+fn get_user_input_as_secret(prompt: &str) -> SecretString {
+    if std::io::stdin().is_terminal() {
+        if let Ok(mut input) = rpassword::prompt_password(prompt) {
+            let secret = SecretString::from(input.trim());
+            input.zeroize();
+            return secret;
+        }
     }
+    print!("{}", prompt);
+    let _ = std::io::stdout().flush();
+    let mut user_input_buffer = String::new();
+    let _ = std::io::stdin().read_line(&mut user_input_buffer).unwrap();
+    let secret = SecretString::from(user_input_buffer.trim());
+    user_input_buffer.zeroize();
+    return secret;
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+use crate::bomanis_bank::passwords::PasswordHash;
+
+// Type Aliases
+pub type StaffID = u32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StaffRole {
+    Teller,
+    Supervisor,
+    Admin,
+}
+
+impl StaffRole {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            StaffRole::Teller => "Teller",
+            StaffRole::Supervisor => "Supervisor",
+            StaffRole::Admin => "Admin",
+        }
+    }
+
+    // Each role can do everything the one below it can.
+    pub fn permissions(self: &Self) -> &'static [StaffPermission] {
+        match self {
+            StaffRole::Teller => &[
+                StaffPermission::SearchCustomers,
+                StaffPermission::ViewAccounts,
+                StaffPermission::LockAccounts,
+            ],
+            StaffRole::Supervisor => &[
+                StaffPermission::SearchCustomers,
+                StaffPermission::ViewAccounts,
+                StaffPermission::LockAccounts,
+                StaffPermission::UnlockAccounts,
+                StaffPermission::PostAdjustments,
+                StaffPermission::ResetPasswords,
+                StaffPermission::ReviewIdentity,
+                StaffPermission::ApproveRequests,
+            ],
+            StaffRole::Admin => &[
+                StaffPermission::SearchCustomers,
+                StaffPermission::ViewAccounts,
+                StaffPermission::LockAccounts,
+                StaffPermission::UnlockAccounts,
+                StaffPermission::PostAdjustments,
+                StaffPermission::ResetPasswords,
+                StaffPermission::ReviewIdentity,
+                StaffPermission::ApproveRequests,
+                StaffPermission::ManageStaff,
+                StaffPermission::VerifyIntegrity,
+                StaffPermission::ExportAudit,
+            ],
+        }
+    }
+
+    pub fn has_permission(self: &Self, permission: StaffPermission) -> bool {
+        return self.permissions().contains(&permission);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StaffPermission {
+    SearchCustomers,
+    ViewAccounts,
+    LockAccounts, // tellers can freeze an account on suspicion, only supervisors can release it
+    UnlockAccounts,
+    PostAdjustments,
+    ResetPasswords,
+    ReviewIdentity,
+    ApproveRequests,
+    ManageStaff,
+    VerifyIntegrity,
+    ExportAudit,
+}
+
+pub struct StaffMember {
+    pub id: StaffID,
+    pub user_id: String,
+    pub name: String,
+    pub password: PasswordHash,
+    pub role: StaffRole,
+    pub active: bool, // deactivated staff keep their record for the audit trail
+    pub created_by: Option<StaffID>, // None for an admin provisioned from the configuration
+}
+
+// What the staff console offers, filtered by the staff member's permissions.
+#[derive(Copy, Clone, PartialEq)]
+pub enum StaffActions {
+    SearchCustomers,
+    ViewCustomer,
+    ViewHistory,
+    PostAdjustment,
+    ReturnCheque,
+    ReverseTransaction,
+    WaiveFee,
+    LockAccount,
+    UnlockAccount,
+    ResetPassword,
+    ReviewIdentity,
+    ReviewApprovals,
+    ManageStaff,
+    VerifyIntegrity,
+    ExportAudit,
+    Logout,
+}
+
+impl StaffActions {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            StaffActions::SearchCustomers => "Search customers",
+            StaffActions::ViewCustomer => "View customer",
+            StaffActions::ViewHistory => "View account history",
+            StaffActions::PostAdjustment => "Post manual adjustment",
+            StaffActions::ReturnCheque => "Return cheque deposit",
+            StaffActions::ReverseTransaction => "Reverse transaction",
+            StaffActions::WaiveFee => "Waive fee",
+            StaffActions::LockAccount => "Lock account",
+            StaffActions::UnlockAccount => "Unlock account",
+            StaffActions::ResetPassword => "Reset customer password",
+            StaffActions::ReviewIdentity => "Settle identity review",
+            StaffActions::ReviewApprovals => "Review pending approvals",
+            StaffActions::ManageStaff => "Manage staff",
+            StaffActions::VerifyIntegrity => "Verify ledger integrity",
+            StaffActions::ExportAudit => "Export audit log",
+            StaffActions::Logout => "Logout",
+        }
+    }
+
+    pub fn required_permission(self: &Self) -> Option<StaffPermission> {
+        match self {
+            StaffActions::SearchCustomers => Some(StaffPermission::SearchCustomers),
+            StaffActions::ViewCustomer | StaffActions::ViewHistory => Some(StaffPermission::ViewAccounts),
+            StaffActions::PostAdjustment | StaffActions::ReturnCheque | StaffActions::ReverseTransaction | StaffActions::WaiveFee => Some(StaffPermission::PostAdjustments),
+            StaffActions::LockAccount => Some(StaffPermission::LockAccounts),
+            StaffActions::UnlockAccount => Some(StaffPermission::UnlockAccounts),
+            StaffActions::ResetPassword => Some(StaffPermission::ResetPasswords),
+            StaffActions::ReviewIdentity => Some(StaffPermission::ReviewIdentity),
+            StaffActions::ReviewApprovals => Some(StaffPermission::ApproveRequests),
+            StaffActions::ManageStaff => Some(StaffPermission::ManageStaff),
+            StaffActions::VerifyIntegrity => Some(StaffPermission::VerifyIntegrity),
+            StaffActions::ExportAudit => Some(StaffPermission::ExportAudit),
+            StaffActions::Logout => None,
+        }
+    }
+}
+
+pub const STAFF_ACTIONS: [StaffActions; 16] = [
+    StaffActions::SearchCustomers,
+    StaffActions::ViewCustomer,
+    StaffActions::ViewHistory,
+    StaffActions::PostAdjustment,
+    StaffActions::ReturnCheque,
+    StaffActions::ReverseTransaction,
+    StaffActions::WaiveFee,
+    StaffActions::LockAccount,
+    StaffActions::UnlockAccount,
+    StaffActions::ResetPassword,
+    StaffActions::ReviewIdentity,
+    StaffActions::ReviewApprovals,
+    StaffActions::ManageStaff,
+    StaffActions::VerifyIntegrity,
+    StaffActions::ExportAudit,
+    StaffActions::Logout,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::{Duration, NaiveDate};
+    use crate::bomanis_bank::audit::AuditActor;
+    use crate::bomanis_bank::clock::{Clock, ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::StaffError;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    fn bank() -> (Rc<ManualClock>, DatabaseHandler) {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        return (clock, database);
+    }
+
+    #[test]
+    fn first_admin_is_provisioned_only_while_there_is_none() {
+        let (_, mut database) = bank();
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        assert_eq!(database.provision_admin(String::from("second"), String::from("Second"), SecretString::from(PASSWORD)), Err(StaffError::PermissionDenied));
+        assert!(database.get_audit_events(None, None, None).last().unwrap().actor == AuditActor::System);
+
+        let teller = database.create_staff(admin, String::from("teller"), String::from("Teller"), SecretString::from(PASSWORD), StaffRole::Teller).unwrap();
+        assert_eq!(database.create_staff(teller, String::from("other"), String::from("Other"), SecretString::from(PASSWORD), StaffRole::Admin), Err(StaffError::PermissionDenied));
+    }
+
+    #[test]
+    fn password_reset_is_kept_in_the_history() {
+        let (clock, mut database) = bank();
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from(PASSWORD),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let admin = database.provision_admin(String::from("admin"), String::from("Admin"), SecretString::from(PASSWORD)).unwrap();
+        clock.advance(Duration::days(100));
+        assert!(database.password_change_required(owner));
+
+        let temporary_password = database.reset_customer_password(admin, owner).unwrap();
+        let bank_account = ptr_bank_account.borrow();
+        assert_eq!(bank_account.password_history.len(), 2);
+        assert!(bank_account.password_history.last().unwrap().verify(temporary_password.expose()));
+        assert_eq!(bank_account.password_changed_at, Some(clock.now()));
+        assert!(bank_account.must_change_password);
+    }
+}
@@ -0,0 +1,276 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+use crate::bomanis_bank::audit;
+use crate::bomanis_bank::currency::Currency;
+use crate::bomanis_bank::ledger::{TransactionID, TransactionKind};
+
+// Type Aliases
+pub type StatementID = u32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StatementFormat {
+    Text,
+    Csv,
+    Html, // standalone page, styles inline
+}
+
+impl StatementFormat {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            StatementFormat::Text => "Plain text",
+            StatementFormat::Csv => "CSV",
+            StatementFormat::Html => "HTML",
+        }
+    }
+
+    pub fn extension(self: &Self) -> &'static str {
+        match self {
+            StatementFormat::Text => "txt",
+            StatementFormat::Csv => "csv",
+            StatementFormat::Html => "html",
+        }
+    }
+}
+
+pub const STATEMENT_FORMATS: [StatementFormat; 3] = [
+    StatementFormat::Text,
+    StatementFormat::Csv,
+    StatementFormat::Html,
+];
+
+// Who the statement is addressed to, as they were when it was issued.
+#[derive(Clone)]
+pub struct StatementHolder {
+    pub customer: BankAccountID,
+    pub name: String,
+    pub company: Option<String>, // business customers
+    pub address: String,
+    pub email: String,
+}
+
+#[derive(Clone)]
+pub struct StatementLine {
+    pub transaction: TransactionID,
+    pub posted_at: NaiveDateTime,
+    pub kind: TransactionKind,
+    pub memo: String,
+    pub amount: i32,
+    pub balance: i32, // running balance after this line
+}
+
+// One monthly cycle of a money account. Archived as issued, so fetching it again later gives
+// the same statement even if the customer's details have changed since.
+pub struct Statement {
+    pub id: StatementID,
+    pub money_account: MoneyAccountID,
+    pub account_name: String,
+    pub account_type: &'static str,
+    pub currency: Currency,
+    pub holder: StatementHolder,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate, // first day after the cycle
+    pub issued_at: NaiveDateTime,
+    pub opening_balance: i32,
+    pub closing_balance: i32,
+    pub lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    pub fn last_day(self: &Self) -> NaiveDate {
+        return self.period_end - Duration::days(1);
+    }
+
+    // e.g. "January 2026"
+    pub fn period_label(self: &Self) -> String {
+        return self.period_start.format("%B %Y").to_string();
+    }
+
+    pub fn money_in(self: &Self) -> i32 {
+        return self.lines.iter().filter(|line| line.amount > 0).map(|line| line.amount).sum();
+    }
+
+    pub fn money_out(self: &Self) -> i32 {
+        return self.lines.iter().filter(|line| line.amount < 0).map(|line| -line.amount).sum();
+    }
+
+    pub fn fees(self: &Self) -> i32 {
+        return self.lines.iter().filter(|line| line.kind == TransactionKind::Fee).map(|line| -line.amount).sum();
+    }
+
+    // Positive when paid to the customer, negative when charged.
+    pub fn interest(self: &Self) -> i32 {
+        return self.lines.iter().filter(|line| line.kind == TransactionKind::Interest).map(|line| line.amount).sum();
+    }
+
+    // e.g. "statement-123456-2026-01.csv"
+    pub fn file_name(self: &Self, format: StatementFormat) -> String {
+        return format!("statement-{}-{}.{}", self.money_account, self.period_start.format("%Y-%m"), format.extension());
+    }
+
+    pub fn render(self: &Self, format: StatementFormat) -> String {
+        match format {
+            StatementFormat::Text => return render_text(self),
+            StatementFormat::Csv => return render_csv(self),
+            StatementFormat::Html => return render_html(self),
+        }
+    }
+}
+
+fn render_text(statement: &Statement) -> String {
+    let currency = statement.currency;
+    let mut lines: Vec<String> = vec![];
+    lines.push(format!("Bomanis Bank statement, {}", statement.period_label()));
+    lines.push(format!("{} (customer {})", statement.holder.name, statement.holder.customer));
+    if let Some(company) = &statement.holder.company {
+        lines.push(company.clone());
+    }
+    lines.push(statement.holder.address.clone());
+    lines.push(format!("{} \"{}\" ({}), {}", statement.account_type, statement.account_name, statement.money_account, currency.code()));
+    lines.push(format!("Period: {} to {}", statement.period_start, statement.last_day()));
+    lines.push(String::new());
+    lines.push(format!("{:<10} {:<5} {:<18} {:>12} {:>12}  {}", "Date", "#", "Type", "Amount", "Balance", "Description"));
+    lines.push(format!("{:<10} {:<5} {:<18} {:>12} {:>12}", statement.period_start, "", "Opening balance", "", currency.format(statement.opening_balance)));
+    for line in &statement.lines {
+        lines.push(format!("{:<10} {:<5} {:<18} {:>12} {:>12}  {}", line.posted_at.format("%Y-%m-%d"), line.transaction, line.kind.description(), currency.format(line.amount), currency.format(line.balance), line.memo));
+    }
+    lines.push(format!("{:<10} {:<5} {:<18} {:>12} {:>12}", statement.last_day(), "", "Closing balance", "", currency.format(statement.closing_balance)));
+    lines.push(String::new());
+    lines.push(format!("Money in: {}  Money out: {}  Fees: {}  Interest: {}", currency.format(statement.money_in()), currency.format(statement.money_out()), currency.format(statement.fees()), currency.format(statement.interest())));
+    return lines.join("\n") + "\n";
+}
+
+// One row per transaction between the opening and closing balance rows. Amounts are plain
+// decimals so spreadsheets read them as numbers.
+fn render_csv(statement: &Statement) -> String {
+    let currency = statement.currency;
+    let mut rows: Vec<String> = vec![String::from("date,transaction_id,type,description,amount,balance")];
+    rows.push(format!("{},,Opening balance,,,{}", statement.period_start, currency.format_decimal(statement.opening_balance)));
+    for line in &statement.lines {
+        rows.push(format!(
+            "{},{},{},{},{},{}",
+            line.posted_at.format("%Y-%m-%d"), line.transaction, audit::csv_field(line.kind.description()), audit::csv_field(&line.memo),
+            currency.format_decimal(line.amount), currency.format_decimal(line.balance),
+        ));
+    }
+    rows.push(format!("{},,Closing balance,,,{}", statement.last_day(), currency.format_decimal(statement.closing_balance)));
+    return rows.join("\n") + "\n";
+}
+
+fn render_html(statement: &Statement) -> String {
+    let currency = statement.currency;
+    let holder = &statement.holder;
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Statement {} {}</title>\n", statement.money_account, html_escape(&statement.period_label())));
+    html.push_str("<style>\nbody { font-family: sans-serif; margin: 2em; }\ntable { border-collapse: collapse; width: 100%; }\nth, td { border-bottom: 1px solid #ccc; padding: 4px 8px; text-align: left; }\ntd.amount, th.amount { text-align: right; }\n</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>Bomanis Bank statement, {}</h1>\n", html_escape(&statement.period_label())));
+    html.push_str(&format!("<p>{} (customer {})<br>\n", html_escape(&holder.name), holder.customer));
+    if let Some(company) = &holder.company {
+        html.push_str(&format!("{}<br>\n", html_escape(company)));
+    }
+    html.push_str(&format!("{}<br>\n{}</p>\n", html_escape(&holder.address), html_escape(&holder.email)));
+    html.push_str(&format!("<p>{} \"{}\" ({}), {}<br>\nPeriod: {} to {}</p>\n", statement.account_type, html_escape(&statement.account_name), statement.money_account, currency.code(), statement.period_start, statement.last_day()));
+    html.push_str("<table>\n<tr><th>Date</th><th>#</th><th>Type</th><th>Description</th><th class=\"amount\">Amount</th><th class=\"amount\">Balance</th></tr>\n");
+    html.push_str(&format!("<tr><td>{}</td><td></td><td>Opening balance</td><td></td><td></td><td class=\"amount\">{}</td></tr>\n", statement.period_start, html_escape(&currency.format(statement.opening_balance))));
+    for line in &statement.lines {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            line.posted_at.format("%Y-%m-%d"), line.transaction, line.kind.description(), html_escape(&line.memo),
+            html_escape(&currency.format(line.amount)), html_escape(&currency.format(line.balance)),
+        ));
+    }
+    html.push_str(&format!("<tr><td>{}</td><td></td><td>Closing balance</td><td></td><td></td><td class=\"amount\">{}</td></tr>\n</table>\n", statement.last_day(), html_escape(&currency.format(statement.closing_balance))));
+    html.push_str(&format!(
+        "<p>Money in: {} &middot; Money out: {} &middot; Fees: {} &middot; Interest: {}</p>\n",
+        html_escape(&currency.format(statement.money_in())), html_escape(&currency.format(statement.money_out())),
+        html_escape(&currency.format(statement.fees())), html_escape(&currency.format(statement.interest())),
+    ));
+    html.push_str(&format!("<p><small>Issued {}</small></p>\n</body>\n</html>\n", statement.issued_at.format("%Y-%m-%d %H:%M")));
+    return html;
+}
+
+fn html_escape(value: &str) -> String {
+    return value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn at(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2026, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+    }
+
+    // Monday 2 March 2026, with one verified customer who has a USD checking account.
+    fn bank_with_account() -> (Rc<ManualClock>, DatabaseHandler, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(at(3, 2, 9)));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let id = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        return (clock, database, id);
+    }
+
+    // Each line's balance is the one before it plus its amount, from the opening balance down to the closing one.
+    fn assert_running_balance(statement: &Statement) {
+        let mut balance = statement.opening_balance;
+        for line in &statement.lines {
+            balance += line.amount;
+            assert_eq!(line.balance, balance);
+        }
+        assert_eq!(statement.closing_balance, balance);
+    }
+
+    #[test]
+    fn statements_carry_the_running_balance_from_one_cycle_to_the_next() {
+        let (clock, mut database, id) = bank_with_account();
+        database.deposit(id, 50000, DepositChannel::Cash, None).unwrap();
+        database.withdraw(id, 12000, None).unwrap();
+        clock.advance(Duration::days(18));
+        database.deposit(id, 3000, DepositChannel::Cash, None).unwrap();
+
+        // midnight on 1 April: posted now, it belongs to April's statement
+        clock.set(at(4, 1, 0));
+        database.deposit(id, 700, DepositChannel::Cash, None).unwrap();
+        database.tick();
+        clock.advance(Duration::days(10));
+        database.withdraw(id, 5000, None).unwrap();
+        clock.set(at(5, 1, 9));
+        database.tick();
+
+        let statements = database.get_statements(id);
+        assert_eq!(statements.len(), 2);
+        let (march, april) = (statements[0], statements[1]);
+        assert_eq!(march.opening_balance, 0);
+        assert_eq!(march.lines.iter().map(|line| line.amount).collect::<Vec<i32>>(), vec![50000, -12000, 3000]);
+        assert_eq!(march.closing_balance, 41000);
+        assert_eq!((march.money_in(), march.money_out()), (53000, 12000));
+        assert_running_balance(march);
+        let csv = march.render(StatementFormat::Csv);
+        assert!(csv.contains(",-120.00,380.00\n"));
+        assert!(csv.ends_with("2026-03-31,,Closing balance,,,410.00\n"));
+
+        assert_eq!(april.opening_balance, march.closing_balance);
+        assert_eq!(april.lines.first().map(|line| line.amount), Some(700));
+        assert_running_balance(april);
+        let last_in_april = database.get_transaction_history(id).into_iter().rfind(|txn| txn.posted_at < at(5, 1, 0)).unwrap().balance_after;
+        assert_eq!(april.closing_balance, last_in_april);
+    }
+}
@@ -0,0 +1,271 @@
+use chrono::{Months, NaiveDate};
+
+use crate::bomanis_bank::account::{BankAccountID, MoneyAccountID};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum InterestPayment {
+    AtMaturity, // added to the deposit when the term ends
+    Monthly, // paid out to the linked account every month
+}
+
+impl InterestPayment {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            InterestPayment::AtMaturity => "Interest at maturity",
+            InterestPayment::Monthly => "Monthly interest",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum MaturityInstruction {
+    Rollover,
+    PayOut,
+}
+
+impl MaturityInstruction {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            MaturityInstruction::Rollover => "Roll over for another term",
+            MaturityInstruction::PayOut => "Pay out to linked account",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TermDepositStatus {
+    Active,
+    Matured,
+    ClosedEarly,
+}
+
+impl TermDepositStatus {
+    pub fn description(self: &Self) -> &'static str {
+        match self {
+            TermDepositStatus::Active => "Active",
+            TermDepositStatus::Matured => "Matured",
+            TermDepositStatus::ClosedEarly => "Closed early",
+        }
+    }
+}
+
+// What the customer chooses when opening a term deposit.
+#[derive(Copy, Clone)]
+pub struct TermDepositTerms {
+    pub principal: i32, // minor units of the funding account's currency
+    pub term_months: u32,
+    pub annual_rate: f64, // e.g. 0.035 for 3.5%
+    pub interest_payment: InterestPayment,
+    pub at_maturity: MaturityInstruction,
+}
+
+#[derive(Copy, Clone)]
+pub struct TermDepositPolicy {
+    pub min_principal: i32,
+    pub min_term_months: u32,
+    pub max_term_months: u32,
+    pub offered_rate: f64, // annual rate offered to customers opening a deposit in the session
+    pub early_closure_penalty_days: u32, // days of interest on the principal forfeited on early closure
+}
+
+impl Default for TermDepositPolicy {
+    fn default() -> Self {
+        return TermDepositPolicy {
+            min_principal: 100000,
+            min_term_months: 1,
+            max_term_months: 120,
+            offered_rate: 0.035,
+            early_closure_penalty_days: 90,
+        }
+    }
+}
+
+// What closing a term deposit today would pay out.
+pub struct EarlyClosureQuote {
+    pub accrued_interest: i32,
+    pub penalty: i32,
+    pub payout: i32,
+}
+
+pub struct TermDeposit {
+    pub money_account: MoneyAccountID,
+    pub owner: BankAccountID,
+    pub linked_account: MoneyAccountID, // checking account that funded it and receives payouts
+    pub terms: TermDepositTerms,
+    pub term_start: NaiveDate,
+    pub maturity_date: NaiveDate,
+    pub periods_paid: u32, // monthly interest payments made this term
+    pub interest_paid: i32, // this term
+    pub status: TermDepositStatus,
+}
+
+impl TermDeposit {
+    pub fn total_interest(self: &Self) -> i32 {
+        let terms = &self.terms;
+        return (terms.principal as f64 * terms.annual_rate * terms.term_months as f64 / 12.0).round() as i32;
+    }
+
+    // Date the next interest payment falls due. With interest at maturity that is the maturity date.
+    pub fn next_interest_on(self: &Self) -> NaiveDate {
+        match self.terms.interest_payment {
+            InterestPayment::AtMaturity => self.maturity_date,
+            InterestPayment::Monthly => add_months(self.term_start, self.periods_paid + 1).unwrap_or(self.maturity_date),
+        }
+    }
+
+    // Interest for the period ending on `next_interest_on`. The last monthly payment picks up
+    // any rounding difference so a term always pays `total_interest`.
+    pub fn interest_due(self: &Self) -> i32 {
+        if self.terms.interest_payment == InterestPayment::Monthly && self.next_interest_on() < self.maturity_date {
+            return (self.terms.principal as f64 * self.terms.annual_rate / 12.0).round() as i32;
+        }
+        return self.total_interest() - self.interest_paid;
+    }
+
+    // Simple interest earned since the last payment, by the day.
+    pub fn accrued_interest(self: &Self, today: NaiveDate) -> i32 {
+        let since = match self.terms.interest_payment {
+            InterestPayment::AtMaturity => self.term_start,
+            InterestPayment::Monthly => add_months(self.term_start, self.periods_paid).unwrap_or(self.maturity_date),
+        };
+        let days = (today.min(self.maturity_date) - since).num_days().max(0);
+        return (self.terms.principal as f64 * self.terms.annual_rate * days as f64 / 365.0).round() as i32;
+    }
+
+    pub fn early_closure_penalty(self: &Self, policy: &TermDepositPolicy) -> i32 {
+        return (self.terms.principal as f64 * self.terms.annual_rate * policy.early_closure_penalty_days as f64 / 365.0).round() as i32;
+    }
+
+    pub fn is_due(self: &Self, today: NaiveDate) -> bool {
+        return self.status == TermDepositStatus::Active && today >= self.next_interest_on();
+    }
+}
+
+// Month arithmetic that clamps to the end of shorter months, e.g. Jan 31 + 1 month is Feb 28.
+// None past the last date chrono can represent.
+pub fn add_months(date: NaiveDate, months: u32) -> Option<NaiveDate> {
+    return date.checked_add_months(Months::new(months));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::Duration;
+    use crate::bomanis_bank::account::MoneyAccountType;
+    use crate::bomanis_bank::clearing::DepositChannel;
+    use crate::bomanis_bank::clock::{ManualClock, SeededRandom};
+    use crate::bomanis_bank::currency::Currency;
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::TermDepositError;
+    use crate::bomanis_bank::ledger::TransactionKind;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+    use crate::bomanis_bank::secret::SecretString;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    }
+
+    // A customer with 10,000.00 in checking on Monday 2 March 2026.
+    fn bank_with_checking() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID, MoneyAccountID) {
+        let clock = Rc::new(ManualClock::new(date(2026, 3, 2).and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let ptr_bank_account = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: date(1990, 6, 15),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap();
+        let owner = ptr_bank_account.borrow().id;
+        let checking = database.make_new_money_account(ptr_bank_account, String::from("Everyday"), MoneyAccountType::Checking, Currency::Usd).borrow().id;
+        database.deposit(checking, 1000000, DepositChannel::Cash, None).unwrap();
+        return (clock, database, owner, checking);
+    }
+
+    fn terms(principal: i32, term_months: u32, annual_rate: f64, interest_payment: InterestPayment, at_maturity: MaturityInstruction) -> TermDepositTerms {
+        return TermDepositTerms { principal: principal, term_months: term_months, annual_rate: annual_rate, interest_payment: interest_payment, at_maturity: at_maturity };
+    }
+
+    fn balance(database: &DatabaseHandler, id: MoneyAccountID) -> i32 {
+        return database.get_money_account_with_id_u32(id).unwrap().borrow().get_balance();
+    }
+
+    #[test]
+    fn months_are_clamped_and_bounded() {
+        assert_eq!(add_months(date(2026, 1, 31), 1), Some(date(2026, 2, 28)));
+        assert_eq!(add_months(date(2026, 1, 31), 2), Some(date(2026, 3, 31)));
+        assert_eq!(add_months(NaiveDate::MAX, 1), None);
+    }
+
+    #[test]
+    fn matured_deposit_is_paid_out_with_its_interest() {
+        let (clock, mut database, owner, checking) = bank_with_checking();
+        let id = database.open_term_deposit(owner, checking, String::from("Fixed"), terms(200000, 3, 0.04, InterestPayment::AtMaturity, MaturityInstruction::PayOut)).unwrap();
+        assert_eq!(balance(&database, checking), 800000);
+
+        clock.set(date(2026, 6, 1).and_hms_opt(9, 0, 0).unwrap());
+        database.process_term_deposits();
+        assert!(database.get_term_deposit(id).unwrap().status == TermDepositStatus::Active);
+
+        clock.advance(Duration::days(1));
+        database.process_term_deposits();
+        assert!(database.get_term_deposit(id).unwrap().status == TermDepositStatus::Matured);
+        assert_eq!((balance(&database, id), balance(&database, checking)), (0, 1002000));
+        assert!(database.take_notifications(owner).iter().any(|message| message.contains("has matured")));
+    }
+
+    #[test]
+    fn rollover_catches_up_over_several_terms() {
+        let (clock, mut database, owner, checking) = bank_with_checking();
+        let id = database.open_term_deposit(owner, checking, String::from("Rolling"), terms(200000, 1, 0.12, InterestPayment::AtMaturity, MaturityInstruction::Rollover)).unwrap();
+
+        clock.set(date(2026, 6, 2).and_hms_opt(9, 0, 0).unwrap());
+        database.process_term_deposits();
+        // each term's interest is rolled into the next term's principal: 2000, 2020, 2040
+        let deposit = database.get_term_deposit(id).unwrap();
+        assert!(deposit.status == TermDepositStatus::Active);
+        assert_eq!((deposit.term_start, deposit.maturity_date), (date(2026, 6, 2), date(2026, 7, 2)));
+        assert_eq!((deposit.terms.principal, deposit.interest_paid), (206060, 0));
+        assert_eq!(balance(&database, id), 206060);
+        assert_eq!(balance(&database, checking), 800000);
+        assert_eq!(database.take_notifications(owner).iter().filter(|message| message.contains("rolled over")).count(), 3);
+    }
+
+    #[test]
+    fn monthly_interest_goes_to_the_linked_account() {
+        let (clock, mut database, owner, checking) = bank_with_checking();
+        let id = database.open_term_deposit(owner, checking, String::from("Income"), terms(100000, 3, 0.05, InterestPayment::Monthly, MaturityInstruction::PayOut)).unwrap();
+
+        clock.set(date(2026, 4, 2).and_hms_opt(9, 0, 0).unwrap());
+        database.process_term_deposits();
+        assert_eq!(balance(&database, checking), 900417);
+        assert_eq!(database.get_term_deposit(id).unwrap().next_interest_on(), date(2026, 5, 2));
+
+        clock.set(date(2026, 6, 2).and_hms_opt(9, 0, 0).unwrap());
+        database.process_term_deposits();
+        // the last payment takes the rounding difference, so the term pays exactly 12.50
+        let interest: Vec<i32> = database.get_transaction_history(checking).iter().filter(|txn| txn.kind == TransactionKind::Interest).map(|txn| txn.amount).collect();
+        assert_eq!(interest, vec![417, 417, 416]);
+        assert_eq!(database.get_term_deposit(id).unwrap().interest_paid, 1250);
+        assert_eq!((balance(&database, id), balance(&database, checking)), (0, 1001250));
+    }
+
+    #[test]
+    fn early_closure_pays_accrued_interest_less_the_penalty() {
+        let (clock, mut database, owner, checking) = bank_with_checking();
+        let id = database.open_term_deposit(owner, checking, String::from("Fixed"), terms(365000, 12, 0.1, InterestPayment::AtMaturity, MaturityInstruction::PayOut)).unwrap();
+
+        clock.advance(Duration::days(73));
+        let quote = database.quote_early_closure(owner, id).unwrap();
+        assert_eq!((quote.accrued_interest, quote.penalty, quote.payout), (7300, 9000, 363300)); // 73 days earned, 90 forfeited
+        assert_eq!(database.close_term_deposit_early(owner, id), Ok(363300));
+        assert!(database.get_term_deposit(id).unwrap().status == TermDepositStatus::ClosedEarly);
+        assert_eq!((balance(&database, id), balance(&database, checking)), (0, 998300));
+        assert_eq!(database.close_term_deposit_early(owner, id), Err(TermDepositError::NotActive));
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::bomanis_bank::clock::RandomSource;
+use crate::bomanis_bank::currency::{Currency, CurrencyAmounts};
+use crate::bomanis_bank::secret::{SecretBytes, SecretString};
+
+const SECRET_LENGTH: usize = 20; // 160 bits, as RFC 4226 recommends
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789"; // nothing that reads like something else
+
+#[derive(Copy, Clone)]
+pub struct TwoFactorPolicy {
+    pub issuer: &'static str, // shown by the authenticator app
+    pub digits: u32,
+    pub step_seconds: u64,
+    pub allowed_drift_steps: u64, // codes this many steps either side of now are accepted too
+    pub recovery_code_count: usize,
+    pub step_up_amount: CurrencyAmounts, // withdrawals, payments and transfers from this amount need a fresh code
+    pub max_failed_attempts: u32, // wrong codes in a row before the second factor is locked
+    pub lockout_minutes: i64,
+}
+
+impl TwoFactorPolicy {
+    // HOTP truncates to a 31-bit number, so more than 9 digits cannot be represented.
+    pub fn is_valid(self: &Self) -> bool {
+        return (6..=9).contains(&self.digits) && self.step_seconds > 0 && self.max_failed_attempts > 0 && self.lockout_minutes > 0;
+    }
+}
+
+impl Default for TwoFactorPolicy {
+    fn default() -> Self {
+        return TwoFactorPolicy {
+            issuer: "Bomanis Bank",
+            digits: 6,
+            step_seconds: 30,
+            allowed_drift_steps: 1,
+            recovery_code_count: 10,
+            step_up_amount: CurrencyAmounts::new([
+                (Currency::Usd, 100000),
+                (Currency::Eur, 100000),
+                (Currency::Gbp, 100000),
+                (Currency::Sek, 1000000),
+                (Currency::Chf, 100000),
+                (Currency::Jpy, 150000),
+            ]),
+            max_failed_attempts: 5,
+            lockout_minutes: 15,
+        }
+    }
+}
+
+// What the customer needs to set up their authenticator app.
+pub struct TwoFactorEnrollment {
+    pub secret: SecretString, // base32, for typing in by hand
+    pub uri: SecretString, // carries the secret too
+}
+
+pub struct TwoFactor {
+    pub secret: SecretBytes,
+    pub confirmed: bool, // false until the customer has entered a code from their app
+    pub last_used_step: Option<u64>, // a code is only good once
+    pub recovery_codes: Vec<SecretString>, // removed as they are used
+    pub failed_attempts: u32, // wrong codes since the last right one
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+impl TwoFactor {
+    pub fn new(secret: SecretBytes) -> Self {
+        return TwoFactor {
+            secret: secret,
+            confirmed: false,
+            last_used_step: None,
+            recovery_codes: vec![],
+            failed_attempts: 0,
+            locked_until: None,
+        }
+    }
+
+    pub fn is_locked(self: &Self, now: NaiveDateTime) -> bool {
+        return self.locked_until.is_some_and(|until| now < until);
+    }
+
+    pub fn secret_base32(self: &Self) -> SecretString {
+        return SecretString::new(base32::encode(base32::Alphabet::RFC4648 { padding: false }, self.secret.expose()));
+    }
+
+    // Time step the code was generated in, if it is valid now and has not been used before.
+    pub fn matching_step(self: &Self, code: &str, unix_time: i64, policy: &TwoFactorPolicy) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != policy.digits as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let current_step = unix_time.max(0) as u64 / policy.step_seconds;
+        let first_step = current_step.saturating_sub(policy.allowed_drift_steps);
+        return (first_step..=current_step + policy.allowed_drift_steps)
+            .filter(|step| self.last_used_step.is_none_or(|last_used| *step > last_used))
+            .find(|step| hotp(self.secret.expose(), *step, policy.digits) == code);
+    }
+
+    // Removes the recovery code if it is one, so it cannot be used again.
+    pub fn use_recovery_code(self: &mut Self, code: &str) -> bool {
+        let code = code.trim().to_lowercase();
+        match self.recovery_codes.iter().position(|recovery_code| recovery_code.expose() == code) {
+            Some(index) => {
+                self.recovery_codes.remove(index);
+                return true;
+            },
+            None => return false,
+        }
+    }
+}
+
+pub fn generate_secret(rng: &mut dyn RandomSource) -> SecretBytes {
+    let mut secret = vec![0u8; SECRET_LENGTH];
+    rng.fill_bytes(&mut secret);
+    return SecretBytes::new(secret);
+}
+
+// Codes like "k7m2-x9qp", easy to read out and type.
+pub fn generate_recovery_codes(rng: &mut dyn RandomSource, count: usize) -> Vec<SecretString> {
+    let mut codes: Vec<SecretString> = vec![];
+    while codes.len() < count {
+        let code: String = (0..9).map(|i| {
+            if i == 4 {
+                return '-';
+            }
+            return RECOVERY_CODE_ALPHABET[rng.gen_range(0, RECOVERY_CODE_ALPHABET.len() as u32) as usize] as char;
+        }).collect();
+        let code = SecretString::new(code);
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    return codes;
+}
+
+// RFC 4226 HOTP with HMAC-SHA1. TOTP (RFC 6238) is this with the counter set to the time step.
+// `digits` has to be at most 9, see TwoFactorPolicy::is_valid.
+pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).unwrap();
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    return format!("{:0width$}", binary % 10u32.pow(digits), width = digits as usize);
+}
+
+// Key URI for authenticator apps, usually shown as a QR code.
+// https://github.com/google/google-authenticator/wiki/Key-Uri-Format
+pub fn otpauth_uri(two_factor: &TwoFactor, account_name: &str, policy: &TwoFactorPolicy) -> SecretString {
+    let issuer = percent_encode(policy.issuer);
+    return SecretString::new(format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        issuer, percent_encode(account_name), two_factor.secret_base32().expose(), issuer, policy.digits, policy.step_seconds
+    ));
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    return encoded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::{Duration, NaiveDate};
+    use crate::bomanis_bank::account::BankAccountID;
+    use crate::bomanis_bank::clock::{Clock, ManualClock, SeededRandom};
+    use crate::bomanis_bank::database::DatabaseHandler;
+    use crate::bomanis_bank::errors::TwoFactorError;
+    use crate::bomanis_bank::onboarding::{Application, ContactDetails};
+    use crate::bomanis_bank::passwords::PasswordPolicy;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    fn bank_with_customer() -> (Rc<ManualClock>, DatabaseHandler, BankAccountID) {
+        let clock = Rc::new(ManualClock::new(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+        let mut database = DatabaseHandler::with_providers(clock.clone(), Box::new(SeededRandom::new(7)));
+        database.set_password_policy(PasswordPolicy { hash_iterations: 1_000, ..PasswordPolicy::default() });
+        let owner = database.register_customer(Application {
+            user_id: String::from("alice"),
+            password: SecretString::from("Correct-Horse-42"),
+            name: String::from("Alice Example"),
+            dob: NaiveDate::from_ymd_opt(1990, 6, 15).unwrap(),
+            contact: ContactDetails { email: String::from("alice@example.com"), phone: String::from("+46701234567"), address: String::from("1 Test Street") },
+            id_document: String::from("ALICE1234"),
+            business: None,
+        }).unwrap().borrow().id;
+        return (clock, database, owner);
+    }
+
+    // Sets up two-factor the way an authenticator app would, from the base32 secret shown to the customer.
+    fn enable_two_factor(clock: &ManualClock, database: &mut DatabaseHandler, owner: BankAccountID) -> Vec<u8> {
+        let enrollment = database.begin_two_factor_enrollment(owner).unwrap();
+        let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, enrollment.secret.expose()).unwrap();
+        let code = hotp(&secret, clock.unix_timestamp() as u64 / 30, 6);
+        database.confirm_two_factor_enrollment(owner, &code).unwrap();
+        return secret;
+    }
+
+    // RFC 4226 appendix D
+    #[test]
+    fn hotp_matches_rfc_4226() {
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(RFC_SECRET, counter as u64, 6), *code);
+        }
+    }
+
+    // RFC 6238 appendix B, the SHA-1 rows
+    #[test]
+    fn totp_matches_rfc_6238() {
+        let expected: [(u64, &str); 6] = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        for (unix_time, code) in expected {
+            assert_eq!(hotp(RFC_SECRET, unix_time / 30, 8), code);
+        }
+    }
+
+    #[test]
+    fn code_is_accepted_within_drift_and_only_once() {
+        let policy = TwoFactorPolicy::default();
+        let mut two_factor = TwoFactor::new(SecretBytes::new(RFC_SECRET.to_vec()));
+        let code = hotp(RFC_SECRET, 1111111111 / 30 - 1, 6);
+        let step = two_factor.matching_step(&code, 1111111111, &policy);
+        assert_eq!(step, Some(1111111111 / 30 - 1));
+        two_factor.last_used_step = step;
+        assert_eq!(two_factor.matching_step(&code, 1111111111, &policy), None);
+        assert_eq!(two_factor.matching_step(&hotp(RFC_SECRET, 1111111111 / 30 - 2, 6), 1111111111, &policy), None);
+    }
+
+    #[test]
+    fn policy_rejects_digits_hotp_cannot_produce() {
+        assert!(TwoFactorPolicy::default().is_valid());
+        assert!(TwoFactorPolicy { digits: 9, ..TwoFactorPolicy::default() }.is_valid());
+        assert!(!TwoFactorPolicy { digits: 10, ..TwoFactorPolicy::default() }.is_valid());
+        assert!(!TwoFactorPolicy { digits: 4, ..TwoFactorPolicy::default() }.is_valid());
+        assert!(!TwoFactorPolicy { step_seconds: 0, ..TwoFactorPolicy::default() }.is_valid());
+    }
+
+    #[test]
+    fn second_factor_locks_after_too_many_wrong_codes() {
+        let (clock, mut database, owner) = bank_with_customer();
+        let secret = enable_two_factor(&clock, &mut database, owner);
+        for _ in 0..4 {
+            assert_eq!(database.verify_second_factor(owner, "000000"), Err(TwoFactorError::InvalidCode));
+        }
+        assert_eq!(database.verify_second_factor(owner, "000000"), Err(TwoFactorError::Locked));
+        clock.advance(Duration::minutes(1));
+        let code = hotp(&secret, clock.unix_timestamp() as u64 / 30, 6);
+        assert_eq!(database.verify_second_factor(owner, &code), Err(TwoFactorError::Locked));
+        clock.advance(Duration::minutes(15));
+        let code = hotp(&secret, clock.unix_timestamp() as u64 / 30, 6);
+        assert!(database.verify_second_factor(owner, &code).is_ok());
+    }
+
+    #[test]
+    fn step_up_amount_depends_on_the_currency() {
+        let (clock, mut database, owner) = bank_with_customer();
+        assert!(!database.requires_step_up(owner, 500000, Currency::Usd));
+        enable_two_factor(&clock, &mut database, owner);
+        assert!(database.requires_step_up(owner, 100000, Currency::Usd));
+        assert!(!database.requires_step_up(owner, 100000, Currency::Sek));
+        assert_eq!(database.set_two_factor_policy(TwoFactorPolicy { digits: 12, ..TwoFactorPolicy::default() }), Err(TwoFactorError::InvalidPolicy));
+    }
+}
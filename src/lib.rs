@@ -0,0 +1,2 @@
+mod bomanis_bank;
+pub use bomanis_bank::*;
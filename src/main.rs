@@ -1,6 +1,4 @@
-mod bomanis_bank;
-
 fn main() {
     let mut session = bomanis_bank::Session::new();
-    session.start();
+    let _ = session.start();
 }